@@ -2,6 +2,7 @@ class HelloWorld {
     private static native String hello(String input);
     private static native byte[] helloByte(byte[] input);
     private static native void factAndCallMeBack(int n, HelloWorld callback);
+    private static native int callThrowingCallback(HelloWorld callback);
 
     private static native long counterNew(HelloWorld callback);
     private static native void counterIncrement(long counter_ptr);
@@ -23,6 +24,12 @@ class HelloWorld {
 
         HelloWorld.factAndCallMeBack(6, new HelloWorld());
 
+        try {
+            HelloWorld.callThrowingCallback(new HelloWorld());
+        } catch (IllegalStateException e) {
+            System.out.println("caught exception thrown through native code: " + e.getMessage());
+        }
+
         long counter_ptr = counterNew(new HelloWorld());
 
         for (int i = 0; i < 5; i++) {
@@ -39,6 +46,10 @@ class HelloWorld {
       System.out.println("factCallback: res = " + res);
     }
 
+    public void throwingCallback() {
+      throw new IllegalStateException("thrown from throwingCallback");
+    }
+
     public void counterCallback(int count) {
       System.out.println("counterCallback: count = " + count);
     }
@@ -72,6 +72,26 @@ pub extern "system" fn Java_HelloWorld_factAndCallMeBack(env: JNIEnv,
     env.call_method(callback, "factCallback", "(I)V", &[res.into()]).unwrap();
 }
 
+#[no_mangle]
+#[allow(non_snake_case)]
+pub extern "system" fn Java_HelloWorld_callThrowingCallback(env: JNIEnv,
+                                                            _class: JClass,
+                                                            callback: JObject)
+                                                            -> jint {
+    // The callback throws. `call_method` reports that as an error, but the
+    // exception itself stays pending.
+    let _ = env.call_method(callback, "throwingCallback", "()V", &[]);
+
+    // Don't clear it: return right away and the JVM rethrows it to whoever
+    // called `callThrowingCallback` on the java side. The return value is
+    // ignored in that case.
+    if env.propagate_pending_exception().is_err() {
+        return 0;
+    }
+
+    1
+}
+
 struct Counter {
     count: i32,
     callback: GlobalRef,
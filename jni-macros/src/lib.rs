@@ -0,0 +1,560 @@
+//! # Procedural macros for the `jni` crate
+//!
+//! `#[jni_export]` turns a plain rust function into a java `native` method
+//! implementation, writing the `Java_<class>_<method>` wrapper for you:
+//!
+//! ```rust,ignore
+//! #[macro_use]
+//! extern crate jni;
+//! extern crate jni_macros;
+//!
+//! use jni::JNIEnv;
+//! use jni::errors::Result;
+//! use jni_macros::jni_export;
+//!
+//! // class HelloWorld {
+//! //     private static native String hello(String input);
+//! // }
+//! #[jni_export(class = "HelloWorld")]
+//! fn hello(_env: &JNIEnv, input: String) -> Result<String> {
+//!     Ok(format!("Hello, {}!", input))
+//! }
+//! ```
+//!
+//! expands to the function as written plus
+//!
+//! ```rust,ignore
+//! #[no_mangle]
+//! pub extern "system" fn Java_HelloWorld_hello(env: JNIEnv, _this: jobject, input: jobject) -> jobject {
+//!     // convert the arguments, call `hello`, convert its return value,
+//!     // turning errors and panics into java exceptions
+//! }
+//! ```
+//!
+//! `#[derive(IntoJava, FromJava)]` implements `jni::IntoJava` and
+//! `jni::FromJava` for a struct, mapping its fields to the fields of a java
+//! class. See `jni::IntoJava` for the attributes they take.
+//!
+//! The generated code refers to the `jni` crate, which must be a dependency
+//! of the crate using the macros.
+
+extern crate proc_macro;
+extern crate proc_macro2;
+#[macro_use]
+extern crate quote;
+extern crate syn;
+
+use proc_macro::TokenStream;
+
+use proc_macro2::{
+    Span,
+    TokenStream as TokenStream2,
+};
+
+use syn::{
+    Attribute,
+    Data,
+    DeriveInput,
+    Fields,
+    FnArg,
+    GenericArgument,
+    Ident,
+    ItemFn,
+    LitStr,
+    Pat,
+    PathArguments,
+    ReturnType,
+    Type,
+};
+
+/// Export a rust function as the implementation of a java `native` method.
+///
+/// * `class` (required) is the fully qualified name of the java class, with
+///   dots or slashes: `"com.example.HelloWorld"`.
+/// * `name` is the name of the java method, if it differs from the rust
+///   function's: `#[jni_export(class = "com.example.HelloWorld", name =
+///   "sayHello")]`.
+///
+/// The function may take a `&JNIEnv` as its first parameter. All other
+/// parameters must implement `jni::NativeArg`, and the return type
+/// `jni::IntoNativeReturn`, or be a `Result` of such a type with an error
+/// implementing `jni::IntoJavaException`. That covers the primitive types,
+/// `bool`, `JObject`, `JString`, `JClass`, `String`, `Vec<u8>` (for
+/// `byte[]`) and `Option` of the object types.
+///
+/// The receiver of the native method (the object, or the class for static
+/// methods) isn't passed to the function; write the wrapper by hand if you
+/// need it.
+///
+/// Like `native_try!`, errors returned by the function are thrown as java
+/// exceptions, panics are thrown as a `RuntimeException`, and java gets a
+/// zero value of the return type in both cases.
+///
+/// The JNI type of an argument or return value is picked from how its type
+/// is written: the primitive type names map to themselves, `bool` to
+/// `jboolean`, and everything else to `jobject`. A type alias of a primitive
+/// type therefore must be written out.
+#[proc_macro_attribute]
+pub fn jni_export(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let item = syn::parse_macro_input!(item as ItemFn);
+
+    let mut class = None;
+    let mut name = None;
+    let parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("class") {
+            class = Some(meta.value()?.parse::<LitStr>()?);
+            Ok(())
+        } else if meta.path.is_ident("name") {
+            name = Some(meta.value()?.parse::<LitStr>()?);
+            Ok(())
+        } else {
+            Err(meta.error("unsupported jni_export attribute, expected `class` or `name`"))
+        }
+    });
+    syn::parse_macro_input!(attr with parser);
+
+    let class = match class {
+        Some(class) => class,
+        None => {
+            return syn::Error::new(Span::call_site(), "jni_export needs a `class = \"...\"` attribute")
+                .to_compile_error()
+                .into()
+        }
+    };
+    let name = name.map(|name| name.value()).unwrap_or_else(|| item.sig.ident.to_string());
+
+    match expand(&item, &class.value(), &name) {
+        Ok(wrapper) => quote!(#item #wrapper).into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn expand(item: &ItemFn, class: &str, name: &str) -> syn::Result<TokenStream2> {
+    let sig = &item.sig;
+    if !sig.generics.params.is_empty() || sig.asyncness.is_some() || sig.variadic.is_some() {
+        return Err(syn::Error::new_spanned(
+            sig,
+            "jni_export functions can't be generic, async or variadic",
+        ));
+    }
+
+    let mut inputs = sig.inputs.iter().peekable();
+
+    // an optional leading `&JNIEnv`
+    let mut call_args = Vec::new();
+    if let Some(&FnArg::Typed(ref arg)) = inputs.peek() {
+        if is_env(&arg.ty)? {
+            call_args.push(quote!(&env));
+            inputs.next();
+        }
+    }
+
+    let mut raw_args = Vec::new();
+    let mut conversions = Vec::new();
+    for (i, input) in inputs.enumerate() {
+        let arg = match *input {
+            FnArg::Typed(ref arg) => arg,
+            FnArg::Receiver(ref recv) => {
+                return Err(syn::Error::new_spanned(recv, "jni_export functions can't take self"))
+            }
+        };
+        let ident = match *arg.pat {
+            Pat::Ident(ref pat) => pat.ident.clone(),
+            _ => Ident::new(&format!("arg{}", i), Span::call_site()),
+        };
+        let raw = raw_type(&arg.ty);
+        raw_args.push(quote!(#ident: #raw));
+        conversions.push(quote! {
+            let #ident = ::jni::NativeArg::from_native_arg(&env, #ident)?;
+        });
+        call_args.push(quote!(#ident));
+    }
+
+    let func = &sig.ident;
+    let call = quote!(#func(#( #call_args ),*));
+    let (raw_ret, body) = match sig.output {
+        ReturnType::Default => (None, quote! {
+            #call;
+            Ok(())
+        }),
+        ReturnType::Type(_, ref ty) => match result_ok_type(ty) {
+            Some(ok) => (raw_return_type(ok), quote! {
+                match #call {
+                    Ok(ret) => ::jni::IntoNativeReturn::into_native_return(ret, &env),
+                    Err(e) => {
+                        ::jni::throw_native_error(&env, &e);
+                        Ok(::jni::NativeReturn::zero())
+                    }
+                }
+            }),
+            None => (raw_return_type(ty), quote! {
+                ::jni::IntoNativeReturn::into_native_return(#call, &env)
+            }),
+        },
+    };
+    let ret = raw_ret.map(|raw| quote!(-> #raw));
+
+    let symbol = Ident::new(
+        &format!("Java_{}_{}", mangle(&class.replace('.', "/")), mangle(name)),
+        sig.ident.span(),
+    );
+
+    Ok(quote! {
+        #[no_mangle]
+        #[doc(hidden)]
+        #[allow(non_snake_case)]
+        pub extern "system" fn #symbol(
+            env: ::jni::JNIEnv,
+            _this: ::jni::sys::jobject,
+            #( #raw_args ),*
+        ) #ret {
+            ::jni::native_try(&env, || -> ::jni::errors::Result<_> {
+                #( #conversions )*
+                #body
+            })
+        }
+    })
+}
+
+/// Implement `jni::IntoJava` (and `jni::IntoJavaField`, so the struct can be
+/// a field of another one) by creating an instance of the java class given
+/// by `#[jni(class = "...")]` and setting its fields.
+#[proc_macro_derive(IntoJava, attributes(jni))]
+pub fn derive_into_java(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    match JavaStruct::parse(&input).map(|s| s.impl_into_java()) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Implement `jni::FromJava` (and `jni::FromJavaField`) by reading each
+/// field of the struct from the java object.
+#[proc_macro_derive(FromJava, attributes(jni))]
+pub fn derive_from_java(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    match JavaStruct::parse(&input).map(|s| s.impl_from_java()) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// A struct deriving `IntoJava` or `FromJava`, with its attributes.
+struct JavaStruct<'a> {
+    ident: &'a Ident,
+    class: String,
+    ctor: bool,
+    fields: Vec<JavaStructField<'a>>,
+}
+
+struct JavaStructField<'a> {
+    ident: &'a Ident,
+    ty: &'a Type,
+    name: String,
+    sig: Option<LitStr>,
+    getter: Option<LitStr>,
+}
+
+impl<'a> JavaStruct<'a> {
+    fn parse(input: &'a DeriveInput) -> syn::Result<JavaStruct<'a>> {
+        if !input.generics.params.is_empty() {
+            return Err(syn::Error::new_spanned(&input.generics, "java structs can't be generic"));
+        }
+        let named = match input.data {
+            Data::Struct(ref data) => match data.fields {
+                Fields::Named(ref named) => named,
+                _ => return Err(syn::Error::new_spanned(&input.ident, "java structs need named fields")),
+            },
+            _ => return Err(syn::Error::new_spanned(&input.ident, "only structs can be java structs")),
+        };
+
+        let mut class = None;
+        let mut ctor = false;
+        for attr in jni_attrs(&input.attrs) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("class") {
+                    class = Some(meta.value()?.parse::<LitStr>()?.value().replace('.', "/"));
+                    Ok(())
+                } else if meta.path.is_ident("ctor") {
+                    ctor = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported jni attribute, expected `class` or `ctor`"))
+                }
+            })?;
+        }
+        let class = match class {
+            Some(class) => class,
+            None => {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "java structs need a `#[jni(class = \"...\")]` attribute",
+                ))
+            }
+        };
+
+        let mut fields = Vec::new();
+        for field in &named.named {
+            let ident = field.ident.as_ref().unwrap();
+            let mut java_field = JavaStructField {
+                ident: ident,
+                ty: &field.ty,
+                name: ident.to_string(),
+                sig: None,
+                getter: None,
+            };
+            for attr in jni_attrs(&field.attrs) {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("name") {
+                        java_field.name = meta.value()?.parse::<LitStr>()?.value();
+                    } else if meta.path.is_ident("sig") {
+                        java_field.sig = Some(meta.value()?.parse()?);
+                    } else if meta.path.is_ident("getter") {
+                        java_field.getter = Some(meta.value()?.parse()?);
+                    } else {
+                        return Err(meta.error("unsupported jni attribute, expected `name`, `sig` or `getter`"));
+                    }
+                    Ok(())
+                })?;
+            }
+            fields.push(java_field);
+        }
+
+        Ok(JavaStruct {
+            ident: &input.ident,
+            class: class,
+            ctor: ctor,
+            fields: fields,
+        })
+    }
+
+    fn impl_into_java(&self) -> TokenStream2 {
+        let ident = self.ident;
+        let class = &self.class;
+        let descriptor = format!("L{};", class);
+
+        let build = if self.ctor {
+            let values = self.fields.iter().map(|field| {
+                let ident = field.ident;
+                quote!(::jni::IntoJavaField::to_jvalue(&self.#ident, env)?)
+            });
+            let descs = self.fields.iter().map(|field| field.descriptor(quote!(::jni::IntoJavaField)));
+            quote! {
+                let args = [#( #values ),*];
+                let mut sig = String::from("(");
+                #( sig.push_str(#descs); )*
+                sig.push_str(")V");
+                let obj = env.new_object(#class, sig, &args);
+                for arg in &args {
+                    match *arg {
+                        ::jni::objects::JValue::Object(arg) if !arg.is_null() => env.delete_local_ref(arg)?,
+                        _ => {}
+                    }
+                }
+                obj
+            }
+        } else {
+            let sets = self.fields.iter().map(|field| {
+                let ident = field.ident;
+                let name = &field.name;
+                let desc = field.descriptor(quote!(::jni::IntoJavaField));
+                quote! {
+                    let val = ::jni::IntoJavaField::to_jvalue(&self.#ident, env)?;
+                    let res = env.set_field(obj, #name, #desc, val);
+                    match val {
+                        ::jni::objects::JValue::Object(val) if !val.is_null() => env.delete_local_ref(val)?,
+                        _ => {}
+                    }
+                    res?;
+                }
+            });
+            quote! {
+                let obj = env.new_object(#class, "()V", &[])?;
+                #( #sets )*
+                Ok(obj)
+            }
+        };
+
+        quote! {
+            impl ::jni::IntoJava for #ident {
+                fn to_java<'e>(
+                    &self,
+                    env: &'e ::jni::JNIEnv<'e>,
+                ) -> ::jni::errors::Result<::jni::objects::JObject<'e>> {
+                    #build
+                }
+            }
+
+            impl ::jni::IntoJavaField for #ident {
+                fn descriptor() -> &'static str {
+                    #descriptor
+                }
+
+                fn to_jvalue<'e>(
+                    &self,
+                    env: &'e ::jni::JNIEnv<'e>,
+                ) -> ::jni::errors::Result<::jni::objects::JValue<'e>> {
+                    Ok(::jni::IntoJava::to_java(self, env)?.into())
+                }
+            }
+        }
+    }
+
+    fn impl_from_java(&self) -> TokenStream2 {
+        let ident = self.ident;
+        let descriptor = format!("L{};", self.class);
+
+        let reads = self.fields.iter().map(|field| {
+            let ident = field.ident;
+            let desc = field.descriptor(quote!(::jni::FromJavaField));
+            let read = match field.getter {
+                Some(ref getter) => quote! {
+                    env.call_method(obj, #getter, format!("(){}", #desc), &[])?
+                },
+                None => {
+                    let name = &field.name;
+                    quote!(env.get_field(obj, #name, #desc)?)
+                }
+            };
+            quote! {
+                #ident: {
+                    let val = #read;
+                    ::jni::FromJavaField::from_jvalue(env, val)?
+                }
+            }
+        });
+
+        quote! {
+            impl ::jni::FromJava for #ident {
+                fn from_java<'e>(
+                    env: &'e ::jni::JNIEnv<'e>,
+                    obj: ::jni::objects::JObject<'e>,
+                ) -> ::jni::errors::Result<Self> {
+                    Ok(#ident {
+                        #( #reads ),*
+                    })
+                }
+            }
+
+            impl ::jni::FromJavaField for #ident {
+                fn descriptor() -> &'static str {
+                    #descriptor
+                }
+
+                fn from_jvalue<'e>(
+                    env: &'e ::jni::JNIEnv<'e>,
+                    val: ::jni::objects::JValue<'e>,
+                ) -> ::jni::errors::Result<Self> {
+                    let obj = val.l()?;
+                    let res = <Self as ::jni::FromJava>::from_java(env, obj);
+                    if !obj.is_null() {
+                        env.delete_local_ref(obj)?;
+                    }
+                    res
+                }
+            }
+        }
+    }
+}
+
+impl<'a> JavaStructField<'a> {
+    /// The type signature of the java field: the `sig` attribute, or the one
+    /// the rust type maps to through `conversion`.
+    fn descriptor(&self, conversion: TokenStream2) -> TokenStream2 {
+        let ty = self.ty;
+        match self.sig {
+            Some(ref sig) => quote!(#sig),
+            None => quote!(<#ty as #conversion>::descriptor()),
+        }
+    }
+}
+
+fn jni_attrs<'a>(attrs: &'a [Attribute]) -> impl Iterator<Item = &'a Attribute> {
+    attrs.iter().filter(|attr| attr.path().is_ident("jni"))
+}
+
+/// Whether a parameter is the `JNIEnv`, which has to be taken by reference
+/// since the wrapper owns it.
+fn is_env(ty: &Type) -> syn::Result<bool> {
+    match *ty {
+        Type::Reference(ref r) => Ok(last_segment(&r.elem) == Some("JNIEnv".to_owned())),
+        _ if last_segment(ty) == Some("JNIEnv".to_owned()) => Err(syn::Error::new_spanned(
+            ty,
+            "take the env as `&JNIEnv` in jni_export functions",
+        )),
+        _ => Ok(false),
+    }
+}
+
+fn last_segment(ty: &Type) -> Option<String> {
+    match *ty {
+        Type::Path(ref path) => path.path.segments.last().map(|seg| seg.ident.to_string()),
+        _ => None,
+    }
+}
+
+/// The `T` of a `Result<T>` or `Result<T, E>` return type.
+fn result_ok_type(ty: &Type) -> Option<&Type> {
+    let seg = match *ty {
+        Type::Path(ref path) => path.path.segments.last()?,
+        _ => return None,
+    };
+    if seg.ident != "Result" {
+        return None;
+    }
+    match seg.arguments {
+        PathArguments::AngleBracketed(ref args) => match args.args.first() {
+            Some(&GenericArgument::Type(ref ok)) => Some(ok),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The JNI type java passes for an argument written as `ty`.
+fn raw_type(ty: &Type) -> TokenStream2 {
+    match last_segment(ty).as_ref().map(|s| s.as_str()) {
+        Some("bool") => quote!(::jni::sys::jboolean),
+        Some(prim @ "jboolean") | Some(prim @ "jbyte") | Some(prim @ "jchar") | Some(prim @ "jshort")
+        | Some(prim @ "jint") | Some(prim @ "jlong") | Some(prim @ "jfloat") | Some(prim @ "jdouble") => {
+            let prim = Ident::new(prim, Span::call_site());
+            quote!(::jni::sys::#prim)
+        }
+        Some(prim @ "u8") | Some(prim @ "i8") | Some(prim @ "u16") | Some(prim @ "i16") | Some(prim @ "i32")
+        | Some(prim @ "i64") | Some(prim @ "f32") | Some(prim @ "f64") => {
+            let prim = Ident::new(prim, Span::call_site());
+            quote!(#prim)
+        }
+        _ => quote!(::jni::sys::jobject),
+    }
+}
+
+/// The JNI type returned to java for a return value written as `ty`, if any.
+fn raw_return_type(ty: &Type) -> Option<TokenStream2> {
+    match *ty {
+        Type::Tuple(ref tuple) if tuple.elems.is_empty() => None,
+        _ => Some(raw_type(ty)),
+    }
+}
+
+/// Escape a class or method name for a JNI symbol name, as described in the
+/// "Resolving Native Method Names" section of the JNI spec.
+fn mangle(name: &str) -> String {
+    let mut mangled = String::with_capacity(name.len());
+    for c in name.chars() {
+        match c {
+            '/' => mangled.push('_'),
+            '_' => mangled.push_str("_1"),
+            ';' => mangled.push_str("_2"),
+            '[' => mangled.push_str("_3"),
+            'a'..='z' | 'A'..='Z' | '0'..='9' => mangled.push(c),
+            _ => {
+                let mut buf = [0; 2];
+                for unit in c.encode_utf16(&mut buf) {
+                    mangled.push_str(&format!("_0{:04x}", unit));
+                }
+            }
+        }
+    }
+    mangled
+}
@@ -166,6 +166,13 @@ extern crate combine;
 
 extern crate cesu8;
 
+#[cfg(feature = "ndarray")]
+extern crate ndarray;
+
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
+
 mod wrapper {
     mod version;
     pub use self::version::*;
@@ -188,6 +195,9 @@ mod wrapper {
     /// String types for going to/from java strings.
     pub mod strings;
 
+    /// Class names of common java exceptions
+    pub mod exceptions;
+
     /// Actual communication with the JVM
     mod jnienv;
     pub use self::jnienv::*;
@@ -195,6 +205,50 @@ mod wrapper {
     /// Java VM interface
     mod java_vm;
     pub use self::java_vm::*;
+
+    /// Limiting how deeply calls into java may nest
+    mod call_depth;
+    #[cfg(feature = "call-depth")]
+    pub use self::call_depth::*;
+
+    /// Counting the local refs created in each frame
+    mod local_refs;
+    #[cfg(feature = "local-ref-tracking")]
+    pub use self::local_refs::*;
+
+    /// Counting calls to java methods
+    mod call_stats;
+    #[cfg(feature = "call-stats")]
+    pub use self::call_stats::*;
+
+    /// Logging calls into java
+    mod call_trace;
+
+    /// Interrupting calls into java that run past their timeout
+    mod watchdog;
+
+    /// Completing java futures from rust futures
+    mod java_future;
+    pub use self::java_future::*;
+
+    /// Turning errors and panics in native methods into java exceptions
+    mod native_try;
+    pub use self::native_try::*;
+
+    /// Converting between rust structs and java objects
+    mod java_convert;
+    pub use self::java_convert::*;
+
+    /// Forwarding `java.util.logging` records to the `log` crate
+    mod java_logging;
+
+    /// Converting between java arrays and `ndarray` arrays
+    #[cfg(feature = "ndarray")]
+    mod ndarray_interop;
+
+    /// Converting between java objects and rust types with `serde`
+    #[cfg(feature = "serde")]
+    pub mod jni_serde;
 }
 
 pub use wrapper::*;
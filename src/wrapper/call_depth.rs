@@ -0,0 +1,69 @@
+#[cfg(feature = "call-depth")]
+use std::cell::Cell;
+
+#[cfg(feature = "call-depth")]
+use std::sync::atomic::{
+    AtomicUsize,
+    Ordering,
+};
+
+use errors::*;
+
+#[cfg(feature = "call-depth")]
+static LIMIT: AtomicUsize = AtomicUsize::new(DEFAULT_CALL_DEPTH_LIMIT);
+
+#[cfg(feature = "call-depth")]
+thread_local! {
+    static DEPTH: Cell<usize> = Cell::new(0);
+}
+
+/// The default limit on nested calls into java, see `set_call_depth_limit`.
+#[cfg(feature = "call-depth")]
+pub const DEFAULT_CALL_DEPTH_LIMIT: usize = 512;
+
+/// Set how many calls into java (method calls and constructors) may be
+/// nested on a single thread before `CallDepthExceeded` is returned instead
+/// of making the call. Nesting happens when a native method called from java
+/// calls back into java, which calls another native method, and so on.
+///
+/// The limit is shared by all threads, the depth is counted per thread.
+#[cfg(feature = "call-depth")]
+pub fn set_call_depth_limit(limit: usize) {
+    LIMIT.store(limit, Ordering::Relaxed);
+}
+
+/// The number of calls into java currently in progress on this thread.
+#[cfg(feature = "call-depth")]
+pub fn call_depth() -> usize {
+    DEPTH.with(|depth| depth.get())
+}
+
+/// Counts a call into java for as long as it's alive.
+pub(crate) struct CallDepthGuard(());
+
+impl CallDepthGuard {
+    #[cfg(feature = "call-depth")]
+    pub(crate) fn enter() -> Result<CallDepthGuard> {
+        let limit = LIMIT.load(Ordering::Relaxed);
+        DEPTH.with(|depth| {
+            if depth.get() >= limit {
+                return Err(ErrorKind::CallDepthExceeded(limit).into());
+            }
+            depth.set(depth.get() + 1);
+            Ok(CallDepthGuard(()))
+        })
+    }
+
+    #[cfg(not(feature = "call-depth"))]
+    #[inline(always)]
+    pub(crate) fn enter() -> Result<CallDepthGuard> {
+        Ok(CallDepthGuard(()))
+    }
+}
+
+#[cfg(feature = "call-depth")]
+impl Drop for CallDepthGuard {
+    fn drop(&mut self) {
+        DEPTH.with(|depth| depth.set(depth.get() - 1));
+    }
+}
@@ -0,0 +1,94 @@
+#[cfg(feature = "call-stats")]
+use std::collections::HashMap;
+
+#[cfg(feature = "call-stats")]
+use std::sync::Mutex;
+
+use JNIEnv;
+
+use objects::JMethodID;
+use objects::JObject;
+
+use signature::TypeSignature;
+
+#[cfg(feature = "call-stats")]
+use std::borrow::Cow;
+use strings::JNIStr;
+
+/// Identifies a java method in the `JNIEnv::call_stats` counts.
+#[cfg(feature = "call-stats")]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MethodKey {
+    /// The binary name of the class of the object the method was called on,
+    /// e.g. `java.util.ArrayList`.
+    pub class: String,
+    /// The method name.
+    pub name: String,
+    /// The method signature.
+    pub sig: String,
+}
+
+// Counts are keyed by method id, so that counting a call doesn't need any
+// string handling. The key is only built the first time a method is seen.
+#[cfg(feature = "call-stats")]
+static STATS: Mutex<Option<HashMap<usize, (MethodKey, u64)>>> = Mutex::new(None);
+
+#[cfg(feature = "call-stats")]
+impl<'a> JNIEnv<'a> {
+    /// The number of calls made through `call_method` to each method, by any
+    /// thread, since the start of the process or the last `reset_call_stats`.
+    pub fn call_stats(&self) -> HashMap<MethodKey, u64> {
+        let mut counts = HashMap::new();
+        if let Some(ref stats) = *STATS.lock().unwrap() {
+            for &(ref key, count) in stats.values() {
+                *counts.entry(key.clone()).or_insert(0) += count;
+            }
+        }
+        counts
+    }
+
+    /// Forget all calls counted so far.
+    pub fn reset_call_stats(&self) {
+        *STATS.lock().unwrap() = None;
+    }
+}
+
+/// Count a call to a method of `class`.
+#[cfg(feature = "call-stats")]
+pub(crate) fn record<'a>(
+    env: &'a JNIEnv<'a>,
+    method_id: JMethodID<'a>,
+    class: JObject,
+    name: &JNIStr,
+    sig: &TypeSignature,
+) {
+    let id = method_id.into_inner() as usize;
+    if let Some(&mut (_, ref mut count)) = STATS
+        .lock()
+        .unwrap()
+        .as_mut()
+        .and_then(|stats| stats.get_mut(&id))
+    {
+        *count += 1;
+        return;
+    }
+
+    let key = MethodKey {
+        class: env.class_name(class).unwrap_or_else(|_| {
+            let _ = env.exception_clear();
+            "<unknown>".into()
+        }),
+        name: Cow::from(name).into_owned(),
+        sig: sig.to_string(),
+    };
+    let mut stats = STATS.lock().unwrap();
+    stats
+        .get_or_insert_with(HashMap::new)
+        .entry(id)
+        .or_insert((key, 0))
+        .1 += 1;
+}
+
+#[cfg(not(feature = "call-stats"))]
+#[inline(always)]
+pub(crate) fn record<'a>(_: &'a JNIEnv<'a>, _: JMethodID<'a>, _: JObject, _: &JNIStr, _: &TypeSignature) {}
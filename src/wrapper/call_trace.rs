@@ -0,0 +1,80 @@
+#[cfg(feature = "trace")]
+use std::borrow::Cow;
+
+use std::fmt::Display;
+
+#[cfg(feature = "trace")]
+use std::time::Instant;
+
+#[cfg(feature = "trace")]
+use log::LogLevel;
+
+use JNIEnv;
+
+use errors::*;
+
+use objects::JObject;
+
+use strings::JNIStr;
+
+/// Run a call into java, and log it at trace level, for the `jni::trace`
+/// target, with the binary name of `class`, the member `name` and `sig`, and
+/// how long it took. Only wrapper-level calls (`call_method`, `get_field`...)
+/// are traced, not the `_unsafe` ones they use, so that each call is logged
+/// once, and neither are the calls made to capture the details of an
+/// exception.
+#[cfg(feature = "trace")]
+pub(crate) fn traced<'a, T, F>(
+    env: &'a JNIEnv<'a>,
+    call: &str,
+    class: JObject,
+    name: &JNIStr,
+    sig: &dyn Display,
+    f: F,
+) -> Result<T>
+where
+    F: FnOnce() -> Result<T>,
+{
+    if !log_enabled!(LogLevel::Trace) || capturing_exception() {
+        return f();
+    }
+
+    // The name is looked up first: once the call has failed, its exception
+    // may be pending, and no other call may be made until it's cleared. If
+    // the lookup fails, e.g. on an exception the caller left pending, that
+    // is left for the call to fail on, and the call isn't logged.
+    let class = match env.class_name(class) {
+        Ok(class) => class,
+        Err(_) => return f(),
+    };
+
+    let start = Instant::now();
+    let res = f();
+    let elapsed = start.elapsed();
+
+    trace!(
+        target: "jni::trace",
+        "{} {}.{} {} in {:?}{}",
+        call,
+        class,
+        Cow::from(name),
+        sig,
+        elapsed,
+        if res.is_err() { " (failed)" } else { "" }
+    );
+    res
+}
+
+/// The name constructors are traced with.
+pub(crate) fn constructor_name() -> &'static JNIStr {
+    unsafe { JNIStr::from_ptr(b"<init>\0".as_ptr() as *const _) }
+}
+
+#[cfg(not(feature = "trace"))]
+#[inline(always)]
+pub(crate) fn traced<'a, T, F>(_: &'a JNIEnv<'a>, _: &str, _: JObject, _: &JNIStr, _: &dyn Display, f: F) -> Result<T>
+where
+    F: FnOnce() -> Result<T>,
+{
+    f()
+}
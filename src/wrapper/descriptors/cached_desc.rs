@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use errors::*;
+
+use descriptors::Desc;
+
+use objects::GlobalRef;
+use objects::JClass;
+use objects::JFieldID;
+use objects::JMethodID;
+use objects::JStaticFieldID;
+use objects::JStaticMethodID;
+
+use JNIEnv;
+
+/// A `(class, name, signature)` descriptor whose id is only looked up the
+/// first time it's used, and then reused by every thread for the rest of the
+/// process. It works for all four id types:
+///
+/// ```rust,ignore
+/// let len: JMethodID = Cached("java/lang/String", "length", "()I").lookup(&env)?;
+/// let s = env.call_method_unsafe(obj, Cached("java/lang/Object", "toString", "()Ljava/lang/String;"), ret, &[])?;
+/// ```
+///
+/// The class is found with `find_class` and then pinned with a global ref, so
+/// that it can't be unloaded while its ids are cached. Entries are keyed by
+/// class name, so classes with the same name loaded through different class
+/// loaders will be mixed up.
+#[derive(Clone, Copy, Debug)]
+pub struct Cached<'s>(pub &'s str, pub &'s str, pub &'s str);
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum IdKind {
+    Method,
+    StaticMethod,
+    Field,
+    StaticField,
+}
+
+#[derive(Default)]
+struct IdCache {
+    ids: HashMap<(IdKind, String, String, String), usize>,
+    classes: HashMap<String, GlobalRef>,
+}
+
+static CACHE: Mutex<Option<IdCache>> = Mutex::new(None);
+
+impl<'s> Cached<'s> {
+    fn lookup_id<'a, F>(&self, env: &JNIEnv<'a>, kind: IdKind, lookup: F) -> Result<usize>
+    where
+        F: FnOnce(JClass) -> Result<usize>,
+    {
+        let key = (kind, self.0.to_owned(), self.1.to_owned(), self.2.to_owned());
+        if let Some(id) = CACHE.lock().unwrap().as_ref().and_then(|c| c.ids.get(&key)) {
+            return Ok(*id);
+        }
+
+        // The lock isn't held while calling into java: finding the class may
+        // run its static initializer, which could end up here again.
+        let class = env.find_class(self.0)?;
+        let found = lookup(class).and_then(|id| Ok((id, env.new_global_ref(class.into())?)));
+        env.delete_local_ref(class.into())?;
+        let (id, class) = found?;
+
+        let mut guard = CACHE.lock().unwrap();
+        let cache = guard.get_or_insert_with(Default::default);
+        cache.classes.entry(self.0.to_owned()).or_insert(class);
+        cache.ids.insert(key, id);
+        Ok(id)
+    }
+}
+
+impl<'a, 's> Desc<'a, JMethodID<'a>> for Cached<'s> {
+    fn lookup(self, env: &JNIEnv<'a>) -> Result<JMethodID<'a>> {
+        let id = self.lookup_id(env, IdKind::Method, |class| {
+            Ok(env.get_method_id(class, self.1, self.2)?.into_inner() as usize)
+        })?;
+        Ok(JMethodID::from(id as ::sys::jmethodID))
+    }
+}
+
+impl<'a, 's> Desc<'a, JStaticMethodID<'a>> for Cached<'s> {
+    fn lookup(self, env: &JNIEnv<'a>) -> Result<JStaticMethodID<'a>> {
+        let id = self.lookup_id(env, IdKind::StaticMethod, |class| {
+            Ok(env.get_static_method_id(class, self.1, self.2)?.into_inner() as usize)
+        })?;
+        Ok(JStaticMethodID::from(id as ::sys::jmethodID))
+    }
+}
+
+impl<'a, 's> Desc<'a, JFieldID<'a>> for Cached<'s> {
+    fn lookup(self, env: &JNIEnv<'a>) -> Result<JFieldID<'a>> {
+        let id = self.lookup_id(env, IdKind::Field, |class| {
+            Ok(env.get_field_id(class, self.1, self.2)?.into_inner() as usize)
+        })?;
+        Ok(JFieldID::from(id as ::sys::jfieldID))
+    }
+}
+
+impl<'a, 's> Desc<'a, JStaticFieldID<'a>> for Cached<'s> {
+    fn lookup(self, env: &JNIEnv<'a>) -> Result<JStaticFieldID<'a>> {
+        let id = self.lookup_id(env, IdKind::StaticField, |class| {
+            Ok(env.get_static_field_id(class, self.1, self.2)?.into_inner() as usize)
+        })?;
+        Ok(JStaticFieldID::from(id as ::sys::jfieldID))
+    }
+}
@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use errors::*;
+
+use descriptors::Desc;
+
+use objects::GlobalRef;
+use objects::JClass;
+
+use JNIEnv;
+
+/// Classes looked up by name the first time they're asked for and then kept
+/// as global refs, so that later lookups don't go through `FindClass`. It can
+/// be shared by all threads:
+///
+/// ```rust,ignore
+/// static CLASSES: ClassCache = ClassCache::new();
+///
+/// // in JNI_OnLoad, where the library's class loader is used
+/// CLASSES.preload(&env, &["com/example/Event", "com/example/Listener"])?;
+///
+/// // later, on any thread
+/// let event = env.new_object(CLASSES.class("com/example/Event"), "()V", &[])?;
+/// ```
+///
+/// Besides being slow, `FindClass` on a thread attached from native code only
+/// sees the classes of the system class loader, not those of the library.
+/// Looking the classes up once in `JNI_OnLoad` works around both.
+///
+/// The cache never evicts anything but with `clear`, and keeps the classes
+/// it holds from being unloaded.
+pub struct ClassCache {
+    classes: RwLock<Option<HashMap<String, GlobalRef>>>,
+}
+
+impl ClassCache {
+    /// Create an empty cache.
+    pub const fn new() -> ClassCache {
+        ClassCache {
+            classes: RwLock::new(None),
+        }
+    }
+
+    /// Get a class by name, in the `java/lang/String` form, finding it with
+    /// `find_class` if it isn't cached yet.
+    pub fn find_class<'c>(&'c self, env: &JNIEnv, name: &str) -> Result<JClass<'c>> {
+        if let Some(class) = self.get(name) {
+            return Ok(class);
+        }
+
+        // The lock isn't held while calling into java: finding the class may
+        // run its static initializer, which could look up classes too.
+        let local = env.find_class(name)?;
+        let global = env.new_global_ref(local.into());
+        env.delete_local_ref(local.into())?;
+        let global = global?;
+
+        let mut classes = self.classes.write().unwrap();
+        let class = classes
+            .get_or_insert_with(HashMap::new)
+            .entry(name.to_owned())
+            .or_insert(global);
+        // The global ref lives until `clear`, which can't be called while
+        // `self` is borrowed.
+        Ok(JClass::from(class.as_obj().into_inner()))
+    }
+
+    /// A descriptor for the class, to pass to the `JNIEnv` methods taking a
+    /// class. It's looked up in the cache when it's used.
+    pub fn class<'c, 's>(&'c self, name: &'s str) -> CachedClass<'c, 's> {
+        CachedClass {
+            cache: self,
+            name: name,
+        }
+    }
+
+    /// Look up all of the classes, e.g. from `JNI_OnLoad`.
+    pub fn preload(&self, env: &JNIEnv, names: &[&str]) -> Result<()> {
+        for name in names {
+            self.find_class(env, name)?;
+        }
+        Ok(())
+    }
+
+    /// Get a class by name, if it's cached.
+    pub fn get<'c>(&'c self, name: &str) -> Option<JClass<'c>> {
+        let classes = self.classes.read().unwrap();
+        classes
+            .as_ref()
+            .and_then(|classes| classes.get(name))
+            .map(|class| JClass::from(class.as_obj().into_inner()))
+    }
+
+    /// The number of classes in the cache.
+    pub fn len(&self) -> usize {
+        self.classes.read().unwrap().as_ref().map_or(0, |classes| classes.len())
+    }
+
+    /// Whether the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drop all cached classes.
+    pub fn clear(&mut self) {
+        *self.classes.write().unwrap() = None;
+    }
+}
+
+impl Default for ClassCache {
+    fn default() -> Self {
+        ClassCache::new()
+    }
+}
+
+/// A class descriptor that looks the class up in a `ClassCache`, see
+/// `ClassCache::class`.
+#[derive(Clone, Copy)]
+pub struct CachedClass<'c, 's> {
+    cache: &'c ClassCache,
+    name: &'s str,
+}
+
+impl<'a, 'c, 's> Desc<'a, JClass<'c>> for CachedClass<'c, 's> {
+    fn lookup(self, env: &JNIEnv<'a>) -> Result<JClass<'c>> {
+        self.cache.find_class(env, self.name)
+    }
+}
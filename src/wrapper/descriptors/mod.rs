@@ -12,3 +12,9 @@ pub use self::field_desc::*;
 
 mod exception_desc;
 pub use self::exception_desc::*;
+
+mod cached_desc;
+pub use self::cached_desc::*;
+
+mod class_cache;
+pub use self::class_cache::*;
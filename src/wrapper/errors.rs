@@ -1,6 +1,21 @@
 #![allow(unused_doc_comment)]
 #![allow(missing_docs)]
 
+use std::cell::Cell;
+
+use std::fmt;
+
+use std::os::raw::c_char;
+
+use std::sync::atomic::{
+    AtomicBool,
+    Ordering,
+};
+
+use JNIEnv;
+
+use objects::{JObject, JThrowable};
+
 use sys;
 
 error_chain!{
@@ -14,6 +29,12 @@ error_chain!{
                     cast,
                     actual)
         }
+        WrongObjectType(expected: &'static str, actual: String) {
+            description("Object is not an instance of the expected class")
+            display("Object is not an instance of {}: it's a {}",
+                    expected,
+                    actual)
+        }
         InvalidCtorReturn {
             description("Invalid constructor return type (must be void)")
             display("Invalid constructor return type (must be void)")
@@ -22,18 +43,69 @@ error_chain!{
             description("Invalid number of arguments passed to java method")
             display("Invalid number of arguments passed to java method")
         }
-        MethodNotFound(name: String, sig: String) {
+        InvalidArgType(index: usize, expected: String, actual: &'static str) {
+            description("Wrong type of argument passed to java method")
+            display("Wrong type of argument {} passed to java method: expected {}, got {}",
+                    index,
+                    expected,
+                    actual)
+        }
+        InvalidMethodIndex(index: usize, len: usize) {
+            description("Method index out of range")
+            display("Method index out of range: {} (table length {})", index, len)
+        }
+        MethodNotFound(class: String, name: String, sig: String) {
             description("Method not found")
-            display("Method not found: {} {}", name, sig)
+            display("Method not found: {}.{} {}", class, name, sig)
+        }
+        NoMatchingOverload(name: String) {
+            description("No method matches the given argument types")
+            display("No method matches the given argument types: {}", name)
+        }
+        AmbiguousOverload(name: String) {
+            description("More than one method matches the given argument types")
+            display("More than one method matches the given argument types: {}", name)
+        }
+        ArrayRegionOutOfBounds(start: sys::jsize, len: usize, array_len: sys::jsize) {
+            description("Array region out of bounds")
+            display("Array region out of bounds: {} elements at {} (array length {})",
+                    len,
+                    start,
+                    array_len)
+        }
+        StringRegionOutOfBounds(start: sys::jsize, len: usize, string_len: sys::jsize) {
+            description("String region out of bounds")
+            display("String region out of bounds: {} characters at {} (string length {})",
+                    len,
+                    start,
+                    string_len)
         }
-        FieldNotFound(name: String, ty: String) {
+        FieldNotFound(class: String, name: String, ty: String) {
             description("Field not found")
-            display("Field not found: {} {}", name, ty)
+            display("Field not found: {}.{} {}", class, name, ty)
         }
         JavaException {
             description("Java exception was thrown")
             display("Java exception was thrown")
         }
+        JavaError {
+            description("Java error was thrown, the VM may not be usable")
+            display("Java error was thrown (an instance of java.lang.Error, such as \
+                     StackOverflowError or OutOfMemoryError). Return to java \
+                     immediately without making further JNI calls")
+        }
+        ThrownException(details: ExceptionDetails) {
+            description("Details of the java exception")
+            display("{}", details)
+        }
+        CallDepthExceeded(limit: usize) {
+            description("Too many nested calls into java")
+            display("Too many nested calls into java (limit {})", limit)
+        }
+        CallContext(desc: String) {
+            description("Failed call")
+            display("{}", desc)
+        }
         JNIEnvMethodNotFound(name: &'static str) {
             description("Method pointer null in JNIEnv")
             display("JNIEnv null method pointer for {}", name)
@@ -54,6 +126,10 @@ error_chain!{
             description("Method pointer null in JavaVM")
             display("JavaVM null method pointer for {}", name)
         }
+        NoJavaVM {
+            description("No java VM has been created in this process")
+            display("No java VM has been created in this process")
+        }
         ThreadDetached {
             description("Current thread is not attached to the java VM")
             display("Current thread is not attached to the java VM")
@@ -81,6 +157,7 @@ pub fn jni_error_code_to_result(code: sys::jint) -> Result<()> {
     }
 }
 
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Exception {
     pub class: String,
     pub msg: String,
@@ -89,3 +166,175 @@ pub struct Exception {
 pub trait ToException {
     fn to_exception(&self) -> Exception;
 }
+
+/// What was captured of the exception behind a `JavaException` error, before
+/// it was thrown again. See `Error::exception_details`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExceptionDetails {
+    /// The class, in internal form, and message of the exception.
+    pub exception: Exception,
+    /// What `printStackTrace` prints, causes included.
+    pub stack_trace: String,
+}
+
+impl fmt::Display for ExceptionDetails {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.stack_trace)
+    }
+}
+
+impl Error {
+    /// The class, message and stack trace of the java exception that caused
+    /// this error, if it's a `JavaException` (or follows from one) and
+    /// `set_capture_stack_traces` is on.
+    ///
+    /// These are captured when the exception is found pending, so they're
+    /// still available after it's been cleared or has reached java. Nothing
+    /// is captured for a `JavaError`, since no java code should run after
+    /// one.
+    pub fn exception_details(&self) -> Option<&ExceptionDetails> {
+        if let ErrorKind::ThrownException(ref details) = *self.kind() {
+            return Some(details);
+        }
+        self.1
+            .next_error
+            .as_ref()
+            .and_then(|next| next.downcast_ref::<Error>())
+            .and_then(|next| next.exception_details())
+    }
+}
+
+static REDACT_CALL_ARGS: AtomicBool = AtomicBool::new(false);
+
+/// Choose whether the values of object arguments are left out of the context
+/// attached to failed `call_method` and `new_object` calls.
+///
+/// By default, object arguments are described using their `toString()`. Turn
+/// this on if they may hold sensitive data; they'll show up as `<object>`
+/// instead. Primitive arguments are always included.
+pub fn set_redact_call_args(redact: bool) {
+    REDACT_CALL_ARGS.store(redact, Ordering::Relaxed);
+}
+
+pub(crate) fn redact_call_args() -> bool {
+    REDACT_CALL_ARGS.load(Ordering::Relaxed)
+}
+
+static CAPTURE_STACK_TRACES: AtomicBool = AtomicBool::new(false);
+
+/// Choose whether the class, message and stack trace of an exception are
+/// captured when it's turned into a `JavaException` error. See
+/// `Error::exception_details`.
+///
+/// This is off by default, since it takes a handful of java calls and
+/// allocations for every failed call, whether or not the error is looked at.
+/// That includes the failures some lookups expect and recover from.
+pub fn set_capture_stack_traces(capture: bool) {
+    CAPTURE_STACK_TRACES.store(capture, Ordering::Relaxed);
+}
+
+/// Add a description of a failed call to an error's chain, right after the
+/// error itself. The error keeps its kind, so it can still be matched on.
+pub(crate) fn with_call_context(err: Error, desc: String) -> Error {
+    let Error(kind, mut state) = err;
+    let context = match state.next_error.take() {
+        Some(next) => Error::with_boxed_chain(next, ErrorKind::CallContext(desc)),
+        None => ErrorKind::CallContext(desc).into(),
+    };
+    state.next_error = Some(Box::new(context));
+    Error(kind, state)
+}
+
+/// Turn a pending java exception into an error: `JavaError` for instances of
+/// `java.lang.Error`, `JavaException` for anything else, with the exception's
+/// details in the chain of the latter. The exception is still pending when
+/// this returns.
+pub(crate) fn pending_exception_error(env: *mut sys::JNIEnv) -> Error {
+    match pending_exception_is_error(env) {
+        Ok((true, _)) => ErrorKind::JavaError.into(),
+        Ok((false, Some(details))) => {
+            Error::with_chain(Error::from(ErrorKind::ThrownException(details)), ErrorKind::JavaException)
+        }
+        _ => ErrorKind::JavaException.into(),
+    }
+}
+
+thread_local! {
+    // set while capturing the details of an exception, which is done with
+    // calls that may throw themselves
+    static CAPTURING: Cell<bool> = Cell::new(false);
+}
+
+/// Whether the details of an exception are being captured on this thread.
+#[cfg(feature = "trace")]
+pub(crate) fn capturing_exception() -> bool {
+    CAPTURING.with(|c| c.get())
+}
+
+// Only a handful of JNI functions may be called while an exception is pending,
+// so the throwable is taken, checked, and then thrown again.
+fn pending_exception_is_error(env: *mut sys::JNIEnv) -> Result<(bool, Option<ExceptionDetails>)> {
+    unsafe {
+        let throwable = jni_unchecked!(env, ExceptionOccurred);
+        if throwable.is_null() {
+            return Ok((false, None));
+        }
+        jni_unchecked!(env, ExceptionClear);
+
+        let class = jni_unchecked!(
+            @no_exception_check env,
+            FindClass,
+            "java/lang/Error\0".as_ptr() as *const c_char
+        );
+        let is_error = if class.is_null() {
+            // FindClass threw something of its own, which we drop in favor of
+            // the original exception.
+            jni_unchecked!(env, ExceptionClear);
+            false
+        } else {
+            let is_error = jni_unchecked!(env, IsInstanceOf, throwable, class) == sys::JNI_TRUE;
+            jni_unchecked!(env, DeleteLocalRef, class);
+            is_error
+        };
+
+        let capture = !is_error && CAPTURE_STACK_TRACES.load(Ordering::Relaxed);
+        let details = if !capture || CAPTURING.with(|c| c.replace(true)) {
+            None
+        } else {
+            let details = capture_details(env, throwable);
+            CAPTURING.with(|c| c.set(false));
+            if details.is_err() {
+                jni_unchecked!(env, ExceptionClear);
+            }
+            details.ok()
+        };
+
+        jni_unchecked!(env, Throw, throwable);
+        jni_unchecked!(env, DeleteLocalRef, throwable);
+        Ok((is_error, details))
+    }
+}
+
+fn capture_details(raw: *mut sys::JNIEnv, throwable: sys::jthrowable) -> Result<ExceptionDetails> {
+    let env = unsafe { JNIEnv::from_raw(raw)? };
+    let exception = env.describe_throwable(JThrowable::from(throwable))?;
+    let writer = env.auto_local(env.new_object("java/io/StringWriter", "()V", &[])?);
+    let print_writer = env.auto_local(env.new_object(
+        "java/io/PrintWriter",
+        "(Ljava/io/Writer;)V",
+        &[writer.as_obj().into()],
+    )?);
+    env.call_method(
+        JObject::from(throwable),
+        "printStackTrace",
+        "(Ljava/io/PrintWriter;)V",
+        &[print_writer.as_obj().into()],
+    )?;
+    let stack_trace = env.call_string_method(writer.as_obj(), "toString", "()Ljava/lang/String;", &[])?
+        .unwrap_or_default();
+
+    Ok(ExceptionDetails {
+        exception: exception,
+        stack_trace: stack_trace,
+    })
+}
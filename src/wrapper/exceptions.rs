@@ -0,0 +1,72 @@
+//! Class names of the JDK exceptions native code most often needs to throw,
+//! in the slash-separated form that `find_class` expects. Since `&str` is a
+//! class descriptor, they can be passed straight to `throw_new`:
+//!
+//! ```rust,ignore
+//! use jni::exceptions::ILLEGAL_ARGUMENT_EXCEPTION;
+//!
+//! if len < 0 {
+//!     env.throw_new(ILLEGAL_ARGUMENT_EXCEPTION, "len must not be negative")?;
+//!     return Ok(JObject::null());
+//! }
+//! ```
+
+/// `java.lang.Exception`
+pub const EXCEPTION: &str = "java/lang/Exception";
+
+/// `java.lang.RuntimeException`
+pub const RUNTIME_EXCEPTION: &str = "java/lang/RuntimeException";
+
+/// `java.lang.IllegalArgumentException`
+pub const ILLEGAL_ARGUMENT_EXCEPTION: &str = "java/lang/IllegalArgumentException";
+
+/// `java.lang.IllegalStateException`
+pub const ILLEGAL_STATE_EXCEPTION: &str = "java/lang/IllegalStateException";
+
+/// `java.lang.NullPointerException`
+pub const NULL_POINTER_EXCEPTION: &str = "java/lang/NullPointerException";
+
+/// `java.lang.UnsupportedOperationException`
+pub const UNSUPPORTED_OPERATION_EXCEPTION: &str = "java/lang/UnsupportedOperationException";
+
+/// `java.lang.IndexOutOfBoundsException`
+pub const INDEX_OUT_OF_BOUNDS_EXCEPTION: &str = "java/lang/IndexOutOfBoundsException";
+
+/// `java.lang.ArrayIndexOutOfBoundsException`
+pub const ARRAY_INDEX_OUT_OF_BOUNDS_EXCEPTION: &str = "java/lang/ArrayIndexOutOfBoundsException";
+
+/// `java.lang.ArithmeticException`
+pub const ARITHMETIC_EXCEPTION: &str = "java/lang/ArithmeticException";
+
+/// `java.lang.ClassCastException`
+pub const CLASS_CAST_EXCEPTION: &str = "java/lang/ClassCastException";
+
+/// `java.lang.NumberFormatException`
+pub const NUMBER_FORMAT_EXCEPTION: &str = "java/lang/NumberFormatException";
+
+/// `java.lang.InterruptedException`
+pub const INTERRUPTED_EXCEPTION: &str = "java/lang/InterruptedException";
+
+/// `java.util.NoSuchElementException`
+pub const NO_SUCH_ELEMENT_EXCEPTION: &str = "java/util/NoSuchElementException";
+
+/// `java.util.ConcurrentModificationException`
+pub const CONCURRENT_MODIFICATION_EXCEPTION: &str = "java/util/ConcurrentModificationException";
+
+/// `java.io.IOException`
+pub const IO_EXCEPTION: &str = "java/io/IOException";
+
+/// `java.io.FileNotFoundException`
+pub const FILE_NOT_FOUND_EXCEPTION: &str = "java/io/FileNotFoundException";
+
+/// `java.lang.OutOfMemoryError`
+pub const OUT_OF_MEMORY_ERROR: &str = "java/lang/OutOfMemoryError";
+
+/// `java.lang.NoClassDefFoundError`
+pub const NO_CLASS_DEF_FOUND_ERROR: &str = "java/lang/NoClassDefFoundError";
+
+/// `java.lang.NoSuchMethodError`
+pub const NO_SUCH_METHOD_ERROR: &str = "java/lang/NoSuchMethodError";
+
+/// `java.lang.NoSuchFieldError`
+pub const NO_SUCH_FIELD_ERROR: &str = "java/lang/NoSuchFieldError";
@@ -0,0 +1,183 @@
+use JNIEnv;
+
+use errors::*;
+
+use objects::JObject;
+use objects::JValue;
+
+use sys::{
+    jbyte,
+    jchar,
+    jdouble,
+    jfloat,
+    jint,
+    jlong,
+    jshort,
+};
+
+/// Rust types that can be converted to a new java object. Usually derived
+/// with `#[derive(IntoJava)]` from the `jni-macros` crate, which creates an
+/// instance of the java class and sets its fields:
+///
+/// ```rust,ignore
+/// #[derive(IntoJava, FromJava)]
+/// #[jni(class = "com.example.Point")]
+/// struct Point {
+///     x: jint,
+///     #[jni(name = "yPos")]
+///     y: jint,
+///     #[jni(sig = "Ljava/lang/CharSequence;", getter = "getLabel")]
+///     label: String,
+/// }
+///
+/// let obj = point.to_java(&env)?;
+/// let point = Point::from_java(&env, obj)?;
+/// ```
+///
+/// On the struct, `class` is the java class, and `ctor` makes `to_java`
+/// pass all fields, in order, to a constructor instead of calling the no-arg
+/// constructor and then setting each field. On fields, `name` is the java
+/// field name, `sig` its type signature if it isn't the one the rust type
+/// maps to (see `IntoJavaField`), and `getter` a no-arg method `from_java`
+/// calls instead of reading the field.
+pub trait IntoJava {
+    /// Create a java object holding the value.
+    fn to_java<'e>(&self, env: &'e JNIEnv<'e>) -> Result<JObject<'e>>;
+}
+
+/// Rust types that can be read from a java object, usually derived with
+/// `#[derive(FromJava)]`. See `IntoJava`.
+pub trait FromJava: Sized {
+    /// Read the value from a java object.
+    fn from_java<'e>(env: &'e JNIEnv<'e>, obj: JObject<'e>) -> Result<Self>;
+}
+
+/// The types of the fields of a struct deriving `IntoJava`, and of the
+/// arguments of its constructor with `ctor`.
+///
+/// These are the primitive types, `bool`, `String` (`java.lang.String`),
+/// `Vec<u8>` (`byte[]`), `Option` of the object types for nullable fields,
+/// and other structs deriving `IntoJava`, which map to their java class.
+pub trait IntoJavaField {
+    /// The java type signature of the field, e.g. `I` or
+    /// `Ljava/lang/String;`.
+    fn descriptor() -> &'static str;
+
+    /// Convert to a value to store in a field or pass to a constructor. An
+    /// object is a new local ref.
+    fn to_jvalue<'e>(&self, env: &'e JNIEnv<'e>) -> Result<JValue<'e>>;
+}
+
+/// The types of the fields of a struct deriving `FromJava`. The same types
+/// as for `IntoJavaField` are supported.
+pub trait FromJavaField: Sized {
+    /// The java type signature of the field, e.g. `I` or
+    /// `Ljava/lang/String;`.
+    fn descriptor() -> &'static str;
+
+    /// Convert from a value read from a field or returned by a getter. An
+    /// object is expected to be a local ref, which is deleted.
+    fn from_jvalue<'e>(env: &'e JNIEnv<'e>, val: JValue<'e>) -> Result<Self>;
+}
+
+macro_rules! java_field_primitive {
+    ( $ty:ty, $desc:expr, $getter:ident ) => {
+        impl IntoJavaField for $ty {
+            fn descriptor() -> &'static str {
+                $desc
+            }
+
+            fn to_jvalue<'e>(&self, _env: &'e JNIEnv<'e>) -> Result<JValue<'e>> {
+                Ok((*self).into())
+            }
+        }
+
+        impl FromJavaField for $ty {
+            fn descriptor() -> &'static str {
+                $desc
+            }
+
+            fn from_jvalue<'e>(_env: &'e JNIEnv<'e>, val: JValue<'e>) -> Result<Self> {
+                val.$getter()
+            }
+        }
+    };
+}
+
+java_field_primitive!(bool, "Z", z);
+java_field_primitive!(jbyte, "B", b);
+java_field_primitive!(jchar, "C", c);
+java_field_primitive!(jshort, "S", s);
+java_field_primitive!(jint, "I", i);
+java_field_primitive!(jlong, "J", j);
+java_field_primitive!(jfloat, "F", f);
+java_field_primitive!(jdouble, "D", d);
+
+impl IntoJavaField for String {
+    fn descriptor() -> &'static str {
+        "Ljava/lang/String;"
+    }
+
+    fn to_jvalue<'e>(&self, env: &'e JNIEnv<'e>) -> Result<JValue<'e>> {
+        Ok(JObject::from(env.new_string(self.as_str())?).into())
+    }
+}
+
+impl FromJavaField for String {
+    fn descriptor() -> &'static str {
+        "Ljava/lang/String;"
+    }
+
+    fn from_jvalue<'e>(env: &'e JNIEnv<'e>, val: JValue<'e>) -> Result<Self> {
+        let obj = env.auto_local(val.l()?);
+        let s = env.get_string(obj.as_obj().into())?.into();
+        Ok(s)
+    }
+}
+
+impl IntoJavaField for Vec<u8> {
+    fn descriptor() -> &'static str {
+        "[B"
+    }
+
+    fn to_jvalue<'e>(&self, env: &'e JNIEnv<'e>) -> Result<JValue<'e>> {
+        Ok(JObject::from(env.byte_array_from_slice(self)?).into())
+    }
+}
+
+impl FromJavaField for Vec<u8> {
+    fn descriptor() -> &'static str {
+        "[B"
+    }
+
+    fn from_jvalue<'e>(env: &'e JNIEnv<'e>, val: JValue<'e>) -> Result<Self> {
+        let obj = env.auto_local(val.l()?);
+        env.convert_byte_array(obj.as_obj().into_inner())
+    }
+}
+
+impl<T: IntoJavaField> IntoJavaField for Option<T> {
+    fn descriptor() -> &'static str {
+        T::descriptor()
+    }
+
+    fn to_jvalue<'e>(&self, env: &'e JNIEnv<'e>) -> Result<JValue<'e>> {
+        match *self {
+            Some(ref val) => val.to_jvalue(env),
+            None => Ok(JObject::null().into()),
+        }
+    }
+}
+
+impl<T: FromJavaField> FromJavaField for Option<T> {
+    fn descriptor() -> &'static str {
+        T::descriptor()
+    }
+
+    fn from_jvalue<'e>(env: &'e JNIEnv<'e>, val: JValue<'e>) -> Result<Self> {
+        match val {
+            JValue::Object(obj) if obj.is_null() => Ok(None),
+            _ => T::from_jvalue(env, val).map(Some),
+        }
+    }
+}
@@ -0,0 +1,244 @@
+use std::fmt::Display;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{
+    Context,
+    Poll,
+    Wake,
+    Waker,
+};
+use std::thread::{
+    self,
+    Thread,
+};
+
+use JNIEnv;
+use JavaVM;
+
+use errors::*;
+
+use objects::GlobalRef;
+use objects::JObject;
+use objects::JValue;
+
+/// Values a rust future can complete a java `CompletableFuture` with.
+pub trait IntoJavaObject: Send + 'static {
+    /// Convert the value to a java object in the given env.
+    fn into_java_object<'e>(self, env: &'e JNIEnv<'e>) -> Result<JObject<'e>>;
+}
+
+impl IntoJavaObject for () {
+    fn into_java_object<'e>(self, _: &'e JNIEnv<'e>) -> Result<JObject<'e>> {
+        Ok(JObject::null())
+    }
+}
+
+impl IntoJavaObject for String {
+    fn into_java_object<'e>(self, env: &'e JNIEnv<'e>) -> Result<JObject<'e>> {
+        Ok(env.new_string(self)?.into())
+    }
+}
+
+impl IntoJavaObject for GlobalRef {
+    fn into_java_object<'e>(self, env: &'e JNIEnv<'e>) -> Result<JObject<'e>> {
+        env.new_local_ref::<JObject>(self.as_obj())
+    }
+}
+
+impl<T: IntoJavaObject> IntoJavaObject for Option<T> {
+    fn into_java_object<'e>(self, env: &'e JNIEnv<'e>) -> Result<JObject<'e>> {
+        match self {
+            Some(val) => val.into_java_object(env),
+            None => Ok(JObject::null()),
+        }
+    }
+}
+
+impl<'a> JNIEnv<'a> {
+    /// Run a rust future and hand its result to java through a
+    /// `java.util.concurrent.CompletableFuture`, which is returned right away.
+    ///
+    /// The future is driven on a new thread, which attaches to the VM once the
+    /// future is done, completes the java future and detaches again. An `Ok`
+    /// value is converted with `IntoJavaObject` and passed to `complete`; an
+    /// `Err` is passed to `completeExceptionally` as a `RuntimeException`
+    /// carrying the error message.
+    ///
+    /// A thread per future is only reasonable for a few long-running ones. To
+    /// run them on an async runtime instead, use `spawn_to_java_future_with`.
+    pub fn spawn_to_java_future<F, T, E>(&self, vm: JavaVM, fut: F) -> Result<JObject<'a>>
+    where
+        F: Future<Output = ::std::result::Result<T, E>> + Send + 'static,
+        T: IntoJavaObject,
+        E: Display,
+    {
+        self.spawn_to_java_future_with(vm, fut, |completion| {
+            thread::spawn(move || block_on(completion));
+        })
+    }
+
+    /// Same as `spawn_to_java_future`, but hands driving the future to
+    /// `spawn`, e.g. to run it on an async runtime's executor:
+    ///
+    /// ```rust,ignore
+    /// let java_future = env.spawn_to_java_future_with(vm, fut, |f| {
+    ///     tokio::spawn(f);
+    /// })?;
+    /// ```
+    ///
+    /// `spawn` is given a future that polls `fut` and completes the java
+    /// future with its result. The thread that polls it last is attached to
+    /// the VM for the completion if it isn't already. If it's dropped before
+    /// `fut` is done, e.g. because `fut` panicked or the runtime shut down,
+    /// the java future is completed exceptionally.
+    pub fn spawn_to_java_future_with<F, T, E, S>(&self, vm: JavaVM, fut: F, spawn: S) -> Result<JObject<'a>>
+    where
+        F: Future<Output = ::std::result::Result<T, E>> + Send + 'static,
+        T: IntoJavaObject,
+        E: Display,
+        S: FnOnce(Pin<Box<dyn Future<Output = ()> + Send>>),
+    {
+        let java_future = self.new_object("java/util/concurrent/CompletableFuture", "()V", &[])?;
+        let global = self.new_global_ref(java_future)?;
+
+        spawn(Box::pin(Completion {
+            fut: Box::pin(fut),
+            target: Some((vm, global)),
+        }));
+
+        Ok(java_future)
+    }
+}
+
+/// Polls a rust future, then completes a java future with its result.
+struct Completion<F> {
+    fut: Pin<Box<F>>,
+    target: Option<(JavaVM, GlobalRef)>,
+}
+
+impl<F, T, E> Future for Completion<F>
+where
+    F: Future<Output = ::std::result::Result<T, E>>,
+    T: IntoJavaObject,
+    E: Display,
+{
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        let result = match self.fut.as_mut().poll(cx) {
+            Poll::Ready(result) => result,
+            Poll::Pending => return Poll::Pending,
+        };
+        let (vm, global) = match self.target.take() {
+            Some(target) => target,
+            None => return Poll::Ready(()),
+        };
+
+        let env = match vm.attach_current_thread() {
+            Ok(env) => env,
+            Err(e) => {
+                warn!("error attaching to complete java future: {}", e);
+                return Poll::Ready(());
+            }
+        };
+        if let Err(e) = complete(&env, global.as_obj(), result) {
+            warn!("error completing java future: {}", e);
+        }
+        // delete the global ref while still attached, rather than having its
+        // drop attach the thread again
+        drop(global);
+        Poll::Ready(())
+    }
+}
+
+impl<F> Drop for Completion<F> {
+    fn drop(&mut self) {
+        // Dropped before the future finished: it panicked, or the executor
+        // gave up on it. Don't leave the java side waiting forever.
+        let (vm, global) = match self.target.take() {
+            Some(target) => target,
+            None => return,
+        };
+
+        let env = match vm.attach_current_thread() {
+            Ok(env) => env,
+            Err(e) => {
+                warn!("error attaching to complete dropped java future: {}", e);
+                return;
+            }
+        };
+        if let Err(e) = complete_exceptionally(&env, global.as_obj(), "rust future dropped before completing") {
+            let _ = env.exception_clear();
+            warn!("error completing dropped java future: {}", e);
+        }
+        drop(global);
+    }
+}
+
+fn complete<T, E>(
+    env: &JNIEnv,
+    java_future: JObject,
+    result: ::std::result::Result<T, E>,
+) -> Result<()>
+where
+    T: IntoJavaObject,
+    E: Display,
+{
+    let res = match result {
+        Ok(val) => val.into_java_object(env).and_then(|val| {
+            env.call_method(java_future, "complete", "(Ljava/lang/Object;)Z", &[val.into()])
+        }),
+        Err(e) => complete_exceptionally(env, java_future, &e.to_string()),
+    };
+
+    match res {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            // Don't leave the java side waiting forever.
+            env.exception_clear()?;
+            complete_exceptionally(env, java_future, &e.to_string())?;
+            Err(e)
+        }
+    }
+}
+
+fn complete_exceptionally<'a>(
+    env: &'a JNIEnv<'a>,
+    java_future: JObject,
+    msg: &str,
+) -> Result<JValue<'a>> {
+    let msg = env.new_string(msg)?;
+    let ex = env.new_object(
+        "java/lang/RuntimeException",
+        "(Ljava/lang/String;)V",
+        &[JObject::from(msg).into()],
+    )?;
+    env.call_method(
+        java_future,
+        "completeExceptionally",
+        "(Ljava/lang/Throwable;)Z",
+        &[ex.into()],
+    )
+}
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark()
+    }
+}
+
+// Minimal executor: poll on the current thread, parking it until woken.
+fn block_on<F: Future>(fut: F) -> F::Output {
+    let mut fut = Box::pin(fut);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        match fut.as_mut().poll(&mut cx) {
+            Poll::Ready(val) => return val,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
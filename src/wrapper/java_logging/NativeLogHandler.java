@@ -0,0 +1,31 @@
+package jni.logging;
+
+import java.util.logging.Handler;
+import java.util.logging.LogRecord;
+import java.util.logging.SimpleFormatter;
+
+/**
+ * Forwards java.util.logging records to the rust `log` crate.
+ *
+ * Compiled with `javac --release 8 NativeLogHandler.java`; the class file is
+ * embedded in the jni crate and defined at runtime.
+ */
+public class NativeLogHandler extends Handler {
+    private final SimpleFormatter formatter = new SimpleFormatter();
+
+    @Override
+    public void publish(LogRecord record) {
+        if (record == null || !isLoggable(record)) {
+            return;
+        }
+        log(record.getLevel().intValue(), record.getLoggerName(), formatter.formatMessage(record));
+    }
+
+    @Override
+    public void flush() {}
+
+    @Override
+    public void close() {}
+
+    private static native void log(int level, String logger, String message);
+}
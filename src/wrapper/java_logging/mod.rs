@@ -0,0 +1,139 @@
+use std::os::raw::c_void;
+use std::sync::atomic::{
+    AtomicBool,
+    Ordering,
+};
+
+use log::LogLevel;
+
+use JNIEnv;
+
+use errors::*;
+
+use objects::JClass;
+use objects::JString;
+use objects::JValue;
+use objects::NativeMethod;
+
+use sys::jint;
+
+const HANDLER_CLASS: &str = "jni/logging/NativeLogHandler";
+
+/// Compiled from `NativeLogHandler.java` next to this file.
+const HANDLER_BYTES: &[u8] = include_bytes!("NativeLogHandler.class");
+
+/// Target used for records from anonymous loggers.
+const DEFAULT_TARGET: &str = "java";
+
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+impl<'a> JNIEnv<'a> {
+    /// Send records logged through `java.util.logging` to the `log` crate, so
+    /// that java and rust logs end up in the same place.
+    ///
+    /// This adds a handler to the root logger that passes each record to
+    /// `log!`, using the java logger name as the target and the message
+    /// formatted with its parameters. Levels map as follows:
+    ///
+    /// * `SEVERE` to `Error`
+    /// * `WARNING` to `Warn`
+    /// * `INFO` to `Info`
+    /// * `CONFIG` and `FINE` to `Debug`
+    /// * `FINER`, `FINEST` and anything lower to `Trace`
+    ///
+    /// Java loggers still filter records by their own level first (the root
+    /// logger defaults to `INFO`), so lower levels have to be enabled on the
+    /// java side too. The handler is only installed once per process; later
+    /// calls do nothing.
+    pub fn install_java_log_handler(&'a self) -> Result<()> {
+        if INSTALLED.swap(true, Ordering::SeqCst) {
+            return Ok(());
+        }
+        let res = self.add_java_log_handler();
+        if res.is_err() {
+            INSTALLED.store(false, Ordering::SeqCst);
+        }
+        res
+    }
+
+    fn add_java_log_handler(&'a self) -> Result<()> {
+        // `Logger.getLogger` needs a java caller, which native threads don't
+        // have, so go through the log manager instead.
+        let manager = self.call_static_method(
+            "java/util/logging/LogManager",
+            "getLogManager",
+            "()Ljava/util/logging/LogManager;",
+            &[],
+        )?.l()?;
+        let manager = self.auto_local(manager);
+        let root_name = self.auto_local(self.new_string("")?.into());
+        let root = self.call_method(
+            manager.as_obj(),
+            "getLogger",
+            "(Ljava/lang/String;)Ljava/util/logging/Logger;",
+            &[JValue::from(root_name.as_obj())],
+        )?.l()?;
+        let root = self.auto_local(non_null!(root, "root logger"));
+
+        let loader = self.call_static_method(
+            "java/lang/ClassLoader",
+            "getSystemClassLoader",
+            "()Ljava/lang/ClassLoader;",
+            &[],
+        )?.l()?;
+        let loader = self.auto_local(loader);
+
+        let class = self.define_class(HANDLER_CLASS, loader.as_obj(), HANDLER_BYTES)?;
+        let class = self.auto_local(class.into());
+        let class = JClass::from(class.as_obj().into_inner());
+
+        let log = unsafe {
+            NativeMethod::new("log", "(ILjava/lang/String;Ljava/lang/String;)V", log_record as *mut c_void)?
+        };
+        self.register_native_methods(class, &[log])?;
+
+        let handler = self.auto_local(self.new_object(class, "()V", &[])?);
+        self.call_method(
+            root.as_obj(),
+            "addHandler",
+            "(Ljava/util/logging/Handler;)V",
+            &[JValue::from(handler.as_obj())],
+        )?;
+        Ok(())
+    }
+}
+
+/// Map a `java.util.logging.Level` value to a rust log level.
+fn log_level(level: jint) -> LogLevel {
+    match level {
+        l if l >= 1000 => LogLevel::Error,
+        l if l >= 900 => LogLevel::Warn,
+        l if l >= 800 => LogLevel::Info,
+        l if l >= 500 => LogLevel::Debug,
+        _ => LogLevel::Trace,
+    }
+}
+
+fn string_or<'a>(env: &JNIEnv<'a>, s: JString, default: &str) -> Result<String> {
+    if s.is_null() {
+        Ok(default.into())
+    } else {
+        Ok(env.get_string(s)?.into())
+    }
+}
+
+extern "system" fn log_record(
+    env: JNIEnv,
+    _class: JClass,
+    level: jint,
+    logger: JString,
+    message: JString,
+) {
+    native_try!(env, {
+        let target = string_or(&env, logger, DEFAULT_TARGET)?;
+        let target = if target.is_empty() { DEFAULT_TARGET.into() } else { target };
+        let message = string_or(&env, message, "")?;
+        log!(target: &target, log_level(level), "{}", message);
+        Ok(())
+    })
+}
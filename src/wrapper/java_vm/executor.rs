@@ -0,0 +1,109 @@
+use std::cell::RefCell;
+use std::sync::Arc;
+
+use errors::*;
+
+use objects::JObject;
+
+use JNIEnv;
+use JavaVM;
+
+/// The local frame capacity `Executor::with_attached` ensures.
+pub const DEFAULT_EXECUTOR_CAPACITY: i32 = 32;
+
+thread_local! {
+    // the VM the thread was attached to by an executor, which detaches it
+    // when the thread exits
+    static ATTACHMENT: RefCell<Option<Attachment>> = RefCell::new(None);
+}
+
+struct Attachment(Arc<JavaVM>);
+
+impl Drop for Attachment {
+    fn drop(&mut self) {
+        if self.0.get_env().is_err() {
+            return;
+        }
+        if let Err(e) = unsafe { self.0.detach_current_thread() } {
+            debug!("error detaching current thread: {:#?}", e);
+        }
+    }
+}
+
+/// Runs closures with a `JNIEnv` on whatever thread calls it, attaching the
+/// thread to the VM the first time and keeping it attached until the thread
+/// exits. This makes calling into java from a thread pool cheap: each worker
+/// pays for attaching only once.
+///
+/// Threads that were already attached, e.g. by java, are used as they are
+/// and left attached.
+///
+/// # Example
+/// ```rust,ignore
+/// let executor = Executor::new(vm);
+/// pool.spawn(move || {
+///     executor.with_attached(|env| {
+///         env.call_static_method("com/example/Lib", "progress", "(I)V", &[50.into()])?;
+///         Ok(())
+///     }).unwrap();
+/// });
+/// ```
+#[derive(Clone)]
+pub struct Executor {
+    vm: Arc<JavaVM>,
+}
+
+impl Executor {
+    /// Create an executor for the given VM.
+    pub fn new(vm: Arc<JavaVM>) -> Self {
+        Executor { vm: vm }
+    }
+
+    /// The VM the executor attaches threads to.
+    pub fn java_vm(&self) -> &Arc<JavaVM> {
+        &self.vm
+    }
+
+    /// Run the closure with the env of the current thread, attaching it
+    /// first if needed. The closure runs in a new local frame with room for
+    /// `DEFAULT_EXECUTOR_CAPACITY` local refs, and every local ref it creates
+    /// is freed when it returns, since a thread that never returns to java
+    /// would otherwise keep them forever.
+    pub fn with_attached<F, R>(&self, f: F) -> Result<R>
+    where
+        F: FnOnce(&JNIEnv) -> Result<R>,
+    {
+        self.with_attached_capacity(DEFAULT_EXECUTOR_CAPACITY, f)
+    }
+
+    /// Like `with_attached`, with room for `capacity` local refs in the frame.
+    pub fn with_attached_capacity<F, R>(&self, capacity: i32, f: F) -> Result<R>
+    where
+        F: FnOnce(&JNIEnv) -> Result<R>,
+    {
+        let env = self.attach()?;
+
+        env.push_local_frame(capacity)?;
+        let res = f(&env);
+        env.pop_local_frame(JObject::null())?;
+        res
+    }
+
+    fn attach(&self) -> Result<JNIEnv> {
+        match self.vm.get_env() {
+            Ok(env) => return Ok(env),
+            Err(e) => match *e.kind() {
+                ErrorKind::ThreadDetached => {}
+                _ => return Err(e),
+            },
+        }
+
+        // a previous attachment of this thread has been detached by someone
+        // else, and dropping it does nothing
+        ATTACHMENT.with(|attachment| attachment.borrow_mut().take());
+
+        let env = self.vm.attach_current_thread_permanently()?;
+        ATTACHMENT.with(|attachment| *attachment.borrow_mut() = Some(Attachment(self.vm.clone())));
+        Ok(env)
+    }
+}
@@ -55,6 +55,30 @@ impl InitArgsBuilder {
         s
     }
 
+    /// Set the class path the VM loads application classes from, i.e. the
+    /// `java.class.path` property. Entries are joined with the platform's
+    /// path separator.
+    pub fn classpath<I, S>(self, paths: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let separator = if cfg!(windows) { ";" } else { ":" };
+        let paths: Vec<String> = paths.into_iter().map(|p| p.as_ref().to_owned()).collect();
+        self.property("java.class.path", &paths.join(separator))
+    }
+
+    /// Set the maximum heap size, e.g. `"512m"` or `"2g"`. Same as the `-Xmx`
+    /// option.
+    pub fn max_heap(self, size: &str) -> Self {
+        self.option(&format!("-Xmx{}", size))
+    }
+
+    /// Set a system property, like `-Dkey=value` on the command line.
+    pub fn property(self, key: &str, value: &str) -> Self {
+        self.option(&format!("-D{}={}", key, value))
+    }
+
     /// Set JNI version for the init args
     ///
     /// Default: V1
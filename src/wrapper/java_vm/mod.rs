@@ -5,3 +5,9 @@ pub use self::init_args::*;
 
 mod vm;
 pub use self::vm::*;
+
+mod on_load;
+pub use self::on_load::*;
+
+mod executor;
+pub use self::executor::*;
@@ -0,0 +1,91 @@
+use std::os::raw::c_void;
+use std::panic::{
+    self,
+    AssertUnwindSafe,
+};
+use std::ptr;
+use std::sync::atomic::{
+    AtomicPtr,
+    Ordering,
+};
+
+use errors::*;
+
+use sys;
+
+use wrapper::native_try::panic_message;
+
+use JNIVersion;
+use JavaVM;
+
+static LOADED_VM: AtomicPtr<sys::JavaVM> = AtomicPtr::new(ptr::null_mut());
+
+/// The java VM that loaded this library, if it was stored by a
+/// `jni_on_load!` with `store_vm`, and not yet unloaded.
+///
+/// Any thread can attach to it with `attach_current_thread`.
+pub fn loaded_java_vm() -> Option<JavaVM> {
+    let ptr = LOADED_VM.load(Ordering::Acquire);
+    unsafe { JavaVM::from_raw(ptr).ok() }
+}
+
+/// The function behind `jni_on_load!`.
+///
+/// # Safety
+///
+/// `vm` must be the VM pointer passed to `JNI_OnLoad`.
+#[doc(hidden)]
+pub unsafe fn on_load<F>(vm: *mut sys::JavaVM, reserved: *mut c_void, version: JNIVersion, store_vm: bool, f: F) -> sys::jint
+where
+    F: FnOnce(&JavaVM, *mut c_void) -> Result<()>,
+{
+    let java_vm = match JavaVM::from_raw(vm) {
+        Ok(java_vm) => java_vm,
+        Err(e) => {
+            error!("JNI_OnLoad failed: {}", e);
+            return sys::JNI_ERR;
+        }
+    };
+
+    match panic::catch_unwind(AssertUnwindSafe(|| f(&java_vm, reserved))) {
+        Ok(Ok(())) => {
+            if store_vm {
+                LOADED_VM.store(vm, Ordering::Release);
+            }
+            version.into()
+        }
+        Ok(Err(e)) => {
+            error!("JNI_OnLoad failed: {}", e);
+            sys::JNI_ERR
+        }
+        Err(payload) => {
+            error!("panic in JNI_OnLoad: {}", panic_message(&payload));
+            sys::JNI_ERR
+        }
+    }
+}
+
+/// The function behind `jni_on_unload!`.
+///
+/// # Safety
+///
+/// `vm` must be the VM pointer passed to `JNI_OnUnload`.
+#[doc(hidden)]
+pub unsafe fn on_unload<F>(vm: *mut sys::JavaVM, reserved: *mut c_void, f: F)
+where
+    F: FnOnce(&JavaVM, *mut c_void),
+{
+    let _ = LOADED_VM.compare_exchange(vm, ptr::null_mut(), Ordering::AcqRel, Ordering::Acquire);
+
+    let java_vm = match JavaVM::from_raw(vm) {
+        Ok(java_vm) => java_vm,
+        Err(e) => {
+            error!("JNI_OnUnload failed: {}", e);
+            return;
+        }
+    };
+
+    if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| f(&java_vm, reserved))) {
+        error!("panic in JNI_OnUnload: {}", panic_message(&payload));
+    }
+}
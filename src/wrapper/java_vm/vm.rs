@@ -3,12 +3,35 @@ use errors::*;
 
 use sys;
 
+use std::cell::Cell;
 use std::ops::Deref;
 use std::ptr;
 
 #[cfg(feature = "invocation")]
 use InitArgs;
 
+thread_local! {
+    // the env of the current thread and the VM it belongs to, set when this
+    // crate attaches the thread or looks its env up, and cleared when it
+    // detaches it
+    static CACHED_ENV: Cell<(*mut sys::JavaVM, *mut sys::JNIEnv)> = Cell::new((ptr::null_mut(), ptr::null_mut()));
+}
+
+fn cache_env(vm: *mut sys::JavaVM, env: *mut sys::JNIEnv) {
+    CACHED_ENV.with(|cached| cached.set((vm, env)));
+}
+
+fn cached_env(vm: *mut sys::JavaVM) -> Option<*mut sys::JNIEnv> {
+    CACHED_ENV.with(|cached| match cached.get() {
+        (cached_vm, env) if cached_vm == vm && !env.is_null() => Some(env),
+        _ => None,
+    })
+}
+
+fn clear_cached_env() {
+    cache_env(ptr::null_mut(), ptr::null_mut());
+}
+
 /// The invocation API.
 pub struct JavaVM(*mut sys::JavaVM);
 
@@ -33,34 +56,122 @@ impl JavaVM {
 
             let vm = Self::from_raw(ptr)?;
             java_vm_unchecked!(vm.0, DetachCurrentThread);
+            clear_cached_env();
 
             Ok(vm)
         }
     }
 
+    /// List the java VMs already created in this process, e.g. by the java
+    /// launcher of the application that loaded this library, or by another
+    /// library. Usually there's at most one, since most VMs don't support
+    /// more per process.
+    ///
+    /// Dropping a `JavaVM` doesn't destroy the VM, so the returned values can
+    /// be used and dropped freely.
+    #[cfg(feature = "invocation")]
+    pub fn list_created_vms() -> Result<Vec<JavaVM>> {
+        let mut count: sys::jsize = 0;
+        unsafe {
+            jni_error_code_to_result(sys::JNI_GetCreatedJavaVMs(ptr::null_mut(), 0, &mut count))?;
+        }
+        if count <= 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut ptrs: Vec<*mut sys::JavaVM> = vec![ptr::null_mut(); count as usize];
+        unsafe {
+            jni_error_code_to_result(sys::JNI_GetCreatedJavaVMs(ptrs.as_mut_ptr(), count, &mut count))?;
+        }
+        // the VM count may have changed between the two calls
+        ptrs.truncate(count.max(0) as usize);
+
+        ptrs.into_iter()
+            .map(|ptr| unsafe { Self::from_raw(ptr) })
+            .collect()
+    }
+
+    /// Get the java VM already created in this process, so that threads can
+    /// attach to it without being handed a `JNIEnv` first. Fails with
+    /// `ErrorKind::NoJavaVM` if there's none. See `list_created_vms`.
+    #[cfg(feature = "invocation")]
+    pub fn singleton() -> Result<JavaVM> {
+        Self::list_created_vms()?
+            .into_iter()
+            .next()
+            .ok_or_else(|| ErrorKind::NoJavaVM.into())
+    }
+
     /// Create a JavaVM from a raw pointer.
     pub unsafe fn from_raw(ptr: *mut sys::JavaVM) -> Result<Self> {
         non_null!(ptr, "from_raw ptr argument");
         Ok(JavaVM(ptr))
     }
 
+    /// Get the raw JavaVM pointer
+    pub fn get_java_vm_pointer(&self) -> *mut sys::JavaVM {
+        self.0
+    }
+
     /// Attaches the current thread to a Java VM. The resulting `AttachGuard`
     /// can be dereferenced to a `JNIEnv` and automatically detaches the thread
     /// when dropped.
+    ///
+    /// If the thread is already attached, the guard just gives access to its
+    /// env and leaves it attached when dropped, so that nested attaches don't
+    /// detach the thread from under whoever attached it first.
     pub fn attach_current_thread(&self) -> Result<AttachGuard> {
+        if let Ok(env) = self.get_env() {
+            return Ok(AttachGuard {
+                java_vm: self,
+                env: env,
+                should_detach: false,
+            });
+        }
+
         let mut ptr = ptr::null_mut();
         unsafe {
             let res = java_vm_unchecked!(self.0, AttachCurrentThread, &mut ptr, ptr::null_mut());
             jni_error_code_to_result(res)?;
+            cache_env(self.0, ptr as *mut sys::JNIEnv);
 
             let env = JNIEnv::from_raw(ptr as *mut sys::JNIEnv)?;
             Ok(AttachGuard {
                 java_vm: self,
                 env: env,
+                should_detach: true,
             })
         }
     }
 
+    /// Attaches the current thread to a Java VM for good: unlike with
+    /// `attach_current_thread`, nothing detaches it, so it must be detached
+    /// with `detach_current_thread` before it exits.
+    ///
+    /// Does nothing but return the env if the thread is already attached.
+    pub fn attach_current_thread_permanently(&self) -> Result<JNIEnv> {
+        if let Ok(env) = self.get_env() {
+            return Ok(env);
+        }
+
+        let mut ptr = ptr::null_mut();
+        unsafe {
+            let res = java_vm_unchecked!(self.0, AttachCurrentThread, &mut ptr, ptr::null_mut());
+            jni_error_code_to_result(res)?;
+            cache_env(self.0, ptr as *mut sys::JNIEnv);
+
+            JNIEnv::from_raw(ptr as *mut sys::JNIEnv)
+        }
+    }
+
+    /// Detaches the current thread from the Java VM. Any `JNIEnv` obtained
+    /// for the thread must no longer be used.
+    pub unsafe fn detach_current_thread(&self) -> Result<()> {
+        let res = java_vm_unchecked!(self.0, DetachCurrentThread);
+        clear_cached_env();
+        jni_error_code_to_result(res)
+    }
+
     /// Attaches the current thread to a Java VM as a daemon.
     pub fn attach_current_thread_as_daemon(&self) -> Result<JNIEnv> {
         let mut ptr = ptr::null_mut();
@@ -72,6 +183,7 @@ impl JavaVM {
                 ptr::null_mut()
             );
             jni_error_code_to_result(res)?;
+            cache_env(self.0, ptr as *mut sys::JNIEnv);
 
             JNIEnv::from_raw(ptr as *mut sys::JNIEnv)
         }
@@ -81,13 +193,42 @@ impl JavaVM {
     /// `ErrorKind::Detached`
     /// if the current thread is not attached to the java VM.
     pub fn get_env(&self) -> Result<JNIEnv> {
+        let env = self.lookup_env()?;
+        cache_env(self.0, env);
+        unsafe { JNIEnv::from_raw(env) }
+    }
+
+    /// Like `get_env`, but returns the env cached for the current thread, if
+    /// any, without going through `GetEnv`. The cache is filled in by
+    /// `get_env` and by attaching through `JavaVM`, and cleared when the
+    /// thread is detached through `JavaVM` or an `AttachGuard`.
+    ///
+    /// # Safety
+    ///
+    /// The current thread must not have been detached by other means, such as
+    /// a raw `DetachCurrentThread` call or another library, since it was
+    /// cached. The returned env would be dangling. Debug builds check the
+    /// cached env with `GetEnv`, and panic if it is stale.
+    pub unsafe fn get_env_cached(&self) -> Result<JNIEnv> {
+        match cached_env(self.0) {
+            Some(env) => {
+                debug_assert!(
+                    self.lookup_env().ok() == Some(env),
+                    "the cached env is stale: the thread was detached without going through JavaVM"
+                );
+                JNIEnv::from_raw(env)
+            }
+            None => self.get_env(),
+        }
+    }
+
+    fn lookup_env(&self) -> Result<*mut sys::JNIEnv> {
         let mut ptr = ptr::null_mut();
         unsafe {
             let res = java_vm_unchecked!(self.0, GetEnv, &mut ptr, sys::JNI_VERSION_1_1);
             jni_error_code_to_result(res)?;
-
-            JNIEnv::from_raw(ptr as *mut sys::JNIEnv)
         }
+        Ok(ptr as *mut sys::JNIEnv)
     }
 }
 
@@ -97,6 +238,7 @@ impl JavaVM {
 pub struct AttachGuard<'a> {
     java_vm: &'a JavaVM,
     env: JNIEnv<'a>,
+    should_detach: bool,
 }
 
 impl<'a> AttachGuard<'a> {
@@ -104,6 +246,7 @@ impl<'a> AttachGuard<'a> {
         unsafe {
             java_vm_unchecked!(self.java_vm.0, DetachCurrentThread);
         }
+        clear_cached_env();
 
         Ok(())
     }
@@ -119,6 +262,9 @@ impl<'a> Deref for AttachGuard<'a> {
 
 impl<'a> Drop for AttachGuard<'a> {
     fn drop(&mut self) {
+        if !self.should_detach {
+            return;
+        }
         match self.detach() {
             Ok(()) => (),
             Err(e) => debug!("error detaching current thread: {:#?}", e),
@@ -0,0 +1,174 @@
+use std::char;
+use std::slice;
+
+use serde::de::{
+    self,
+    DeserializeOwned,
+    DeserializeSeed,
+    IntoDeserializer,
+    MapAccess,
+    Visitor,
+};
+
+use JNIEnv;
+
+use errors::*;
+
+use descriptors::ClassCache;
+
+use objects::JObject;
+use objects::JValue;
+
+use super::field_signature;
+
+/// Build a rust value from a java object, reading each struct field from the
+/// java field of the same name. See the module docs for how types map.
+///
+/// # Example
+/// ```rust,ignore
+/// #[derive(Deserialize)]
+/// struct Point {
+///     x: i32,
+///     y: i32,
+/// }
+///
+/// let point: Point = jni_serde::from_object(&env, java_point)?;
+/// ```
+pub fn from_object<'e, T>(env: &'e JNIEnv<'e>, obj: JObject<'e>) -> Result<T>
+where
+    T: DeserializeOwned,
+{
+    non_null!(obj, "from_object obj argument");
+    T::deserialize(ValueDeserializer {
+        env: env,
+        value: JValue::Object(obj),
+    })
+}
+
+/// A value read from a java field, or the object passed to `from_object`.
+struct ValueDeserializer<'e> {
+    env: &'e JNIEnv<'e>,
+    value: JValue<'e>,
+}
+
+impl<'de, 'e> de::Deserializer<'de> for ValueDeserializer<'e> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            JValue::Bool(b) => visitor.visit_bool(b != 0),
+            JValue::Byte(b) => visitor.visit_i8(b),
+            JValue::Char(c) => match char::from_u32(c as u32) {
+                Some(c) => visitor.visit_char(c),
+                None => Err(format!("java char {:#x} is not a valid rust char", c).into()),
+            },
+            JValue::Short(s) => visitor.visit_i16(s),
+            JValue::Int(i) => visitor.visit_i32(i),
+            JValue::Long(l) => visitor.visit_i64(l),
+            JValue::Float(f) => visitor.visit_f32(f),
+            JValue::Double(d) => visitor.visit_f64(d),
+            JValue::Void => visitor.visit_unit(),
+            JValue::Object(obj) => {
+                // kept for the process, rather than found for every value
+                static STRING: ClassCache = ClassCache::new();
+                if obj.is_null() {
+                    visitor.visit_none()
+                } else if self.env.is_instance_of(obj, STRING.class("java/lang/String"))? {
+                    let s: String = self.env.get_string(obj.into())?.into();
+                    visitor.visit_string(s)
+                } else {
+                    Err("java objects other than strings can only be read into structs".into())
+                }
+            }
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            JValue::Object(obj) if obj.is_null() => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let obj = match self.value {
+            JValue::Object(obj) if !obj.is_null() => obj,
+            _ => return Err(format!("expected a java object for {}", name).into()),
+        };
+        visitor.visit_map(FieldAccess {
+            env: self.env,
+            obj: obj,
+            fields: fields.iter(),
+            current: None,
+        })
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string bytes
+        byte_buf unit unit_struct newtype_struct seq tuple tuple_struct map
+        enum identifier ignored_any
+    }
+}
+
+/// Walks the fields of a rust struct, reading each from the java object.
+struct FieldAccess<'e> {
+    env: &'e JNIEnv<'e>,
+    obj: JObject<'e>,
+    fields: slice::Iter<'static, &'static str>,
+    current: Option<&'static str>,
+}
+
+impl<'de, 'e> MapAccess<'de> for FieldAccess<'e> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        let name = match self.fields.next() {
+            Some(name) => *name,
+            None => return Ok(None),
+        };
+        self.current = Some(name);
+        seed.deserialize(<&str as IntoDeserializer<Error>>::into_deserializer(name))
+            .map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let name = match self.current.take() {
+            Some(name) => name,
+            None => return Err("field value requested before its name".into()),
+        };
+
+        let sig = field_signature(self.env, self.obj, name)?;
+        let value = self.env.get_field(self.obj, name, sig.as_str())?;
+
+        let res = seed.deserialize(ValueDeserializer {
+            env: self.env,
+            value: value,
+        });
+        if let JValue::Object(obj) = value {
+            if !obj.is_null() {
+                self.env.delete_local_ref(obj)?;
+            }
+        }
+        res
+    }
+}
@@ -0,0 +1,116 @@
+//! Mapping between java objects and rust types with `serde`.
+//!
+//! Java objects are treated as structs: each field of the rust struct is read
+//! from (or written to) the java field of the same name, public or not, on
+//! the object's class or one of its superclasses. The java field's declared
+//! type decides how the value is converted, so it has to match the rust type
+//! closely: `int` for `i32`, `java.lang.String` for `String`, another object
+//! for a nested struct and so on. `Option` maps to a nullable field. When
+//! serializing, sequences and maps become a `java.util.ArrayList` and a
+//! `java.util.HashMap`.
+//!
+//! Field types are looked up through reflection for every field, so this is
+//! meant for moving DTOs across the boundary, not for hot paths.
+
+use std::fmt::Display;
+
+use serde::{
+    de,
+    ser,
+};
+
+use JNIEnv;
+
+use errors::*;
+
+use descriptors::ClassCache;
+
+use objects::JObject;
+use objects::JThrowable;
+use objects::JValue;
+
+mod de_impl;
+pub use self::de_impl::from_object;
+
+mod ser_impl;
+pub use self::ser_impl::to_object;
+
+impl de::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        msg.to_string().into()
+    }
+}
+
+impl ser::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        msg.to_string().into()
+    }
+}
+
+/// Find the type signature of a field declared on the class of `obj` or one
+/// of its superclasses.
+fn field_signature<'e>(env: &'e JNIEnv<'e>, obj: JObject<'e>, name: &str) -> Result<String> {
+    let class = env.auto_local(env.get_object_class(obj)?.into());
+    let ty = env.auto_local(field_type(env, class.as_obj(), name)?);
+    class_descriptor(env, ty.as_obj())
+}
+
+/// Find the declared type of a field on `class` or one of its superclasses,
+/// as a local ref to its `Class`.
+fn field_type<'e>(env: &'e JNIEnv<'e>, class: JObject<'e>, name: &str) -> Result<JObject<'e>> {
+    let jname = env.auto_local(env.new_string(name)?.into());
+    let mut class = env.auto_local(env.new_local_ref::<JObject>(class)?);
+
+    loop {
+        let res = env.call_method(
+            class.as_obj(),
+            "getDeclaredField",
+            "(Ljava/lang/String;)Ljava/lang/reflect/Field;",
+            &[JValue::from(jname.as_obj())],
+        );
+        match res {
+            Ok(field) => {
+                let field = env.auto_local(field.l()?);
+                return env.call_method(field.as_obj(), "getType", "()Ljava/lang/Class;", &[])?
+                    .l();
+            }
+            Err(ref e) if is_no_such_field(env, e)? => {}
+            Err(e) => return Err(e),
+        }
+
+        let parent = env.call_method(class.as_obj(), "getSuperclass", "()Ljava/lang/Class;", &[])?
+            .l()?;
+        if parent.is_null() {
+            return Err(format!("no field named {} on the java object", name).into());
+        }
+        class = env.auto_local(parent);
+    }
+}
+
+// Clears the pending exception if it's a `NoSuchFieldException`, and leaves
+// any other one pending. It has to be cleared to be checked, since no other
+// call may be made while it's pending.
+fn is_no_such_field<'e>(env: &'e JNIEnv<'e>, e: &Error) -> Result<bool> {
+    static NO_SUCH_FIELD: ClassCache = ClassCache::new();
+    match *e.kind() {
+        ErrorKind::JavaException => {}
+        _ => return Ok(false),
+    }
+    let ex = env.exception_occurred()?;
+    if ex.is_null() {
+        return Ok(false);
+    }
+    let ex = env.auto_local(ex.into());
+    env.exception_clear()?;
+    let missing = env.is_instance_of(ex.as_obj(), NO_SUCH_FIELD.class("java/lang/NoSuchFieldException"))?;
+    if !missing {
+        env.throw(JThrowable::from(ex.as_obj()))?;
+    }
+    Ok(missing)
+}
+
+/// Turn a `Class` into a type signature, e.g. `int` into `I` and
+/// `java.lang.String` into `Ljava/lang/String;`.
+fn class_descriptor<'e>(env: &'e JNIEnv<'e>, class: JObject<'e>) -> Result<String> {
+    env.class_type_signature(class)
+}
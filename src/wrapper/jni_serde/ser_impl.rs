@@ -0,0 +1,545 @@
+use std::convert::TryFrom;
+
+use serde::ser::{
+    self,
+    Impossible,
+    Serialize,
+};
+
+use JNIEnv;
+
+use errors::*;
+
+use objects::JClass;
+use objects::JObject;
+use objects::JValue;
+
+use signature::Primitive;
+
+use super::class_descriptor;
+use super::field_type;
+
+/// Build a java object of class `class` from a rust value, setting each java
+/// field from the struct field of the same name. See the module docs for how
+/// types map.
+///
+/// The object is allocated without running any constructor, so fields that
+/// the rust struct doesn't have keep their default value (zero or null).
+/// Sequences are stored as a `java.util.ArrayList` and maps as a
+/// `java.util.HashMap`, so such fields have to be declared with a type those
+/// classes can be assigned to, such as `List` or `Map`. Numbers inside them
+/// are boxed according to their rust type, e.g. `i32` becomes `Integer`.
+///
+/// # Example
+/// ```rust,ignore
+/// #[derive(Serialize)]
+/// struct Point {
+///     x: i32,
+///     y: i32,
+/// }
+///
+/// let class = env.find_class("java/awt/Point")?;
+/// let java_point = jni_serde::to_object(&env, &Point { x: 1, y: 2 }, class)?;
+/// ```
+pub fn to_object<'e, T>(env: &'e JNIEnv<'e>, value: &T, class: JClass<'e>) -> Result<JObject<'e>>
+where
+    T: ?Sized + Serialize,
+{
+    non_null!(class, "to_object class argument");
+    let sig = class_descriptor(env, class.into())?;
+    value
+        .serialize(ValueSerializer {
+            env: env,
+            sig: sig,
+            class: Some(class.into()),
+        })?
+        .l()
+}
+
+/// Turns a rust value into a value for a java field of type `sig`. `class`
+/// is that type's `Class`, unless the value goes into a collection, where
+/// only `Object` is known.
+struct ValueSerializer<'e> {
+    env: &'e JNIEnv<'e>,
+    sig: String,
+    class: Option<JObject<'e>>,
+}
+
+/// The signatures that any boxed value or string can be stored as.
+fn is_any_object(sig: &str) -> bool {
+    match sig {
+        "Ljava/lang/Object;" | "Ljava/io/Serializable;" | "Ljava/lang/Comparable;" => true,
+        _ => false,
+    }
+}
+
+fn mismatch(sig: &str, what: &str) -> Error {
+    format!("can't store {} in a java field of type {}", what, sig).into()
+}
+
+fn narrow<T: TryFrom<i64>>(v: i64, java_type: &str) -> Result<T> {
+    T::try_from(v).map_err(|_| format!("{} doesn't fit in a java {}", v, java_type).into())
+}
+
+/// Box a primitive value into the matching `java.lang` wrapper class.
+fn box_primitive<'e>(env: &'e JNIEnv<'e>, value: JValue<'e>) -> Result<JValue<'e>> {
+    let (class, sig) = match value {
+        JValue::Bool(_) => ("java/lang/Boolean", "Z"),
+        JValue::Byte(_) => ("java/lang/Byte", "B"),
+        JValue::Char(_) => ("java/lang/Character", "C"),
+        JValue::Short(_) => ("java/lang/Short", "S"),
+        JValue::Int(_) => ("java/lang/Integer", "I"),
+        JValue::Long(_) => ("java/lang/Long", "J"),
+        JValue::Float(_) => ("java/lang/Float", "F"),
+        JValue::Double(_) => ("java/lang/Double", "D"),
+        JValue::Object(_) | JValue::Void => return Ok(value),
+    };
+    env.call_static_method(class, "valueOf", format!("({})L{};", sig, class), &[value])
+}
+
+impl<'e> ValueSerializer<'e> {
+    /// Store a primitive, boxing it if the field is an object.
+    fn primitive(self, value: JValue<'e>) -> Result<JValue<'e>> {
+        if self.sig.len() == 1 {
+            Ok(value)
+        } else {
+            box_primitive(self.env, value)
+        }
+    }
+
+    fn integer(self, v: i64, natural: Primitive) -> Result<JValue<'e>> {
+        let target = match self.sig.as_str() {
+            "B" | "Ljava/lang/Byte;" => Primitive::Byte,
+            "S" | "Ljava/lang/Short;" => Primitive::Short,
+            "I" | "Ljava/lang/Integer;" => Primitive::Int,
+            "J" | "Ljava/lang/Long;" => Primitive::Long,
+            "Ljava/lang/Number;" => natural,
+            sig if is_any_object(sig) => natural,
+            sig => return Err(mismatch(sig, "an integer")),
+        };
+        let value = match target {
+            Primitive::Byte => JValue::Byte(narrow(v, "byte")?),
+            Primitive::Short => JValue::Short(narrow(v, "short")?),
+            Primitive::Int => JValue::Int(narrow(v, "int")?),
+            _ => JValue::Long(v),
+        };
+        self.primitive(value)
+    }
+
+    fn float(self, v: f64, natural: Primitive) -> Result<JValue<'e>> {
+        let target = match self.sig.as_str() {
+            "F" | "Ljava/lang/Float;" => Primitive::Float,
+            "D" | "Ljava/lang/Double;" => Primitive::Double,
+            "Ljava/lang/Number;" => natural,
+            sig if is_any_object(sig) => natural,
+            sig => return Err(mismatch(sig, "a floating point number")),
+        };
+        let value = match target {
+            Primitive::Float => JValue::Float(v as f32),
+            _ => JValue::Double(v),
+        };
+        self.primitive(value)
+    }
+
+    fn string(self, v: &str) -> Result<JValue<'e>> {
+        match self.sig.as_str() {
+            "Ljava/lang/String;" | "Ljava/lang/CharSequence;" => {}
+            sig if is_any_object(sig) => {}
+            sig => return Err(mismatch(sig, "a string")),
+        }
+        Ok(JObject::from(self.env.new_string(v)?).into())
+    }
+
+    fn collection(&self, class: &str, interfaces: &[&str]) -> Result<JObject<'e>> {
+        let sig = self.sig.as_str();
+        let own_sig = format!("L{};", class);
+        if sig != own_sig && !is_any_object(sig) && !interfaces.contains(&sig) {
+            return Err(mismatch(sig, &format!("a {}", class)));
+        }
+        self.env.new_object(class, "()V", &[])
+    }
+}
+
+impl<'e> ser::Serializer for ValueSerializer<'e> {
+    type Ok = JValue<'e>;
+    type Error = Error;
+
+    type SerializeSeq = SeqSerializer<'e>;
+    type SerializeTuple = SeqSerializer<'e>;
+    type SerializeTupleStruct = Impossible<JValue<'e>, Error>;
+    type SerializeTupleVariant = Impossible<JValue<'e>, Error>;
+    type SerializeMap = MapSerializer<'e>;
+    type SerializeStruct = StructSerializer<'e>;
+    type SerializeStructVariant = Impossible<JValue<'e>, Error>;
+
+    fn serialize_bool(self, v: bool) -> Result<JValue<'e>> {
+        match self.sig.as_str() {
+            "Z" | "Ljava/lang/Boolean;" => {}
+            sig if is_any_object(sig) => {}
+            sig => return Err(mismatch(sig, "a bool")),
+        }
+        self.primitive(JValue::from(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<JValue<'e>> {
+        self.integer(v as i64, Primitive::Byte)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<JValue<'e>> {
+        self.integer(v as i64, Primitive::Short)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<JValue<'e>> {
+        self.integer(v as i64, Primitive::Int)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<JValue<'e>> {
+        self.integer(v, Primitive::Long)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<JValue<'e>> {
+        self.integer(v as i64, Primitive::Short)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<JValue<'e>> {
+        self.integer(v as i64, Primitive::Int)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<JValue<'e>> {
+        self.integer(v as i64, Primitive::Long)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<JValue<'e>> {
+        let v = i64::try_from(v).map_err(|_| format!("{} doesn't fit in a java long", v))?;
+        self.integer(v, Primitive::Long)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<JValue<'e>> {
+        self.float(v as f64, Primitive::Float)
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<JValue<'e>> {
+        self.float(v, Primitive::Double)
+    }
+
+    fn serialize_char(self, v: char) -> Result<JValue<'e>> {
+        match self.sig.as_str() {
+            "C" | "Ljava/lang/Character;" => {}
+            "Ljava/lang/String;" | "Ljava/lang/CharSequence;" => {
+                return self.string(v.encode_utf8(&mut [0; 4]));
+            }
+            sig if is_any_object(sig) => {}
+            sig => return Err(mismatch(sig, "a char")),
+        }
+        let c = v as u32;
+        if c > 0xffff {
+            return Err(format!("{:?} doesn't fit in a java char", v).into());
+        }
+        self.primitive(JValue::Char(c as u16))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<JValue<'e>> {
+        self.string(v)
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<JValue<'e>> {
+        if self.sig != "[B" {
+            return Err(mismatch(&self.sig, "bytes"));
+        }
+        Ok(JObject::from(self.env.byte_array_from_slice(v)?).into())
+    }
+
+    fn serialize_none(self) -> Result<JValue<'e>> {
+        if self.sig.len() == 1 {
+            return Err(mismatch(&self.sig, "None"));
+        }
+        Ok(JObject::null().into())
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<JValue<'e>>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<JValue<'e>> {
+        self.serialize_none()
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<JValue<'e>> {
+        self.serialize_none()
+    }
+
+    /// Unit variants become a constant of the field's enum type, or their
+    /// name if the field is a string.
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<JValue<'e>> {
+        let class = match self.class {
+            Some(class) if !is_any_object(&self.sig) && self.sig != "Ljava/lang/String;" => class,
+            _ => return self.string(variant),
+        };
+        let variant = self.env.auto_local(self.env.new_string(variant)?.into());
+        self.env.call_static_method(
+            "java/lang/Enum",
+            "valueOf",
+            "(Ljava/lang/Class;Ljava/lang/String;)Ljava/lang/Enum;",
+            &[class.into(), variant.as_obj().into()],
+        )
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<JValue<'e>>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _value: &T,
+    ) -> Result<JValue<'e>>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(format!("enum variant {}::{} has data, which java enums can't hold", name, variant).into())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<SeqSerializer<'e>> {
+        let list = self.collection(
+            "java/util/ArrayList",
+            &[
+                "Ljava/util/List;",
+                "Ljava/util/Collection;",
+                "Ljava/lang/Iterable;",
+                "Ljava/util/AbstractList;",
+            ],
+        )?;
+        Ok(SeqSerializer {
+            env: self.env,
+            list: list,
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SeqSerializer<'e>> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        Err(format!("tuple struct {} can't be stored in a java field", name).into())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Err(format!("enum variant {}::{} has data, which java enums can't hold", name, variant).into())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<MapSerializer<'e>> {
+        let map = self.collection(
+            "java/util/HashMap",
+            &["Ljava/util/Map;", "Ljava/util/AbstractMap;"],
+        )?;
+        Ok(MapSerializer {
+            env: self.env,
+            map: map,
+            key: None,
+        })
+    }
+
+    fn serialize_struct(self, name: &'static str, _len: usize) -> Result<StructSerializer<'e>> {
+        let class = match self.class {
+            Some(class) if self.sig.starts_with('L') && !is_any_object(&self.sig) => class,
+            _ => {
+                return Err(format!(
+                    "struct {} needs a java field of a concrete class, not {}",
+                    name, self.sig
+                ).into())
+            }
+        };
+        let obj = self.env.alloc_object(JClass::from(class.into_inner()))?;
+        Ok(StructSerializer {
+            env: self.env,
+            obj: obj,
+            class: class,
+        })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Err(format!("enum variant {}::{} has data, which java enums can't hold", name, variant).into())
+    }
+}
+
+/// Serializer for a value going into a collection, where the element type is
+/// unknown.
+fn element<'e>(env: &'e JNIEnv<'e>) -> ValueSerializer<'e> {
+    ValueSerializer {
+        env: env,
+        sig: "Ljava/lang/Object;".into(),
+        class: None,
+    }
+}
+
+/// Sets fields on a newly allocated object.
+struct StructSerializer<'e> {
+    env: &'e JNIEnv<'e>,
+    obj: JObject<'e>,
+    class: JObject<'e>,
+}
+
+impl<'e> ser::SerializeStruct for StructSerializer<'e> {
+    type Ok = JValue<'e>;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        let ty = self.env.auto_local(field_type(self.env, self.class, key)?);
+        let sig = class_descriptor(self.env, ty.as_obj())?;
+        let value = value.serialize(ValueSerializer {
+            env: self.env,
+            sig: sig.clone(),
+            class: Some(ty.as_obj()),
+        })?;
+
+        if let JValue::Object(obj) = value {
+            let obj = self.env.auto_local(obj);
+            // JNI doesn't check what gets stored in a field
+            if !obj.as_obj().is_null()
+                && !self.env.is_instance_of(obj.as_obj(), JClass::from(ty.as_obj()))?
+            {
+                return Err(format!("value for field {} isn't a {}", key, sig).into());
+            }
+            self.env.set_field(self.obj, key, sig.as_str(), obj.as_obj().into())
+        } else {
+            self.env.set_field(self.obj, key, sig.as_str(), value)
+        }
+    }
+
+    fn end(self) -> Result<JValue<'e>> {
+        Ok(self.obj.into())
+    }
+}
+
+/// Adds elements to a new `ArrayList`.
+struct SeqSerializer<'e> {
+    env: &'e JNIEnv<'e>,
+    list: JObject<'e>,
+}
+
+impl<'e> SeqSerializer<'e> {
+    fn add<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        let value = value.serialize(element(self.env))?.l()?;
+        let value = self.env.auto_local(value);
+        self.env.call_method(
+            self.list,
+            "add",
+            "(Ljava/lang/Object;)Z",
+            &[value.as_obj().into()],
+        )?;
+        Ok(())
+    }
+}
+
+impl<'e> ser::SerializeSeq for SeqSerializer<'e> {
+    type Ok = JValue<'e>;
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.add(value)
+    }
+
+    fn end(self) -> Result<JValue<'e>> {
+        Ok(self.list.into())
+    }
+}
+
+impl<'e> ser::SerializeTuple for SeqSerializer<'e> {
+    type Ok = JValue<'e>;
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.add(value)
+    }
+
+    fn end(self) -> Result<JValue<'e>> {
+        Ok(self.list.into())
+    }
+}
+
+/// Puts entries into a new `HashMap`.
+struct MapSerializer<'e> {
+    env: &'e JNIEnv<'e>,
+    map: JObject<'e>,
+    key: Option<JObject<'e>>,
+}
+
+impl<'e> ser::SerializeMap for MapSerializer<'e> {
+    type Ok = JValue<'e>;
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.key = Some(key.serialize(element(self.env))?.l()?);
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        let key = match self.key.take() {
+            Some(key) => self.env.auto_local(key),
+            None => return Err("map value serialized before its key".into()),
+        };
+        let value = value.serialize(element(self.env))?.l()?;
+        let value = self.env.auto_local(value);
+        let prev = self.env.call_method(
+            self.map,
+            "put",
+            "(Ljava/lang/Object;Ljava/lang/Object;)Ljava/lang/Object;",
+            &[key.as_obj().into(), value.as_obj().into()],
+        )?.l()?;
+        if !prev.is_null() {
+            self.env.delete_local_ref(prev)?;
+        }
+        Ok(())
+    }
+
+    fn end(self) -> Result<JValue<'e>> {
+        Ok(self.map.into())
+    }
+}
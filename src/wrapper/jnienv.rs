@@ -1,5 +1,11 @@
 use std::str;
 
+use std::borrow::Cow;
+
+use std::cmp::Ordering;
+
+use std::collections::HashMap;
+
 use std::marker::PhantomData;
 
 use std::iter::IntoIterator;
@@ -8,6 +14,9 @@ use std::slice;
 
 use std::sync::Mutex;
 use std::sync::MutexGuard;
+use std::sync::RwLock;
+
+use std::time::Duration;
 
 use errors::*;
 
@@ -28,6 +37,7 @@ use sys::{
     jlong,
     jshort,
     jsize,
+    jthrowable,
     jvalue,
     jbooleanArray,
     jbyteArray,
@@ -40,28 +50,59 @@ use sys::{
     jshortArray,
 };
 
+use wrapper::call_depth::CallDepthGuard;
+use wrapper::call_stats;
+use wrapper::call_trace;
+use wrapper::watchdog;
+
+use strings::InternedId;
+use strings::JNIStr;
 use strings::JNIString;
 use strings::JavaStr;
+use strings::JavaStrChars;
+use strings::JavaStrCritical;
+use strings::StringAccess;
+use strings::StringInterner;
 
+use objects::AutoArray;
 use objects::AutoLocal;
+use objects::AutoPrimitiveArrayCritical;
+use objects::ClassByNameCache;
 use objects::GlobalRef;
 use objects::JByteBuffer;
 use objects::JClass;
+use objects::JCloseable;
 use objects::JFieldID;
+use objects::JIterator;
 use objects::JList;
 use objects::JMap;
 use objects::JMethodID;
 use objects::JObject;
+use objects::JObjectArray;
+use objects::JResultAccessors;
 use objects::JStaticFieldID;
 use objects::JStaticMethodID;
 use objects::JString;
 use objects::JThrowable;
 use objects::JValue;
+use objects::NativeMethod;
+use objects::RefType;
+use objects::ReleaseMode;
+use objects::TypeArray;
+use objects::WeakRef;
 
+use descriptors::ClassCache;
 use descriptors::Desc;
 
+use exceptions::CLASS_CAST_EXCEPTION;
+use exceptions::ILLEGAL_ARGUMENT_EXCEPTION;
+use exceptions::NO_CLASS_DEF_FOUND_ERROR;
+use exceptions::NO_SUCH_FIELD_ERROR;
+use exceptions::NO_SUCH_METHOD_ERROR;
+
 use signature::JavaType;
 use signature::Primitive;
+use signature::ReturnKind;
 use signature::TypeSignature;
 
 use JNIVersion;
@@ -76,13 +117,33 @@ use JavaVM;
 /// potential to cause an exception to get thrown. If this is the case, an `Err`
 /// result will be returned with the error kind `JavaException`. Note that this
 /// will _not_ clear the exception - it's up to the caller to decide whether to
-/// do so or to let it continue being thrown.
+/// do so or to let it continue being thrown. Its class, message and stack
+/// trace can be captured in the error though, see `Error::exception_details`.
+///
+/// Some methods, like the `_unsafe` ones, don't check for an exception after
+/// every JNI function they call, and nothing stops further calls from being
+/// made with one pending, which the JNI spec makes undefined behavior. With
+/// the `exception-check` feature, every JNI function call is checked: one
+/// that leaves an exception pending returns a `JavaException` error right
+/// away, and none but the few the spec allows is made while one is pending,
+/// returning the error instead.
+///
+/// If the thrown object is a `java.lang.Error` (`StackOverflowError`,
+/// `OutOfMemoryError`...) rather than an `Exception`, the error kind is
+/// `JavaError` instead. The VM may be in no state to keep going after one of
+/// these, so native code should return to java immediately, without making
+/// any more JNI calls.
 ///
 /// Because null pointers are a thing in Java, this also converts them to an
 /// `Err` result with the kind `NullPtr`. This may occur when either a null
 /// argument is passed to a method or when a null would be returned. Where
 /// applicable, the null error is changed to a more applicable error type, such
 /// as `MethodNotFound`.
+///
+/// Failed method and field lookups are always reported as `MethodNotFound`
+/// and `FieldNotFound`, with the class, name and signature that were looked
+/// up. The `NoSuchMethodError` or `NoSuchFieldError` thrown by the lookup is
+/// cleared.
 #[repr(C)]
 pub struct JNIEnv<'a> {
     internal: *mut sys::JNIEnv,
@@ -106,8 +167,21 @@ impl<'a> JNIEnv<'a> {
         Ok(unsafe { jni_unchecked!(self.internal, GetVersion) }.into())
     }
 
-    /// Define a new java class. See the JNI docs for more details - I've never
-    /// had occasion to use this and haven't researched it fully.
+    /// Define a new java class from the bytes of a `.class` file, e.g. one
+    /// embedded in the library with `include_bytes!`, so that small helper
+    /// classes don't need to ship in a separate jar.
+    ///
+    /// The name, in the `com/example/Callback` form, must match the one in
+    /// the class file. The class is defined in `loader`, and its superclass
+    /// and the other classes it refers to are resolved through that loader.
+    ///
+    /// # Example
+    /// ```rust,ignore
+    /// let loader = env.call_static_method("java/lang/ClassLoader", "getSystemClassLoader", "()Ljava/lang/ClassLoader;", &[])?.l()?;
+    /// let class = env.define_class("com/example/Callback", loader, include_bytes!("Callback.class"))?;
+    /// ```
+    ///
+    /// Defining a class twice in the same loader throws a `LinkageError`.
     pub fn define_class<S>(&self, name: S, loader: JObject, buf: &[u8]) -> Result<JClass>
     where
         S: Into<JNIString>,
@@ -125,19 +199,118 @@ impl<'a> JNIEnv<'a> {
         Ok(class)
     }
 
+    /// Define a new java class like `define_class`, taking its name from the
+    /// class file, for bytecode generated at runtime whose name isn't known
+    /// beforehand. Use `Class.getName` on the result to find what it is.
+    pub fn define_unnamed_class(&self, loader: JObject, buf: &[u8]) -> Result<JClass> {
+        non_null!(loader, "define_unnamed_class loader argument");
+        let class = jni_call!(
+            self.internal,
+            DefineClass,
+            ptr::null(),
+            loader.into_inner(),
+            buf.as_ptr() as *const jbyte,
+            buf.len() as jsize
+        );
+        Ok(class)
+    }
+
     /// Look up a class by name.
     ///
     /// # Example
     /// ```rust,ignore
     /// let class: JClass<'a> = env.find_class("java/lang/String");
     /// ```
+    ///
+    /// If the class isn't found and an application class loader has been set
+    /// with `set_app_class_loader`, the class is looked up through that
+    /// loader before giving up, with `Class.forName`. If that fails too, the
+    /// `NoClassDefFoundError` of `FindClass` is left pending.
     pub fn find_class<S>(&self, name: S) -> Result<JClass<'a>>
     where
         S: Into<JNIString>,
     {
         let name = name.into();
-        let class = jni_call!(self.internal, FindClass, name.as_ptr());
-        Ok(class)
+        let res: Result<JClass<'a>> = catch!({ Ok(jni_call!(self.internal, FindClass, name.as_ptr())) });
+        match res {
+            Err(e) => match *e.kind() {
+                ErrorKind::JavaException | ErrorKind::JavaError => match app_class_loader() {
+                    Some(loader) => self.find_class_fallback(&loader, &name, e),
+                    None => Err(e),
+                },
+                _ => Err(e),
+            },
+            class => class,
+        }
+    }
+
+    /// Set the class loader `find_class` falls back to when `FindClass`
+    /// doesn't find a class.
+    ///
+    /// `FindClass` uses the class loader of the java method that called into
+    /// native code, so it only sees the system classes on threads attached
+    /// from native code, or from `JNI_OnLoad` on some VMs. On Android, that
+    /// means none of the application's classes. Setting the loader of an
+    /// application class, e.g. from `JNI_OnLoad` or the first native method
+    /// call, makes those classes visible to every thread:
+    ///
+    /// ```rust,ignore
+    /// let class = env.find_class("com/example/MainActivity")?;
+    /// let loader = env.call_method(class.into(), "getClassLoader", "()Ljava/lang/ClassLoader;", &[])?.l()?;
+    /// env.set_app_class_loader(loader)?;
+    /// ```
+    ///
+    /// The loader is kept with a global ref, and used by all threads. Pass
+    /// `JObject::null()` to stop using it.
+    pub fn set_app_class_loader(&self, loader: JObject) -> Result<()> {
+        let loader = if loader.is_null() {
+            None
+        } else {
+            Some(self.new_global_ref(loader)?)
+        };
+        *APP_CLASS_LOADER.write().unwrap() = loader;
+        Ok(())
+    }
+
+    /// Look a class up through the app class loader, if `FindClass` failed
+    /// with a `NoClassDefFoundError`, which is pending. Leaves the original
+    /// exception pending and returns `err` if that fails too.
+    fn find_class_fallback(&self, loader: &GlobalRef, name: &JNIStr, err: Error) -> Result<JClass<'a>> {
+        let original: JObject = self.exception_occurred()?.into();
+        self.exception_clear()?;
+
+        let class = match self.is_instance_of(original, JDK_CLASSES.class(NO_CLASS_DEF_FOUND_ERROR))? {
+            true => self.find_class_in(loader, name).ok(),
+            false => None,
+        };
+        if class.is_none() {
+            self.exception_clear()?;
+            self.throw(JThrowable::from(original))?;
+        }
+        self.delete_local_ref(original)?;
+        class.ok_or(err)
+    }
+
+    fn find_class_in(&self, loader: &GlobalRef, name: &JNIStr) -> Result<JClass<'a>> {
+        let for_name = cached_static_method_id!(
+            self,
+            "java/lang/Class",
+            "forName",
+            "(Ljava/lang/String;ZLjava/lang/ClassLoader;)Ljava/lang/Class;"
+        );
+        let class_class = JDK_CLASSES.find_class(self, "java/lang/Class")?;
+        let name: Cow<str> = name.into();
+        let name = self.new_string(name.replace('/', "."))?;
+        let class = unsafe {
+            self.call_static_method_unsafe(
+                class_class,
+                for_name,
+                JavaType::Object("java/lang/Class".into()),
+                &[JObject::from(name).into(), true.into(), loader.as_obj().into()],
+            )
+        };
+        self.delete_local_ref(name.into())?;
+        Ok(class?.l()?.into_inner().into())
     }
 
     /// Get the superclass for a particular class. As with `find_class`, takes
@@ -147,17 +320,24 @@ impl<'a> JNIEnv<'a> {
         T: Desc<'a, JClass<'a>>,
     {
         let class = class.lookup(self)?;
+        non_null!(class, "get_superclass class argument");
         Ok(jni_call!(self.internal, GetSuperclass, class.into_inner()))
     }
 
     /// Tests whether class1 is assignable from class2.
-    pub fn is_assignable_from<T, U>(&self, class1: T, class2: U) -> Result<bool>
+    ///
+    /// _NB: like the JNI function, this checks whether an instance of class1
+    /// can be cast to class2, which is the reverse of java's
+    /// `class1.isAssignableFrom(class2)`._
+    pub fn is_assignable_from<'c, 'd, T, U>(&self, class1: T, class2: U) -> Result<bool>
     where
-        T: Desc<'a, JClass<'a>>,
-        U: Desc<'a, JClass<'a>>,
+        T: Desc<'a, JClass<'c>>,
+        U: Desc<'a, JClass<'d>>,
     {
         let class1 = class1.lookup(self)?;
+        non_null!(class1, "is_assignable_from class1 argument");
         let class2 = class2.lookup(self)?;
+        non_null!(class2, "is_assignable_from class2 argument");
         Ok(unsafe {
             jni_unchecked!(
                 self.internal,
@@ -175,11 +355,12 @@ impl<'a> JNIEnv<'a> {
     ///
     /// See [JNI documentation](https://docs.oracle.com/javase/8/docs/technotes/guides/jni/spec/functions.html#IsInstanceOf)
     /// for details.
-    pub fn is_instance_of<T>(&self, object: JObject<'a>, class: T) -> Result<bool>
+    pub fn is_instance_of<'c, T>(&self, object: JObject, class: T) -> Result<bool>
     where
-        T: Desc<'a, JClass<'a>>,
+        T: Desc<'a, JClass<'c>>,
     {
         let class = class.lookup(self)?;
+        non_null!(class, "is_instance_of class argument");
         Ok(
             unsafe {
                 jni_unchecked!(
@@ -192,6 +373,20 @@ impl<'a> JNIEnv<'a> {
         )
     }
 
+    /// Returns true if the two references refer to the same java object, like
+    /// `==` on objects in java. Any kind of reference may be compared: local
+    /// refs, global refs through `as_obj()`, or nulls.
+    pub fn is_same_object(&self, obj1: JObject, obj2: JObject) -> Result<bool> {
+        Ok(unsafe {
+            jni_unchecked!(
+                self.internal,
+                IsSameObject,
+                obj1.into_inner(),
+                obj2.into_inner()
+            )
+        } == sys::JNI_TRUE)
+    }
+
     /// Raise an exception from an existing object. This will continue being
     /// thrown in java unless `exception_clear` is called.
     ///
@@ -210,6 +405,7 @@ impl<'a> JNIEnv<'a> {
         E: Desc<'a, JThrowable<'a>>,
     {
         let throwable = obj.lookup(self)?;
+        let throwable = non_null!(throwable, "throw obj argument");
         let res: i32 = unsafe { jni_unchecked!(self.internal, Throw, throwable.into_inner()) };
         if res < 0 {
             Err(format!("throw failed with code {}", res).into())
@@ -219,11 +415,13 @@ impl<'a> JNIEnv<'a> {
     }
 
     /// Create and throw a new exception from a class descriptor and an error
-    /// message.
+    /// message. The `exceptions` module has the class names of the common JDK
+    /// exceptions.
     ///
     /// # Example
     /// ```rust,ignore
     /// let _ = env.throw_new("java/lang/Exception", "something bad happened");
+    /// let _ = env.throw_new(exceptions::ILLEGAL_ARGUMENT_EXCEPTION, "bad argument");
     /// ```
     pub fn throw_new<S, T>(&self, class: T, msg: S) -> Result<()>
     where
@@ -275,32 +473,111 @@ impl<'a> JNIEnv<'a> {
         Ok(check)
     }
 
+    /// Get the class and message of a throwable, e.g. to log it or turn it
+    /// into a rust error. The class is in internal form
+    /// (`java/lang/IllegalStateException`), and a null message becomes an
+    /// empty string, so the result can be thrown again with `throw`.
+    pub fn describe_throwable(&'a self, throwable: JThrowable) -> Result<Exception> {
+        let throwable: JObject = non_null!(throwable, "describe_throwable throwable argument").into();
+        let class = self.auto_local(self.get_object_class(throwable)?.into());
+        let class_name = self.call_string_method(class.as_obj(), "getName", "()Ljava/lang/String;", &[])?
+            .unwrap_or_default();
+        let msg = self.call_string_method(throwable, "getMessage", "()Ljava/lang/String;", &[])?
+            .unwrap_or_default();
+
+        Ok(Exception {
+            class: class_name.replace('.', "/"),
+            msg: msg,
+        })
+    }
+
+    /// Get the class and message of the pending exception, if there is one,
+    /// as for `describe_throwable`. The exception is still pending afterwards.
+    pub fn pending_exception(&'a self) -> Result<Option<Exception>> {
+        let throwable = self.exception_occurred()?;
+        if throwable.is_null() {
+            return Ok(None);
+        }
+        let throwable = self.auto_local(throwable.into());
+
+        // No java methods may be called while the exception is pending.
+        self.exception_clear()?;
+        let desc = self.describe_throwable(JThrowable::from(throwable.as_obj()));
+        if desc.is_err() {
+            // whatever `describe_throwable` threw gives way to the original
+            self.exception_clear()?;
+        }
+        self.throw(JThrowable::from(throwable.as_obj()))?;
+        desc.map(Some)
+    }
+
+    /// Return a `JavaException` (or `JavaError`) error if an exception is
+    /// pending, *without* clearing it.
+    ///
+    /// This is meant for native methods that call back into java: when the
+    /// callback throws, return from the native method right away (with any
+    /// placeholder value) and the JVM will rethrow the still-pending exception
+    /// to the original java caller, untouched.
+    ///
+    /// # Example
+    /// ```rust,ignore
+    /// let _ = env.call_method(callback, "onEvent", "()V", &[]);
+    /// if env.propagate_pending_exception().is_err() {
+    ///     // the exception thrown by `onEvent` reaches our java caller
+    ///     return 0;
+    /// }
+    /// ```
+    pub fn propagate_pending_exception(&self) -> Result<()> {
+        check_exception!(self.internal);
+        Ok(())
+    }
+
     /// Create a new instance of a direct java.nio.ByteBuffer.
-    pub fn new_direct_byte_buffer(&self, data: &mut [u8]) -> Result<JByteBuffer> {
-        let obj = unsafe {
-            jni_unchecked!(
-                self.internal,
-                NewDirectByteBuffer,
-                data.as_mut_ptr() as *mut c_void,
-                data.len() as jlong
-            )
-        };
-        Ok(JByteBuffer::from(obj))
+    ///
+    /// The buffer reads and writes `data` in place, so it borrows `data` for
+    /// as long as the returned `JByteBuffer` is usable from rust. Java code
+    /// that keeps the buffer around past that must not touch it.
+    pub fn new_direct_byte_buffer<'b>(&self, data: &'b mut [u8]) -> Result<JByteBuffer<'b>>
+    where
+        'a: 'b,
+    {
+        let obj: JObject = jni_call!(
+            self.internal,
+            NewDirectByteBuffer,
+            data.as_mut_ptr() as *mut c_void,
+            data.len() as jlong
+        );
+        Ok(JByteBuffer::from(obj.into_inner()))
     }
 
     /// Returns the starting address of the memory of the direct
     /// java.nio.ByteBuffer.
-    pub fn get_direct_buffer_address(&self, buf: JByteBuffer) -> Result<&mut [u8]> {
-        let ptr: *mut c_void =
-            unsafe { jni_unchecked!(self.internal, GetDirectBufferAddress, buf.into_inner()) };
+    ///
+    /// The slice lives as long as the buffer object. Fails with a `NullPtr`
+    /// error if `buf` isn't a direct buffer.
+    ///
+    /// # Safety
+    ///
+    /// The slice aliases the buffer's memory. No other slice of the same
+    /// buffer, from another call, and no java code may access that memory
+    /// while the returned slice is in use.
+    pub unsafe fn get_direct_buffer_address<'b>(&self, buf: JByteBuffer<'b>) -> Result<&'b mut [u8]> {
+        non_null!(buf, "get_direct_buffer_address buf argument");
+        let ptr: *mut c_void = jni_unchecked!(self.internal, GetDirectBufferAddress, buf.into_inner());
+        let ptr = non_null!(ptr, "get_direct_buffer_address result");
         let capacity = self.get_direct_buffer_capacity(buf)?;
-        unsafe { Ok(slice::from_raw_parts_mut(ptr as *mut u8, capacity as usize)) }
+        Ok(slice::from_raw_parts_mut(ptr as *mut u8, capacity as usize))
     }
 
-    /// Returns the capacity of the direct java.nio.ByteBuffer.
+    /// Returns the capacity of the direct java.nio.ByteBuffer. Fails if `buf`
+    /// isn't a direct buffer.
     pub fn get_direct_buffer_capacity(&self, buf: JByteBuffer) -> Result<jlong> {
+        non_null!(buf, "get_direct_buffer_capacity buf argument");
         let capacity =
             unsafe { jni_unchecked!(self.internal, GetDirectBufferCapacity, buf.into_inner()) };
+        if capacity < 0 {
+            return Err("get_direct_buffer_capacity: not a direct buffer".into());
+        }
         Ok(capacity)
     }
 
@@ -314,6 +591,58 @@ impl<'a> JNIEnv<'a> {
         Ok(global)
     }
 
+    /// Bind rust functions to the `native` methods of a class, replacing any
+    /// previous binding. This is typically done from `JNI_OnLoad`, so that the
+    /// functions don't need mangled `Java_...` names.
+    pub fn register_native_methods<'c, T>(&self, class: T, methods: &[NativeMethod]) -> Result<()>
+    where
+        T: Desc<'a, JClass<'c>>,
+    {
+        let class = class.lookup(self)?;
+        non_null!(class, "register_native_methods class argument");
+        let jni_methods: Vec<sys::JNINativeMethod> = methods.iter().map(|m| m.to_jni()).collect();
+        let res = jni_non_null_call!(
+            self.internal,
+            RegisterNatives,
+            class.into_inner(),
+            jni_methods.as_ptr(),
+            jni_methods.len() as jint
+        );
+        jni_error_code_to_result(res)
+    }
+
+    /// Remove all the native method bindings of a class, including ones
+    /// found by name mangling. Calling those methods from java then fails with
+    /// an `UnsatisfiedLinkError` until they're bound again.
+    pub fn unregister_native_methods<'c, T>(&self, class: T) -> Result<()>
+    where
+        T: Desc<'a, JClass<'c>>,
+    {
+        let class = class.lookup(self)?;
+        non_null!(class, "unregister_native_methods class argument");
+        let res = jni_non_null_call!(self.internal, UnregisterNatives, class.into_inner());
+        jni_error_code_to_result(res)
+    }
+
+    /// Creates a weak global reference to an object. Unlike a global ref, it
+    /// doesn't stop the object from being GC'd; use `WeakRef::upgrade` to get
+    /// at the object while it's still alive.
+    pub fn new_weak_ref(&self, obj: JObject) -> Result<WeakRef> {
+        non_null!(obj, "new_weak_ref obj argument");
+        let new_ref: JObject = jni_call!(self.internal, NewWeakGlobalRef, obj.into_inner());
+        let weak = unsafe { WeakRef::from_raw(self.get_java_vm()?, new_ref.into_inner()) };
+        Ok(weak)
+    }
+
+    /// Get the kind of a reference: local, global or weak global. A ref that
+    /// was already deleted is usually reported as `Invalid`, but not
+    /// reliably, since the VM may have reused its slot.
+    pub fn get_object_ref_type(&self, obj: JObject) -> Result<RefType> {
+        non_null!(obj, "get_object_ref_type obj argument");
+        let ref_type = unsafe { jni_unchecked!(self.internal, GetObjectRefType, obj.into_inner()) };
+        Ok(ref_type.into())
+    }
+
     /// Create a new local ref to an object.
     ///
     /// Note that the object passed to this is *already* a local ref. This
@@ -337,6 +666,16 @@ impl<'a> JNIEnv<'a> {
         AutoLocal::new(self, obj)
     }
 
+    /// Wrap a `java.lang.AutoCloseable` so that it's closed when the wrapper
+    /// goes out of scope. See `JCloseable`.
+    pub fn auto_closeable(&'a self, obj: JObject<'a>) -> Result<JCloseable<'a>> {
+        non_null!(obj, "auto_closeable obj argument");
+        if !self.is_instance_of(obj, "java/lang/AutoCloseable")? {
+            return Err("auto_closeable obj argument is not an AutoCloseable".into());
+        }
+        Ok(JCloseable::new(self, obj))
+    }
+
     /// Deletes the local reference.
     ///
     /// Local references are valid for the duration of a native method call.
@@ -393,12 +732,18 @@ impl<'a> JNIEnv<'a> {
     /// Provides a convenient way to use `push_local_frame` by automatically
     /// calling
     /// `pop_local_frame` function.
-    pub fn with_local_frame<F>(&self, capacity: i32, f: F) -> Result<JObject>
+    ///
+    /// The closure is given the env to make its calls with. Every local ref it
+    /// creates is freed when it returns, except for the object it returns,
+    /// which is moved out to the enclosing frame. Return `JObject::null()` if
+    /// there's nothing to keep. If the closure fails, the frame is popped
+    /// without keeping anything.
+    pub fn with_local_frame<F>(&'a self, capacity: i32, f: F) -> Result<JObject<'a>>
     where
-        F: FnOnce() -> Result<JObject<'a>>,
+        F: FnOnce(&'a JNIEnv<'a>) -> Result<JObject<'a>>,
     {
         self.push_local_frame(capacity)?;
-        let res = f();
+        let res = f(self);
         match res {
             Ok(obj) => self.pop_local_frame(obj),
             Err(e) => {
@@ -415,6 +760,7 @@ impl<'a> JNIEnv<'a> {
         T: Desc<'a, JClass<'a>>,
     {
         let class = class.lookup(self)?;
+        non_null!(class, "alloc_object class argument");
         Ok(jni_call!(self.internal, AllocObject, class.into_inner()))
     }
 
@@ -433,22 +779,97 @@ impl<'a> JNIEnv<'a> {
         C: for<'d> Fn(&JClass<'d>, &JNIString, &JNIString) -> Result<R>,
     {
         let class = class.lookup(self)?;
+        non_null!(class, "method id lookup class argument");
         let ffi_name = name.into();
         let sig = sig.into();
 
         let res: Result<R> = catch!({ get_method(&class, &ffi_name, &sig) });
 
-        match res {
-            Ok(m) => Ok(m),
-            Err(e) => match e.kind() {
-                &ErrorKind::NullPtr(_) => {
-                    let name: String = ffi_name.into();
-                    let sig: String = sig.into();
-                    return Err(ErrorKind::MethodNotFound(name, sig).into());
+        res.map_err(|e| {
+            self.member_not_found(e, class.into(), NO_SUCH_METHOD_ERROR, |class| {
+                ErrorKind::MethodNotFound(class, ffi_name.into(), sig.into())
+            })
+        })
+    }
+
+    /// Turn the failure of a method or field lookup into a `MethodNotFound`
+    /// or `FieldNotFound` error, including the binary name of the class. A
+    /// `NoSuchMethodError` or `NoSuchFieldError` thrown by the lookup, which
+    /// is `missing`, is cleared; any other error is returned as is.
+    fn member_not_found<F>(&self, err: Error, class: JObject, missing: &str, not_found: F) -> Error
+    where
+        F: FnOnce(String) -> ErrorKind,
+    {
+        match *err.kind() {
+            ErrorKind::NullPtr(_) => {}
+            ErrorKind::JavaException | ErrorKind::JavaError => {
+                let thrown: JObject = match self.exception_occurred() {
+                    Ok(thrown) if !thrown.is_null() => thrown.into(),
+                    _ => return err,
+                };
+                let _ = self.exception_clear();
+                let is_missing = self.is_instance_of(thrown, missing).unwrap_or(false);
+                if !is_missing {
+                    let _ = self.exception_clear();
+                    let _ = self.throw(JThrowable::from(thrown));
                 }
-                _ => return Err(e),
-            },
+                let _ = self.delete_local_ref(thrown);
+                if !is_missing {
+                    return err;
+                }
+            }
+            _ => return err,
+        }
+
+        let class_name = self.class_name(class).unwrap_or_else(|_| {
+            let _ = self.exception_clear();
+            "<unknown>".into()
+        });
+        not_found(class_name).into()
+    }
+
+    // Goes through `call_method_unsafe` so that getting the name isn't
+    // counted or traced itself.
+    pub(crate) fn class_name(&self, class: JObject) -> Result<String> {
+        let get_name = cached_method_id!(self, "java/lang/Class", "getName", "()Ljava/lang/String;");
+        let name = unsafe {
+            self.call_method_unsafe(class, get_name, JavaType::Object("java/lang/String".into()), &[])?
+        }.l()?.into_inner();
+        let name_str = String::from(self.get_string(JString::from(name))?);
+        self.delete_local_ref(name.into())?;
+        Ok(name_str)
+    }
+
+    // Calling through a method id looked up in a class or interface needs the
+    // receiver to be an instance of it, which the VM doesn't check. Throws a
+    // `ClassCastException`, as a cast in java would, if it isn't.
+    pub(crate) fn check_receiver(&self, obj: JObject, class: &str) -> Result<()> {
+        let class_obj = self.auto_local(self.find_class(class)?.into());
+        if !self.is_instance_of(obj, &class_obj)? {
+            self.throw_new(CLASS_CAST_EXCEPTION, format!("object is not a {}", class))?;
+            return Err(ErrorKind::JavaException.into());
+        }
+        Ok(())
+    }
+
+    // Throw and return a `JavaException` error unless `member` is an instance
+    // of `class` (`Method` or `Field`), static or not as asked. The
+    // `FromReflected*` calls crash the VM on other objects, and the ids of
+    // members of the wrong kind would be misused later.
+    fn check_reflected_member(&self, member: JObject, class: &str, is_static: bool) -> Result<()> {
+        let get_modifiers = cached_method_id!(self, "java/lang/reflect/Member", "getModifiers", "()I");
+        self.check_receiver(member, class)?;
+
+        let modifiers = unsafe {
+            self.call_method_unsafe(member, get_modifiers, JavaType::Primitive(Primitive::Int), &[])?
+        }.i()?;
+        // java.lang.reflect.Modifier.STATIC
+        if (modifiers & 0x0008 != 0) != is_static {
+            let kind = if is_static { "a static" } else { "an instance" };
+            self.throw_new(ILLEGAL_ARGUMENT_EXCEPTION, format!("expected {} {}", kind, class))?;
+            return Err(ErrorKind::JavaException.into());
         }
+        Ok(())
     }
 
     /// Look up a method by class descriptor, name, and
@@ -519,6 +940,7 @@ impl<'a> JNIEnv<'a> {
         V: Into<JNIString>,
     {
         let class = class.lookup(self)?;
+        non_null!(class, "get_field_id class argument");
         let ffi_name = name.into();
         let ffi_sig = sig.into();
 
@@ -532,17 +954,11 @@ impl<'a> JNIEnv<'a> {
             ))
         });
 
-        match res {
-            Ok(m) => Ok(m),
-            Err(e) => match e.kind() {
-                &ErrorKind::NullPtr(_) => {
-                    let name: String = ffi_name.into();
-                    let sig: String = ffi_sig.into();
-                    return Err(ErrorKind::FieldNotFound(name, sig).into());
-                }
-                _ => return Err(e),
-            },
-        }
+        res.map_err(|e| {
+            self.member_not_found(e, class.into(), NO_SUCH_FIELD_ERROR, |class| {
+                ErrorKind::FieldNotFound(class, ffi_name.into(), ffi_sig.into())
+            })
+        })
     }
 
     /// Look up the static field ID for a class/name/type combination.
@@ -563,6 +979,7 @@ impl<'a> JNIEnv<'a> {
         V: Into<JNIString>,
     {
         let class = class.lookup(self)?;
+        non_null!(class, "get_static_field_id class argument");
         let ffi_name = name.into();
         let ffi_sig = sig.into();
 
@@ -576,21 +993,16 @@ impl<'a> JNIEnv<'a> {
             ))
         });
 
-        match res {
-            Ok(m) => Ok(m),
-            Err(e) => match e.kind() {
-                &ErrorKind::NullPtr(_) => {
-                    let name: String = ffi_name.into();
-                    let sig: String = ffi_sig.into();
-                    return Err(ErrorKind::FieldNotFound(name, sig).into());
-                }
-                _ => return Err(e),
-            },
-        }
+        res.map_err(|e| {
+            self.member_not_found(e, class.into(), NO_SUCH_FIELD_ERROR, |class| {
+                ErrorKind::FieldNotFound(class, ffi_name.into(), ffi_sig.into())
+            })
+        })
     }
 
     /// Get the class for an object.
     pub fn get_object_class(&self, obj: JObject) -> Result<JClass<'a>> {
+        non_null!(obj, "get_object_class obj argument");
         Ok(jni_call!(self.internal, GetObjectClass, obj.into_inner()))
     }
 
@@ -612,7 +1024,9 @@ impl<'a> JNIEnv<'a> {
         T: Desc<'a, JClass<'a>>,
         U: Desc<'a, JStaticMethodID<'a>>,
     {
+        let _depth = CallDepthGuard::enter()?;
         let class = class.lookup(self)?;
+        non_null!(class, "call_static_method_unsafe class argument");
 
         let method_id = method_id.lookup(self)?.into_inner();
 
@@ -623,13 +1037,13 @@ impl<'a> JNIEnv<'a> {
         // TODO clean this up
         Ok(match ret {
             JavaType::Object(_) | JavaType::Array(_) => {
-                let obj: JObject = jni_call!(
+                let obj: JObject = jni_non_null_call!(
                     self.internal,
                     CallStaticObjectMethodA,
                     class,
                     method_id,
                     jni_args
-                );
+                ).into();
                 obj.into()
             }
             // JavaType::Object
@@ -723,6 +1137,8 @@ impl<'a> JNIEnv<'a> {
     where
         T: Desc<'a, JMethodID<'a>>,
     {
+        non_null!(obj, "call_method_unsafe obj argument");
+        let _depth = CallDepthGuard::enter()?;
         let method_id = method_id.lookup(self)?.into_inner();
 
         let obj = obj.into_inner();
@@ -797,6 +1213,51 @@ impl<'a> JNIEnv<'a> {
         }) // match parsed.ret
     }
 
+    /// Call one of a table of precomputed methods on an object, picked by
+    /// `index`. `method_ids` and `ret_types` are parallel tables: the method at
+    /// `method_ids[i]` must return `ret_types[i]`.
+    ///
+    /// Only the tables and the index are checked: tables of different lengths
+    /// return `InvalidArgList` and an out-of-range index returns
+    /// `InvalidMethodIndex`. Everything else has the same caveats as
+    /// `call_method_unsafe`.
+    pub unsafe fn call_method_indexed(
+        &self,
+        obj: JObject,
+        method_ids: &[JMethodID<'a>],
+        ret_types: &[JavaType],
+        index: usize,
+        args: &[JValue],
+    ) -> Result<JValue> {
+        if method_ids.len() != ret_types.len() {
+            return Err(ErrorKind::InvalidArgList.into());
+        }
+
+        let (method_id, ret) = match (method_ids.get(index), ret_types.get(index)) {
+            (Some(method_id), Some(ret)) => (*method_id, ret.clone()),
+            _ => return Err(ErrorKind::InvalidMethodIndex(index, method_ids.len()).into()),
+        };
+
+        self.call_method_unsafe(obj, method_id, ret, args)
+    }
+
+    /// Get the class of an object from a `ClassByNameCache`, adding it if
+    /// it isn't there yet. This still looks up the object's class to compare
+    /// it with the cached ones, but only reads the name of classes that
+    /// aren't cached yet and doesn't leave a local ref behind. The returned
+    /// global ref stays valid after the cache is cleared or dropped.
+    pub fn class_of(&'a self, obj: JObject, cache: &mut ClassByNameCache) -> Result<GlobalRef> {
+        non_null!(obj, "class_of obj argument");
+        let class = self.auto_local(self.get_object_class(obj)?.into());
+        if let Some(cached) = cache.find(self, class.as_obj())? {
+            return Ok(cached);
+        }
+
+        let name = self.call_string_method(class.as_obj(), "getName", "()Ljava/lang/String;", &[])?
+            .ok_or_else(|| Error::from(ErrorKind::NullPtr("class_of class name")))?;
+        Ok(cache.insert(name, self.new_global_ref(class.as_obj())?))
+    }
+
     /// Calls an object method safely. This comes with a number of
     /// lookups/checks. It
     ///
@@ -804,11 +1265,21 @@ impl<'a> JNIEnv<'a> {
     ///   type
     /// * Looks up the JClass for the given object.
     /// * Looks up the JMethodID for the class/name/signature combination
-    /// * Ensures that the number of args matches the signature
+    /// * Ensures that the number of args matches the signature, and that each
+    ///   arg is a primitive of the declared type, or an object where the
+    ///   signature has an object or array
     /// * Calls `call_method_unsafe` with the verified safe arguments.
     ///
-    /// Note: this may cause a java exception if the arguments are the wrong
-    /// type, in addition to if the method itself throws.
+    /// The returned `JValue` variant always matches the return type in the
+    /// signature, so unwrapping it as something else (e.g. `.i()` on an
+    /// object) is an `ErrorKind::WrongJValueType` error.
+    ///
+    /// Note: this may cause a java exception if an object arg isn't an
+    /// instance of the declared class, in addition to if the method itself
+    /// throws.
+    ///
+    /// If the call fails, a description of the method and of the arguments
+    /// passed is added to the error chain (see `errors::set_redact_call_args`).
     pub fn call_method<S, T>(
         &'a self,
         obj: JObject,
@@ -824,96 +1295,585 @@ impl<'a> JNIEnv<'a> {
 
         // parse the signature
         let parsed = TypeSignature::from_str(sig.as_ref())?;
-        if parsed.args.len() != args.len() {
-            return Err(ErrorKind::InvalidArgList.into());
-        }
+        check_args(&parsed.args, args)?;
 
         let class = self.auto_local(self.get_object_class(obj)?.into());
 
-        let res = unsafe { self.call_method_unsafe(obj, (&class, name, sig), parsed.ret, args) };
-
-        res
+        let name = name.into();
+        let res = (&class, name.borrowed().to_owned(), sig)
+            .lookup(self)
+            .and_then(|method_id: JMethodID<'a>| {
+                call_stats::record(self, method_id, class.as_obj(), &name, &parsed);
+                call_trace::traced(self, "call_method", class.as_obj(), &name, &parsed, || unsafe {
+                    self.call_method_unsafe(obj, method_id, parsed.ret.clone(), args)
+                })
+            });
+
+        res.map_err(|e| {
+            let name = String::from(name);
+            self.with_args_context(e, &format!("call to {}{}", name, parsed), args)
+        })
     }
 
-    /// Calls a static method safely. This comes with a number of
-    /// lookups/checks. It
-    ///
-    /// * Parses the type signature to find the number of arguments and return
-    ///   type
-    /// * Looks up the JMethodID for the class/name/signature combination
-    /// * Ensures that the number of args matches the signature
-    /// * Calls `call_method_unsafe` with the verified safe arguments.
+    /// Call the implementation of a method in a given class, without virtual
+    /// dispatch: `class` may be a superclass of the object's class, whose
+    /// implementation runs even if the object's class overrides it. This is
+    /// what `super.method()` does in java, and what the native method of a
+    /// subclass needs to call the one it overrides.
     ///
-    /// Note: this may cause a java exception if the arguments are the wrong
-    /// type, in addition to if the method itself throws.
-    pub fn call_static_method<T, U, V>(
-        &self,
-        class: T,
-        name: U,
-        sig: V,
+    /// Checks are the same as for `call_method`, and the method is looked
+    /// up in `class`.
+    pub fn call_nonvirtual_method<'c, C, S, T>(
+        &'a self,
+        obj: JObject,
+        class: C,
+        name: S,
+        sig: T,
         args: &[JValue],
     ) -> Result<JValue>
     where
-        T: Desc<'a, JClass<'a>>,
-        U: Into<JNIString>,
-        V: Into<JNIString> + AsRef<str>,
+        C: Desc<'a, JClass<'c>>,
+        S: Into<JNIString>,
+        T: Into<JNIString> + AsRef<str>,
     {
-        let parsed = TypeSignature::from_str(&sig)?;
-        if parsed.args.len() != args.len() {
-            return Err(ErrorKind::InvalidArgList.into());
-        }
+        non_null!(obj, "call_nonvirtual_method obj argument");
+
+        // parse the signature
+        let parsed = TypeSignature::from_str(sig.as_ref())?;
+        check_args(&parsed.args, args)?;
 
-        // go ahead and look up the class since it's already Copy,
-        // and we'll need that for the next call.
         let class = class.lookup(self)?;
+        // the VM doesn't check this, and runs the implementation on whatever
+        // object it's given
+        if !self.is_instance_of(obj, class)? {
+            let expected = self.class_name(class.into())?;
+            let actual = self.auto_local(self.get_object_class(obj)?.into());
+            let actual = self.class_name(actual.as_obj())?;
+            return Err(format!("nonvirtual call on a {}, which isn't a {}", actual, expected).into());
+        }
 
-        unsafe { self.call_static_method_unsafe(class, (class, name, sig), parsed.ret, args) }
+        let name = name.into();
+        let res = (class, name.borrowed().to_owned(), sig)
+            .lookup(self)
+            .and_then(|method_id: JMethodID<'a>| {
+                call_trace::traced(self, "call_nonvirtual_method", class.into(), &name, &parsed, || unsafe {
+                    self.call_nonvirtual_method_unsafe(obj, class, method_id, parsed.ret.clone(), args)
+                })
+            });
+
+        res.map_err(|e| {
+            let name = String::from(name);
+            self.with_args_context(e, &format!("nonvirtual call to {}{}", name, parsed), args)
+        })
     }
 
-    /// Create a new object using a constructor. This is done safely using
-    /// checks similar to those in `call_static_method`.
-    pub fn new_object<'c, T, U>(
+    /// Call the implementation of a method in a given class, without virtual
+    /// dispatch and without checking the arguments, like
+    /// `call_method_unsafe`. See `call_nonvirtual_method`.
+    pub unsafe fn call_nonvirtual_method_unsafe<'c, C, T>(
         &self,
-        class: T,
-        ctor_sig: U,
-        ctor_args: &[JValue],
-    ) -> Result<JObject<'a>>
+        obj: JObject,
+        class: C,
+        method_id: T,
+        ret: JavaType,
+        args: &[JValue],
+    ) -> Result<JValue>
     where
-        T: Desc<'a, JClass<'c>>,
-        U: Into<JNIString> + AsRef<str>,
+        C: Desc<'a, JClass<'c>>,
+        T: Desc<'a, JMethodID<'a>>,
     {
-        // parse the signature
-        let parsed = TypeSignature::from_str(&ctor_sig)?;
+        non_null!(obj, "call_nonvirtual_method_unsafe obj argument");
+        let _depth = CallDepthGuard::enter()?;
+        let class = class.lookup(self)?.into_inner();
+        non_null!(class, "call_nonvirtual_method_unsafe class argument");
+        let method_id = method_id.lookup(self)?.into_inner();
 
-        if parsed.args.len() != ctor_args.len() {
-            return Err(ErrorKind::InvalidArgList.into());
-        }
+        let obj = obj.into_inner();
 
-        if parsed.ret != JavaType::Primitive(Primitive::Void) {
-            return Err(ErrorKind::InvalidCtorReturn.into());
+        let args: Vec<jvalue> = args.into_iter().map(|v| v.to_jni()).collect();
+        let jni_args = args.as_ptr();
+
+        macro_rules! call {
+            ( $name:tt ) => {
+                jni_non_null_call!(self.internal, $name, obj, class, method_id, jni_args)
+            };
         }
 
-        // build strings
-        let class = class.lookup(self)?;
+        Ok(match ret {
+            JavaType::Object(_) | JavaType::Array(_) => {
+                let obj: JObject = call!(CallNonvirtualObjectMethodA).into();
+                obj.into()
+            }
+            JavaType::Method(_) => {
+                return Err(ErrorKind::WrongJValueType("Method", "see java method").into())
+            }
+            JavaType::Primitive(p) => match p {
+                Primitive::Boolean => (call!(CallNonvirtualBooleanMethodA) == sys::JNI_TRUE).into(),
+                Primitive::Char => call!(CallNonvirtualCharMethodA).into(),
+                Primitive::Short => call!(CallNonvirtualShortMethodA).into(),
+                Primitive::Int => call!(CallNonvirtualIntMethodA).into(),
+                Primitive::Long => call!(CallNonvirtualLongMethodA).into(),
+                Primitive::Float => call!(CallNonvirtualFloatMethodA).into(),
+                Primitive::Double => call!(CallNonvirtualDoubleMethodA).into(),
+                Primitive::Byte => call!(CallNonvirtualByteMethodA).into(),
+                Primitive::Void => {
+                    jni_void_call!(self.internal, CallNonvirtualVoidMethodA, obj, class, method_id, jni_args);
+                    JValue::Void
+                }
+            },
+        })
+    }
 
-        let method_id: JMethodID = (class, ctor_sig).lookup(self)?;
+    /// Call a method returning a `java.lang.String` and decode the result.
+    /// Returns `None` if the method returned null.
+    ///
+    /// Checks are the same as for `call_method`, plus the return type in the
+    /// signature must be `Ljava/lang/String;`.
+    pub fn call_string_method<S, T>(
+        &'a self,
+        obj: JObject,
+        name: S,
+        sig: T,
+        args: &[JValue],
+    ) -> Result<Option<String>>
+    where
+        S: Into<JNIString>,
+        T: Into<JNIString> + AsRef<str>,
+    {
+        let parsed = TypeSignature::from_str(sig.as_ref())?;
+        if parsed.ret != JavaType::Object("java/lang/String".into()) {
+            return Err(format!("not a String-returning signature: {}", sig.as_ref()).into());
+        }
+
+        let ret = self.auto_local(self.call_method(obj, name, sig, args)?.l()?);
+        if ret.as_obj().is_null() {
+            return Ok(None);
+        }
 
-        self.new_object_by_id(class, method_id, ctor_args)
+        let s: String = self.get_string(ret.as_obj().into())?.into();
+        Ok(Some(s))
+    }
+
+    /// Call a method returning a boolean, e.g. a predicate like `isEmpty` or
+    /// `contains`.
+    ///
+    /// Checks are the same as for `call_method`, plus the return type in the
+    /// signature must be `Z`. Any non-zero `jboolean` is `true`.
+    pub fn call_bool_method<S, T>(
+        &'a self,
+        obj: JObject,
+        name: S,
+        sig: T,
+        args: &[JValue],
+    ) -> Result<bool>
+    where
+        S: Into<JNIString>,
+        T: Into<JNIString> + AsRef<str>,
+    {
+        let parsed = TypeSignature::from_str(sig.as_ref())?;
+        if parsed.ret != JavaType::Primitive(Primitive::Boolean) {
+            return Err(format!("not a boolean-returning signature: {}", sig.as_ref()).into());
+        }
+
+        self.call_method(obj, name, sig, args)?.z()
+    }
+
+    /// Call a method and turn whatever it returns into a readable string:
+    /// objects through their `toString()`, primitives formatted the way java
+    /// would. Null results give `"null"` and void methods `"void"`. Meant for
+    /// logging and debugging.
+    pub fn call_method_to_string<S, T>(
+        &'a self,
+        obj: JObject,
+        name: S,
+        sig: T,
+        args: &[JValue],
+    ) -> Result<String>
+    where
+        S: Into<JNIString>,
+        T: Into<JNIString> + AsRef<str>,
+    {
+        Ok(match self.call_method(obj, name, sig, args)? {
+            JValue::Object(ret) => {
+                if ret.is_null() {
+                    return Ok("null".into());
+                }
+                let ret = self.auto_local(ret);
+                self.call_string_method(ret.as_obj(), "toString", "()Ljava/lang/String;", &[])?
+                    .unwrap_or_else(|| "null".into())
+            }
+            JValue::Bool(b) => (b != 0).to_string(),
+            JValue::Char(c) => String::from_utf16_lossy(&[c]),
+            JValue::Byte(b) => b.to_string(),
+            JValue::Short(s) => s.to_string(),
+            JValue::Int(i) => i.to_string(),
+            JValue::Long(l) => l.to_string(),
+            JValue::Float(f) => f.to_string(),
+            JValue::Double(d) => d.to_string(),
+            JValue::Void => "void".into(),
+        })
+    }
+
+    /// Call a method with a soft timeout. If the call hasn't returned after
+    /// `timeout`, a watchdog thread calls `Thread.interrupt()` on the java
+    /// thread making the call. The watchdog is started by the first such call
+    /// and shared by all of them.
+    ///
+    /// This only works for java code that honors interruption, e.g. blocking
+    /// I/O on interruptible channels, `Thread.sleep` or `Object.wait`. Such
+    /// code usually gives up with an `InterruptedException`, which is left
+    /// pending as for `call_method`. A call that ignores interruption simply
+    /// runs to completion. If the watchdog fired but the call returned without
+    /// throwing, the thread's interrupt flag is cleared again.
+    pub fn call_method_with_interrupt<S, T>(
+        &'a self,
+        obj: JObject,
+        name: S,
+        sig: T,
+        args: &[JValue],
+        timeout: Duration,
+    ) -> Result<JValue<'a>>
+    where
+        S: Into<JNIString>,
+        T: Into<JNIString> + AsRef<str>,
+    {
+        let current = self.auto_local(self.current_thread()?);
+        let current = self.new_global_ref(current.as_obj())?;
+        let watch = watchdog::watch(self.get_java_vm()?, current, timeout)?;
+
+        let res = self.call_method(obj, name, sig, args);
+
+        if watch.finish() && !self.exception_check()? {
+            let clear = cached_static_method_id!(self, "java/lang/Thread", "interrupted", "()Z");
+            unsafe {
+                self.call_static_method_unsafe(
+                    JDK_CLASSES.find_class(self, "java/lang/Thread")?,
+                    clear,
+                    JavaType::Primitive(Primitive::Boolean),
+                    &[],
+                )?;
+            }
+        }
+        res
+    }
+
+    /// Shortcut for `call_method` with no arguments, e.g. for getters.
+    pub fn call_method_0<S, T>(&'a self, obj: JObject, name: S, sig: T) -> Result<JValue<'a>>
+    where
+        S: Into<JNIString>,
+        T: Into<JNIString> + AsRef<str>,
+    {
+        self.call_method(obj, name, sig, &[])
+    }
+
+    /// Shortcut for `call_method_0` on a method returning an object.
+    pub fn call_object_method_0<S, T>(&'a self, obj: JObject, name: S, sig: T) -> Result<JObject<'a>>
+    where
+        S: Into<JNIString>,
+        T: Into<JNIString> + AsRef<str>,
+    {
+        self.call_method_0(obj, name, sig)?.l()
+    }
+
+    /// Shortcut for `call_method_0` on a method returning a boolean.
+    pub fn call_bool_method_0<S, T>(&'a self, obj: JObject, name: S, sig: T) -> Result<bool>
+    where
+        S: Into<JNIString>,
+        T: Into<JNIString> + AsRef<str>,
+    {
+        self.call_method_0(obj, name, sig)?.z()
+    }
+
+    /// Shortcut for `call_method_0` on a method returning an int.
+    pub fn call_int_method_0<S, T>(&'a self, obj: JObject, name: S, sig: T) -> Result<jint>
+    where
+        S: Into<JNIString>,
+        T: Into<JNIString> + AsRef<str>,
+    {
+        self.call_method_0(obj, name, sig)?.i()
+    }
+
+    /// Shortcut for `call_method_0` on a method returning a long.
+    pub fn call_long_method_0<S, T>(&'a self, obj: JObject, name: S, sig: T) -> Result<jlong>
+    where
+        S: Into<JNIString>,
+        T: Into<JNIString> + AsRef<str>,
+    {
+        self.call_method_0(obj, name, sig)?.j()
+    }
+
+    /// Shortcut for `call_method_0` on a method returning a double.
+    pub fn call_double_method_0<S, T>(&'a self, obj: JObject, name: S, sig: T) -> Result<jdouble>
+    where
+        S: Into<JNIString>,
+        T: Into<JNIString> + AsRef<str>,
+    {
+        self.call_method_0(obj, name, sig)?.d()
+    }
+
+    /// Calls a static method safely. This comes with a number of
+    /// lookups/checks. It
+    ///
+    /// * Parses the type signature to find the number of arguments and return
+    ///   type
+    /// * Looks up the JMethodID for the class/name/signature combination
+    /// * Ensures that the number of args matches the signature, and that each
+    ///   arg is a primitive of the declared type, or an object where the
+    ///   signature has an object or array
+    /// * Calls `call_method_unsafe` with the verified safe arguments.
+    ///
+    /// The returned `JValue` variant always matches the return type in the
+    /// signature, so unwrapping it as something else (e.g. `.i()` on an
+    /// object) is an `ErrorKind::WrongJValueType` error.
+    ///
+    /// Note: this may cause a java exception if an object arg isn't an
+    /// instance of the declared class, in addition to if the method itself
+    /// throws.
+    pub fn call_static_method<T, U, V>(
+        &self,
+        class: T,
+        name: U,
+        sig: V,
+        args: &[JValue],
+    ) -> Result<JValue>
+    where
+        T: Desc<'a, JClass<'a>>,
+        U: Into<JNIString>,
+        V: Into<JNIString> + AsRef<str>,
+    {
+        let parsed = TypeSignature::from_str(&sig)?;
+        check_args(&parsed.args, args)?;
+
+        // go ahead and look up the class since it's already Copy,
+        // and we'll need that for the next call.
+        let class = class.lookup(self)?;
+
+        let name = name.into();
+        call_trace::traced(self, "call_static_method", class.into(), &name, &parsed, || unsafe {
+            self.call_static_method_unsafe(class, (class, name.borrowed().to_owned(), sig), parsed.ret.clone(), args)
+        })
+    }
+
+    /// Call an object method declared with varargs, such as
+    /// `void log(String fmt, Object... args)`.
+    ///
+    /// `fixed_sig_prefix` is the method's signature without the varargs
+    /// parameter, e.g. `(Ljava/lang/String;)V`, and `element_class` is the
+    /// element type of that parameter, e.g. `java/lang/Object`, or an array
+    /// type such as `[I`. `fixed_args` are passed for the leading parameters,
+    /// and `var_args` are packed into a new array of `element_class` that's
+    /// passed last. Primitive varargs aren't supported.
+    pub fn call_varargs_method<S, T>(
+        &'a self,
+        obj: JObject,
+        name: S,
+        fixed_sig_prefix: T,
+        element_class: &str,
+        fixed_args: &[JValue],
+        var_args: &[JObject],
+    ) -> Result<JValue>
+    where
+        S: Into<JNIString>,
+        T: AsRef<str>,
+    {
+        let (sig, array) = self.new_varargs_array(fixed_sig_prefix.as_ref(), element_class, fixed_args, var_args)?;
+        let array = self.auto_local(array);
+        let mut args = fixed_args.to_vec();
+        args.push(array.as_obj().into());
+
+        self.call_method(obj, name, sig, &args)
+    }
+
+    /// Call a static method declared with varargs, such as
+    /// `String.format(String, Object...)`. See `call_varargs_method` for how
+    /// the signature is built and the arguments are packed.
+    ///
+    /// # Example
+    /// ```rust,ignore
+    /// let s = env.call_static_varargs_method(
+    ///     "java/lang/String",
+    ///     "format",
+    ///     "(Ljava/lang/String;)Ljava/lang/String;",
+    ///     "java/lang/Object",
+    ///     &[fmt.into()],
+    ///     &[arg1, arg2],
+    /// )?;
+    /// ```
+    pub fn call_static_varargs_method<T, U, V>(
+        &'a self,
+        class: T,
+        name: U,
+        fixed_sig_prefix: V,
+        element_class: &str,
+        fixed_args: &[JValue],
+        var_args: &[JObject],
+    ) -> Result<JValue>
+    where
+        T: Desc<'a, JClass<'a>>,
+        U: Into<JNIString>,
+        V: AsRef<str>,
+    {
+        let (sig, array) = self.new_varargs_array(fixed_sig_prefix.as_ref(), element_class, fixed_args, var_args)?;
+        let array = self.auto_local(array);
+        let mut args = fixed_args.to_vec();
+        args.push(array.as_obj().into());
+
+        self.call_static_method(class, name, sig, &args)
+    }
+
+    /// Builds the full signature and the trailing array for a varargs call.
+    fn new_varargs_array(
+        &self,
+        fixed_sig_prefix: &str,
+        element_class: &str,
+        fixed_args: &[JValue],
+        var_args: &[JObject],
+    ) -> Result<(String, JObject<'a>)> {
+        let mut parsed = TypeSignature::from_str(fixed_sig_prefix)?;
+        check_args(&parsed.args, fixed_args)?;
+
+        let element = match element_class.starts_with('[') {
+            true => JavaType::from_str(element_class)?,
+            false => JavaType::Object(element_class.to_owned()),
+        };
+        parsed.args.push(JavaType::Array(Box::new(element)));
+
+        let array = self.new_object_array(var_args.len() as jsize, element_class, JObject::null())?;
+        for (i, arg) in var_args.iter().enumerate() {
+            self.set_object_array_element(array, i as jsize, *arg)?;
+        }
+        Ok((parsed.to_string(), JObject::from(array)))
+    }
+
+    /// Create a new object using a constructor. This is done safely using
+    /// checks similar to those in `call_static_method`.
+    pub fn new_object<'c, T, U>(
+        &self,
+        class: T,
+        ctor_sig: U,
+        ctor_args: &[JValue],
+    ) -> Result<JObject<'a>>
+    where
+        T: Desc<'a, JClass<'c>>,
+        U: Into<JNIString> + AsRef<str>,
+    {
+        // parse the signature
+        let parsed = TypeSignature::from_str(&ctor_sig)?;
+
+        check_args(&parsed.args, ctor_args)?;
+
+        if parsed.ret != JavaType::Primitive(Primitive::Void) {
+            return Err(ErrorKind::InvalidCtorReturn.into());
+        }
+
+        // build strings
+        let class = class.lookup(self)?;
+
+        let method_id: JMethodID = (class, ctor_sig).lookup(self)?;
+
+        call_trace::traced(self, "new_object", class.into(), call_trace::constructor_name(), &parsed, || {
+            self.new_object_by_id(class, method_id, ctor_args)
+        }).map_err(|e| self.with_args_context(e, &format!("constructor {}", parsed), ctor_args))
+    }
+
+    /// Attach the arguments of a failed call to its error. Object arguments
+    /// are described with `toString()` unless `set_redact_call_args` is on, or
+    /// the error is a `JavaError`, after which no more java code should run.
+    /// Any pending exception is set aside while doing so and thrown again
+    /// afterwards.
+    fn with_args_context(&self, err: Error, call: &str, args: &[JValue]) -> Error {
+        let redact = redact_call_args() || match *err.kind() {
+            ErrorKind::JavaError => true,
+            _ => false,
+        };
+
+        match self.describe_args(args, redact) {
+            Ok(described) => with_call_context(
+                err,
+                format!("{} failed with arguments ({})", call, described),
+            ),
+            Err(_) => err,
+        }
+    }
+
+    fn describe_args(&self, args: &[JValue], redact: bool) -> Result<String> {
+        let pending = if redact {
+            JObject::null()
+        } else {
+            let pending = JObject::from(self.exception_occurred()?.into_inner());
+            self.exception_clear()?;
+            pending
+        };
+
+        let described: Vec<String> = args.iter().map(|arg| self.describe_arg(arg, redact)).collect();
+
+        if !pending.is_null() {
+            self.throw(JThrowable::from(pending.into_inner() as jthrowable))?;
+            self.delete_local_ref(pending)?;
+        }
+
+        Ok(described.join(", "))
+    }
+
+    fn describe_arg(&self, arg: &JValue, redact: bool) -> String {
+        match *arg {
+            JValue::Object(obj) if obj.is_null() => "null".into(),
+            JValue::Object(_) if redact => "<object>".into(),
+            JValue::Object(obj) => {
+                let described = self.call_method(obj, "toString", "()Ljava/lang/String;", &[])
+                    .and_then(|s| s.l())
+                    .and_then(|s| {
+                        let described = String::from(self.get_string(s.into())?);
+                        self.delete_local_ref(s)?;
+                        Ok(described)
+                    });
+                match described {
+                    Ok(s) => format!("{:?}", s),
+                    Err(_) => {
+                        let _ = self.exception_clear();
+                        "<object>".into()
+                    }
+                }
+            }
+            JValue::Bool(b) => (b != 0).to_string(),
+            JValue::Char(c) => format!("{:?}", String::from_utf16_lossy(&[c])),
+            JValue::Byte(b) => b.to_string(),
+            JValue::Short(s) => s.to_string(),
+            JValue::Int(i) => i.to_string(),
+            JValue::Long(l) => l.to_string(),
+            JValue::Float(f) => f.to_string(),
+            JValue::Double(d) => d.to_string(),
+            JValue::Void => "void".into(),
+        }
     }
 
     /// Create a new object using a constructor. Arguments aren't checked
     /// because
     /// of the `JMethodID` usage.
-    pub fn new_object_by_id<'c, T>(
+    ///
+    /// The constructor can be given as a `JMethodID` or any descriptor of
+    /// one, such as a `Cached` descriptor, which looks it up only once:
+    ///
+    /// ```rust,ignore
+    /// let point = env.new_object_by_id(
+    ///     "com/example/Point",
+    ///     Cached("com/example/Point", "<init>", "(II)V"),
+    ///     &[JValue::Int(1), JValue::Int(2)],
+    /// )?;
+    /// ```
+    pub fn new_object_by_id<'c, T, U>(
         &self,
         class: T,
-        ctor_id: JMethodID,
+        ctor_id: U,
         ctor_args: &[JValue],
     ) -> Result<JObject<'a>>
     where
         T: Desc<'a, JClass<'c>>,
+        U: Desc<'a, JMethodID<'a>>,
     {
+        let _depth = CallDepthGuard::enter()?;
         let class = class.lookup(self)?;
+        let ctor_id = ctor_id.lookup(self)?;
 
         let jni_args: Vec<jvalue> = ctor_args.into_iter().map(|v| v.to_jni()).collect();
         let jni_args = jni_args.as_ptr();
@@ -927,20 +1887,1068 @@ impl<'a> JNIEnv<'a> {
         ))
     }
 
-    /// Cast a JObject to a JString. This won't throw exceptions or return errors
-    /// in the event that the object isn't actually a list, but the methods on
-    /// the resulting map object will.
-    pub fn get_list(&self, obj: JObject<'a>) -> Result<JList> {
-        non_null!(obj, "get_list obj argument");
-        JList::from_env(self, obj)
+    /// Find the public constructor of a class whose parameters accept the given
+    /// argument types, using `Class.getConstructors()`.
+    ///
+    /// A constructor matches if it takes exactly `arg_types.len()` parameters
+    /// and each argument type is assignable to the corresponding parameter
+    /// type. For primitive parameters, pass the primitive class (e.g. the
+    /// value of `Integer.TYPE`). Returns `NoMatchingOverload` if nothing
+    /// matches and `AmbiguousOverload` if more than one constructor does.
+    ///
+    /// The returned ID can be cached and passed to `new_object_by_id`.
+    pub fn find_constructor<'c, T>(
+        &'a self,
+        class: T,
+        arg_types: &[JClass],
+    ) -> Result<JMethodID<'a>>
+    where
+        T: Desc<'a, JClass<'c>>,
+    {
+        let class = class.lookup(self)?;
+
+        let ctors = self.call_method(
+            class.into(),
+            "getConstructors",
+            "()[Ljava/lang/reflect/Constructor;",
+            &[],
+        )?.l()?;
+        let ctors = self.auto_local(ctors);
+        let ctors_array = ctors.as_obj().into_inner() as jobjectArray;
+
+        let mut found = None;
+        for i in 0..self.get_array_length(ctors_array)? {
+            let ctor = self.auto_local(self.get_object_array_element(ctors_array, i)?);
+            if !self.parameters_accept(ctor.as_obj(), arg_types)? {
+                continue;
+            }
+            if found.is_some() {
+                return Err(ErrorKind::AmbiguousOverload("<init>".into()).into());
+            }
+            found = Some(self.from_reflected_method(ctor.as_obj())?);
+        }
+
+        match found {
+            Some(id) => Ok(id),
+            None => Err(ErrorKind::NoMatchingOverload("<init>".into()).into()),
+        }
+    }
+
+    /// Checks whether the parameters of a reflected `Method` or `Constructor`
+    /// accept arguments of the given types.
+    fn parameters_accept(&'a self, executable: JObject, arg_types: &[JClass]) -> Result<bool> {
+        let params = self.call_method(executable, "getParameterTypes", "()[Ljava/lang/Class;", &[])?
+            .l()?;
+        let params = self.auto_local(params);
+        let params_array = params.as_obj().into_inner() as jobjectArray;
+
+        if self.get_array_length(params_array)? as usize != arg_types.len() {
+            return Ok(false);
+        }
+
+        for (i, arg_type) in arg_types.iter().enumerate() {
+            let param = self.auto_local(self.get_object_array_element(params_array, i as jsize)?);
+            if !self.is_assignable_from(*arg_type, JClass::from(param.as_obj()))? {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Create a new object using whichever public constructor accepts the given
+    /// argument types. See `find_constructor` for how the constructor is
+    /// picked. `args` must line up with `arg_types`.
+    ///
+    /// This does the reflective lookup on every call; if the same constructor
+    /// is used repeatedly, cache the result of `find_constructor` instead.
+    pub fn new_object_reflective<'c, T>(
+        &'a self,
+        class: T,
+        arg_types: &[JClass],
+        args: &[JValue],
+    ) -> Result<JObject<'a>>
+    where
+        T: Desc<'a, JClass<'c>>,
+    {
+        if arg_types.len() != args.len() {
+            return Err(ErrorKind::InvalidArgList.into());
+        }
+
+        let class = class.lookup(self)?;
+        let ctor_id = self.find_constructor(class, arg_types)?;
+        self.new_object_by_id(class, ctor_id, args)
+    }
+
+    /// Get the method ID for a `java.lang.reflect.Method` or
+    /// `java.lang.reflect.Constructor` object.
+    pub fn from_reflected_method(&self, method: JObject) -> Result<JMethodID<'a>> {
+        non_null!(method, "from_reflected_method method argument");
+        let constructor_class = self.auto_local(self.find_class("java/lang/reflect/Constructor")?.into());
+        if !self.is_instance_of(method, &constructor_class)? {
+            self.check_reflected_member(method, "java/lang/reflect/Method", false)?;
+        }
+        Ok(jni_call!(self.internal, FromReflectedMethod, method.into_inner()))
+    }
+
+    /// Get the method ID for a static `java.lang.reflect.Method` object.
+    pub fn from_reflected_static_method(&self, method: JObject) -> Result<JStaticMethodID<'a>> {
+        non_null!(method, "from_reflected_static_method method argument");
+        self.check_reflected_member(method, "java/lang/reflect/Method", true)?;
+        let id: JMethodID = jni_call!(self.internal, FromReflectedMethod, method.into_inner());
+        Ok(id.into_inner().into())
+    }
+
+    /// Get the field ID for a `java.lang.reflect.Field` object.
+    pub fn from_reflected_field(&self, field: JObject) -> Result<JFieldID<'a>> {
+        non_null!(field, "from_reflected_field field argument");
+        self.check_reflected_member(field, "java/lang/reflect/Field", false)?;
+        Ok(jni_call!(self.internal, FromReflectedField, field.into_inner()))
+    }
+
+    /// Get the field ID for a static `java.lang.reflect.Field` object.
+    pub fn from_reflected_static_field(&self, field: JObject) -> Result<JStaticFieldID<'a>> {
+        non_null!(field, "from_reflected_static_field field argument");
+        self.check_reflected_member(field, "java/lang/reflect/Field", true)?;
+        let id: JFieldID = jni_call!(self.internal, FromReflectedField, field.into_inner());
+        Ok(id.into_inner().into())
+    }
+
+    /// Get a `java.lang.reflect.Method` (or `Constructor`, for `<init>`)
+    /// object for a method of a class, e.g. to hand it to java code.
+    pub fn to_reflected_method<'c, T, U>(&self, class: T, method: U) -> Result<JObject<'a>>
+    where
+        T: Desc<'a, JClass<'c>>,
+        U: Desc<'a, JMethodID<'a>>,
+    {
+        let class = class.lookup(self)?;
+        let method = method.lookup(self)?;
+        Ok(jni_call!(
+            self.internal,
+            ToReflectedMethod,
+            class.into_inner(),
+            method.into_inner(),
+            sys::JNI_FALSE
+        ))
+    }
+
+    /// Get a `java.lang.reflect.Method` object for a static method.
+    pub fn to_reflected_static_method<'c, T, U>(&self, class: T, method: U) -> Result<JObject<'a>>
+    where
+        T: Desc<'a, JClass<'c>>,
+        U: Desc<'a, JStaticMethodID<'a>>,
+    {
+        let class = class.lookup(self)?;
+        let method = method.lookup(self)?;
+        Ok(jni_call!(
+            self.internal,
+            ToReflectedMethod,
+            class.into_inner(),
+            method.into_inner(),
+            sys::JNI_TRUE
+        ))
+    }
+
+    /// Get a `java.lang.reflect.Field` object for a field of a class.
+    pub fn to_reflected_field<'c, T, U>(&self, class: T, field: U) -> Result<JObject<'a>>
+    where
+        T: Desc<'a, JClass<'c>>,
+        U: Desc<'a, JFieldID<'a>>,
+    {
+        let class = class.lookup(self)?;
+        let field = field.lookup(self)?;
+        Ok(jni_call!(
+            self.internal,
+            ToReflectedField,
+            class.into_inner(),
+            field.into_inner(),
+            sys::JNI_FALSE
+        ))
+    }
+
+    /// Get a `java.lang.reflect.Field` object for a static field.
+    pub fn to_reflected_static_field<'c, T, U>(&self, class: T, field: U) -> Result<JObject<'a>>
+    where
+        T: Desc<'a, JClass<'c>>,
+        U: Desc<'a, JStaticFieldID<'a>>,
+    {
+        let class = class.lookup(self)?;
+        let field = field.lookup(self)?;
+        Ok(jni_call!(
+            self.internal,
+            ToReflectedField,
+            class.into_inner(),
+            field.into_inner(),
+            sys::JNI_TRUE
+        ))
+    }
+
+    /// Find out what kind of value a `java.lang.reflect.Method` returns, from
+    /// its return type's `Class`. Useful for invoking methods found through
+    /// reflection without having their signature.
+    pub fn return_type_kind(&'a self, method: JObject) -> Result<ReturnKind> {
+        non_null!(method, "return_type_kind method argument");
+        let get_return_type = cached_method_id!(
+            self,
+            "java/lang/reflect/Method",
+            "getReturnType",
+            "()Ljava/lang/Class;"
+        );
+        let is_primitive = cached_method_id!(self, "java/lang/Class", "isPrimitive", "()Z");
+        let is_array = cached_method_id!(self, "java/lang/Class", "isArray", "()Z");
+        let bool_ret = || JavaType::Primitive(Primitive::Boolean);
+        self.check_receiver(method, "java/lang/reflect/Method")?;
+
+        let ret = unsafe {
+            self.call_method_unsafe(
+                method,
+                get_return_type,
+                JavaType::Object("java/lang/Class".into()),
+                &[],
+            )?
+        }.l()?;
+        let ret = self.auto_local(non_null!(ret, "return_type_kind return type"));
+
+        if unsafe { self.call_method_unsafe(ret.as_obj(), is_array, bool_ret(), &[])? }.z()? {
+            return Ok(ReturnKind::Array);
+        }
+        if !unsafe { self.call_method_unsafe(ret.as_obj(), is_primitive, bool_ret(), &[])? }.z()? {
+            return Ok(ReturnKind::Object);
+        }
+
+        let name = self.call_string_method(ret.as_obj(), "getName", "()Ljava/lang/String;", &[])?
+            .unwrap_or_default();
+        Ok(ReturnKind::Primitive(match name.as_str() {
+            "void" => return Ok(ReturnKind::Void),
+            "boolean" => Primitive::Boolean,
+            "byte" => Primitive::Byte,
+            "char" => Primitive::Char,
+            "short" => Primitive::Short,
+            "int" => Primitive::Int,
+            "long" => Primitive::Long,
+            "float" => Primitive::Float,
+            "double" => Primitive::Double,
+            _ => return Err(format!("unknown primitive type: {}", name).into()),
+        }))
+    }
+
+    /// Pick the public instance method called `name` that can take arguments
+    /// of the given types, for calling overloaded methods whose exact
+    /// signature isn't known up front. Returns its method id along with its
+    /// signature, which `call_method_unsafe` needs for the return type.
+    ///
+    /// Primitive arguments have to match the parameter type exactly; object
+    /// and array arguments match any parameter type they can be assigned to.
+    /// If several methods match, the most specific one is picked as in java,
+    /// e.g. `println(String)` rather than `println(Object)` for a string.
+    /// If no method matches, a `MethodNotFound` error is returned. If no
+    /// single one is more specific than all the others, the call is
+    /// ambiguous and an error listing the candidates is returned.
+    pub fn resolve_overload<'c, T>(
+        &'a self,
+        class: T,
+        name: &str,
+        arg_types: &[JavaType],
+    ) -> Result<(JMethodID<'a>, TypeSignature)>
+    where
+        T: Desc<'a, JClass<'c>>,
+    {
+        // Looking the class up by name makes a local ref, which goes with the
+        // frame along with the ones made to look at the methods.
+        self.push_local_frame(arg_types.len() as i32 + 8)?;
+        let res = catch!({ self.resolve_overload_in_frame(class, name, arg_types) });
+        self.pop_local_frame(JObject::null())?;
+        res
+    }
+
+    fn resolve_overload_in_frame<'c, T>(
+        &'a self,
+        class: T,
+        name: &str,
+        arg_types: &[JavaType],
+    ) -> Result<(JMethodID<'a>, TypeSignature)>
+    where
+        T: Desc<'a, JClass<'c>>,
+    {
+        let class = class.lookup(self)?;
+
+        let mut arg_classes = Vec::with_capacity(arg_types.len());
+        for ty in arg_types {
+            arg_classes.push(match *ty {
+                JavaType::Primitive(Primitive::Void) | JavaType::Method(_) => {
+                    return Err(ErrorKind::InvalidArgList.into())
+                }
+                JavaType::Primitive(_) => None,
+                JavaType::Object(ref name) => Some(self.auto_local(self.find_class(name.as_str())?.into())),
+                JavaType::Array(_) => Some(self.auto_local(self.find_class(ty.to_string())?.into())),
+            });
+        }
+
+        let methods = self.call_method(class.into(), "getMethods", "()[Ljava/lang/reflect/Method;", &[])?
+            .l()?;
+        let methods = self.auto_local(methods);
+        let methods_array = methods.as_obj().into_inner() as jobjectArray;
+
+        let mut found: Vec<(AutoLocal, TypeSignature)> = Vec::new();
+        for i in 0..self.get_array_length(methods_array)? {
+            // looking at a method takes a handful of local refs, which go
+            // with the frame; only a matching method is kept
+            let mut candidate = None;
+            let method = self.with_local_frame(16, |env| {
+                let method = env.get_object_array_element(methods_array, i)?;
+                candidate = env.overload_candidate(method, name, arg_types, &arg_classes)?;
+                Ok(if candidate.is_some() { method } else { JObject::null() })
+            })?;
+            if let Some(sig) = candidate {
+                let method = self.auto_local(method);
+                // the same parameters with a different return type is a
+                // covariant override, found through a supertype
+                if !found.iter().any(|f| f.1.args == sig.args) {
+                    found.push((method, sig));
+                }
+            }
+        }
+
+        // Of the methods that fit, java picks the most specific one: drop
+        // any that another one's parameters can all be passed to.
+        if found.len() > 1 {
+            let mut most_specific = Vec::with_capacity(found.len());
+            for (i, f) in found.iter().enumerate() {
+                let mut is_most_specific = true;
+                for (j, other) in found.iter().enumerate() {
+                    if i != j && self.params_assignable(other.0.as_obj(), f.0.as_obj())? {
+                        is_most_specific = false;
+                        break;
+                    }
+                }
+                most_specific.push(is_most_specific);
+            }
+            let mut most_specific = most_specific.into_iter();
+            found.retain(|_| most_specific.next().unwrap());
+        }
+
+        match found.len() {
+            0 => {
+                let args: Vec<String> = arg_types.iter().map(|ty| ty.to_string()).collect();
+                let class_name = self.class_name(class.into())?;
+                Err(ErrorKind::MethodNotFound(class_name, name.into(), format!("({})", args.concat())).into())
+            }
+            1 => {
+                let (method, sig) = found.remove(0);
+                Ok((self.from_reflected_method(method.as_obj())?, sig))
+            }
+            _ => {
+                let candidates: Vec<String> = found.iter().map(|f| f.1.to_string()).collect();
+                Err(format!("call to {} is ambiguous between {}", name, candidates.join(", ")).into())
+            }
+        }
+    }
+
+    /// Whether every parameter of the `java.lang.reflect.Method` `a` can be
+    /// assigned to the matching one of `b`, which takes as many.
+    fn params_assignable(&'a self, a: JObject, b: JObject) -> Result<bool> {
+        let get_params = |method| -> Result<AutoLocal> {
+            let params = self.call_method(method, "getParameterTypes", "()[Ljava/lang/Class;", &[])?.l()?;
+            Ok(self.auto_local(params))
+        };
+        let a_params = get_params(a)?;
+        let b_params = get_params(b)?;
+        let a_array = a_params.as_obj().into_inner() as jobjectArray;
+        let b_array = b_params.as_obj().into_inner() as jobjectArray;
+
+        for i in 0..self.get_array_length(a_array)? {
+            let a_param = self.auto_local(self.get_object_array_element(a_array, i)?);
+            let b_param = self.auto_local(self.get_object_array_element(b_array, i)?);
+            if !self.is_assignable_from(JClass::from(a_param.as_obj()), JClass::from(b_param.as_obj()))? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Check whether a `java.lang.reflect.Method` fits `resolve_overload`'s
+    /// query, and get its signature if it does.
+    fn overload_candidate(
+        &'a self,
+        method: JObject,
+        name: &str,
+        arg_types: &[JavaType],
+        arg_classes: &[Option<AutoLocal>],
+    ) -> Result<Option<TypeSignature>> {
+        let method_name = self.call_string_method(method, "getName", "()Ljava/lang/String;", &[])?;
+        if method_name.as_ref().map(|n| n.as_str()) != Some(name) {
+            return Ok(None);
+        }
+        let modifiers = self.call_method(method, "getModifiers", "()I", &[])?.i()?;
+        // java.lang.reflect.Modifier.STATIC
+        let is_static = modifiers & 0x0008 != 0;
+        if is_static || self.call_method(method, "isBridge", "()Z", &[])?.z()? {
+            return Ok(None);
+        }
+
+        let params = self.call_method(method, "getParameterTypes", "()[Ljava/lang/Class;", &[])?.l()?;
+        let params = self.auto_local(params);
+        let params_array = params.as_obj().into_inner() as jobjectArray;
+        if self.get_array_length(params_array)? as usize != arg_types.len() {
+            return Ok(None);
+        }
+
+        let mut args = Vec::with_capacity(arg_types.len());
+        for (i, arg) in arg_types.iter().enumerate() {
+            let param = self.auto_local(self.get_object_array_element(params_array, i as jsize)?);
+            let param_type = JavaType::from_str(&self.class_type_signature(param.as_obj())?)?;
+            let matches = match (&arg_classes[i], &param_type) {
+                (&None, _) => *arg == param_type,
+                (&Some(_), &JavaType::Primitive(_)) => false,
+                (&Some(ref arg_class), _) => self.is_assignable_from(
+                    JClass::from(arg_class.as_obj()),
+                    JClass::from(param.as_obj()),
+                )?,
+            };
+            if !matches {
+                return Ok(None);
+            }
+            args.push(param_type);
+        }
+
+        let ret = self.call_method(method, "getReturnType", "()Ljava/lang/Class;", &[])?.l()?;
+        let ret = self.auto_local(ret);
+        Ok(Some(TypeSignature {
+            args: args,
+            ret: JavaType::from_str(&self.class_type_signature(ret.as_obj())?)?,
+        }))
+    }
+
+    /// Get the type signature for a `java.lang.Class`, e.g. `I` for `int`
+    /// and `Ljava/lang/String;` for `String`.
+    pub(crate) fn class_type_signature(&'a self, class: JObject) -> Result<String> {
+        let name = self.call_string_method(class, "getName", "()Ljava/lang/String;", &[])?
+            .unwrap_or_default();
+        Ok(match name.as_str() {
+            "boolean" => "Z".into(),
+            "byte" => "B".into(),
+            "char" => "C".into(),
+            "short" => "S".into(),
+            "int" => "I".into(),
+            "long" => "J".into(),
+            "float" => "F".into(),
+            "double" => "D".into(),
+            "void" => "V".into(),
+            // arrays are already named by their signature, with dots
+            _ if name.starts_with('[') => name.replace('.', "/"),
+            _ => format!("L{};", name.replace('.', "/")),
+        })
+    }
+
+    /// Cast a JObject to a JString. This won't throw exceptions or return errors
+    /// in the event that the object isn't actually a list, but the methods on
+    /// the resulting map object will.
+    pub fn get_list(&self, obj: JObject<'a>) -> Result<JList> {
+        non_null!(obj, "get_list obj argument");
+        JList::from_env(self, obj)
+    }
+
+    /// Wrap a `jobjectArray` in a `JObjectArray`, for indexing and iterating
+    /// over it.
+    pub fn get_object_array(&'a self, array: jobjectArray) -> Result<JObjectArray<'a>> {
+        JObjectArray::from_env(self, array)
+    }
+
+    /// Cast a JObject to a JMap. This won't throw exceptions or return errors
+    /// in the event that the object isn't actually a map, but the methods on
+    /// the resulting map object will.
+    pub fn get_map(&self, obj: JObject<'a>) -> Result<JMap> {
+        non_null!(obj, "get_map obj argument");
+        JMap::from_env(self, obj)
+    }
+
+    /// Iterate over anything implementing `java/lang/Iterable` (lists, sets,
+    /// custom iterables...) by calling its `iterator()` method. Anything else
+    /// gets a `ClassCastException` thrown.
+    pub fn iterable_iter(&'a self, obj: JObject<'a>) -> Result<JIterator<'a>> {
+        non_null!(obj, "iterable_iter obj argument");
+        let iterator = cached_method_id!(self, "java/lang/Iterable", "iterator", "()Ljava/util/Iterator;");
+        self.check_receiver(obj, "java/lang/Iterable")?;
+
+        let iter = unsafe {
+            self.call_method_unsafe(
+                obj,
+                iterator,
+                JavaType::Object("java/util/Iterator".into()),
+                &[],
+            )?
+        }.l()?;
+
+        JIterator::from_env(self, non_null!(iter, "iterable_iter iterator() result"))
+    }
+
+    /// Run `f` on every element of a `java/lang/Iterable` while keeping the
+    /// number of live local refs bounded, for collections too large to hold
+    /// a local ref per element.
+    ///
+    /// Elements are handled in a local frame that's popped and pushed again
+    /// every `batch` elements, which deletes the element refs along with any
+    /// local refs `f` created for them. So `f` must not keep local refs past
+    /// its batch: anything that needs to survive has to be turned into a
+    /// `GlobalRef` (or copied into rust values) before `f` returns. The
+    /// element and env are only borrowed for the call to enforce that.
+    ///
+    /// Stops at the first error, from either `f` or the iteration itself.
+    pub fn process_in_batches<F>(&'a self, iterable: JObject<'a>, batch: usize, mut f: F) -> Result<()>
+    where
+        F: for<'f> FnMut(&'f JNIEnv<'f>, JObject<'f>) -> Result<()>,
+    {
+        if batch == 0 {
+            return Err("process_in_batches batch size must be at least 1".into());
+        }
+        let capacity = ::std::cmp::min(batch, jint::MAX as usize) as jint;
+        let iter = self.iterable_iter(iterable)?;
+
+        self.push_local_frame(capacity)?;
+        let mut in_frame = 0;
+        loop {
+            let step = iter.get_next().and_then(|item| match item {
+                Some(item) => f(self, item).map(|_| true),
+                None => Ok(false),
+            });
+            match step {
+                Ok(true) => {}
+                Ok(false) => break,
+                Err(e) => {
+                    self.pop_local_frame(JObject::null())?;
+                    return Err(e);
+                }
+            }
+
+            in_frame += 1;
+            if in_frame == batch {
+                in_frame = 0;
+                self.pop_local_frame(JObject::null())?;
+                self.push_local_frame(capacity)?;
+            }
+        }
+        self.pop_local_frame(JObject::null())?;
+
+        self.delete_local_ref(iter.into())
+    }
+
+    /// Call `java.util.function.Function.apply` on `func`, e.g. a lambda
+    /// passed in from java.
+    ///
+    /// If `func` isn't a `Function`, a `ClassCastException` is thrown and a
+    /// `JavaException` error returned. The same goes for `accept`, `get` and
+    /// `test` and their interfaces.
+    pub fn apply(&'a self, func: JObject<'a>, arg: JObject<'a>) -> Result<JObject<'a>> {
+        non_null!(func, "apply func argument");
+        let apply = cached_method_id!(
+            self,
+            "java/util/function/Function",
+            "apply",
+            "(Ljava/lang/Object;)Ljava/lang/Object;"
+        );
+        self.check_receiver(func, "java/util/function/Function")?;
+
+        unsafe {
+            self.call_method_unsafe(
+                func,
+                apply,
+                JavaType::Object("java/lang/Object".into()),
+                &[arg.into()],
+            )?
+        }.l()
+    }
+
+    /// Call `java.util.function.Consumer.accept` on `consumer`.
+    pub fn accept(&'a self, consumer: JObject<'a>, arg: JObject<'a>) -> Result<()> {
+        non_null!(consumer, "accept consumer argument");
+        let accept = cached_method_id!(
+            self,
+            "java/util/function/Consumer",
+            "accept",
+            "(Ljava/lang/Object;)V"
+        );
+        self.check_receiver(consumer, "java/util/function/Consumer")?;
+
+        unsafe {
+            self.call_method_unsafe(
+                consumer,
+                accept,
+                JavaType::Primitive(Primitive::Void),
+                &[arg.into()],
+            )?
+        }.v()
+    }
+
+    /// Call `java.util.function.Supplier.get` on `supplier`.
+    pub fn get(&'a self, supplier: JObject<'a>) -> Result<JObject<'a>> {
+        non_null!(supplier, "get supplier argument");
+        let get = cached_method_id!(
+            self,
+            "java/util/function/Supplier",
+            "get",
+            "()Ljava/lang/Object;"
+        );
+        self.check_receiver(supplier, "java/util/function/Supplier")?;
+
+        unsafe {
+            self.call_method_unsafe(
+                supplier,
+                get,
+                JavaType::Object("java/lang/Object".into()),
+                &[],
+            )?
+        }.l()
+    }
+
+    /// Call `java.util.function.Predicate.test` on `predicate`.
+    pub fn test(&'a self, predicate: JObject<'a>, arg: JObject<'a>) -> Result<bool> {
+        non_null!(predicate, "test predicate argument");
+        let test = cached_method_id!(
+            self,
+            "java/util/function/Predicate",
+            "test",
+            "(Ljava/lang/Object;)Z"
+        );
+        self.check_receiver(predicate, "java/util/function/Predicate")?;
+
+        unsafe {
+            self.call_method_unsafe(
+                predicate,
+                test,
+                JavaType::Primitive(Primitive::Boolean),
+                &[arg.into()],
+            )?
+        }.z()
+    }
+
+    /// Check whether `haystack` holds an object equal to `needle`, according
+    /// to `needle.equals`, like `java.util.List.contains` does. Stops at the
+    /// first match. A null `needle` matches null elements only.
+    pub fn contains_object(&'a self, haystack: &[JObject], needle: JObject) -> Result<bool> {
+        if needle.is_null() {
+            return Ok(haystack.iter().any(|obj| obj.is_null()));
+        }
+
+        let equals = cached_method_id!(self, "java/lang/Object", "equals", "(Ljava/lang/Object;)Z");
+        for obj in haystack {
+            let eq = unsafe {
+                self.call_method_unsafe(
+                    needle,
+                    equals,
+                    JavaType::Primitive(Primitive::Boolean),
+                    &[(*obj).into()],
+                )?
+            }.z()?;
+            if eq {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Convert a java enum constant to a rust value by its `name()`, looked up
+    /// in `mapping`. Returns `None` for names the mapping doesn't have, e.g.
+    /// constants added to the java enum later.
+    ///
+    /// # Example
+    /// ```rust,ignore
+    /// let status = env.java_enum_to_rust(obj, &[("ACTIVE", Status::Active), ("DONE", Status::Done)])?;
+    /// ```
+    pub fn java_enum_to_rust<T: Clone>(&'a self, obj: JObject<'a>, mapping: &[(&str, T)]) -> Result<Option<T>> {
+        self.check_enum(obj, "java_enum_to_rust")?;
+        let name = cached_method_id!(self, "java/lang/Enum", "name", "()Ljava/lang/String;");
+
+        let name = unsafe {
+            self.call_method_unsafe(obj, name, JavaType::Object("java/lang/String".into()), &[])?
+        }.l()?;
+        let name = self.auto_local(name);
+        let name: String = self.get_string(name.as_obj().into())?.into();
+
+        Ok(mapping.iter().find(|m| m.0 == name).map(|m| m.1.clone()))
+    }
+
+    /// Same as `java_enum_to_rust`, but by `ordinal()`: the constant with
+    /// ordinal `i` maps to `mapping[i]`. This doesn't need a string per
+    /// conversion, but breaks if the java constants are reordered.
+    pub fn java_enum_to_rust_by_ordinal<T: Clone>(&'a self, obj: JObject<'a>, mapping: &[T]) -> Result<Option<T>> {
+        self.check_enum(obj, "java_enum_to_rust_by_ordinal")?;
+        let ordinal = cached_method_id!(self, "java/lang/Enum", "ordinal", "()I");
+
+        let ordinal = unsafe {
+            self.call_method_unsafe(obj, ordinal, JavaType::Primitive(Primitive::Int), &[])?
+        }.i()?;
+
+        Ok(mapping.get(ordinal as usize).cloned())
+    }
+
+    fn check_enum(&'a self, obj: JObject<'a>, ctx: &'static str) -> Result<()> {
+        non_null!(obj, ctx);
+        if !self.is_instance_of(obj, JDK_CLASSES.class("java/lang/Enum"))? {
+            return Err(format!("{}: object isn't a java enum constant", ctx).into());
+        }
+        Ok(())
+    }
+
+    /// Compare two objects by their natural ordering, with
+    /// `java.lang.Comparable.compareTo`. Returns a negative number, zero or a
+    /// positive number if `a` is less than, equal to or greater than `b`.
+    ///
+    /// If the objects can't be compared with each other, the
+    /// `ClassCastException` thrown by `compareTo` is left pending and a
+    /// `JavaException` error is returned.
+    pub fn compare_to(&'a self, a: JObject<'a>, b: JObject<'a>) -> Result<i32> {
+        non_null!(a, "compare_to a argument");
+        let compare_to = cached_method_id!(
+            self,
+            "java/lang/Comparable",
+            "compareTo",
+            "(Ljava/lang/Object;)I"
+        );
+
+        self.check_receiver(a, "java/lang/Comparable")?;
+
+        unsafe {
+            self.call_method_unsafe(
+                a,
+                compare_to,
+                JavaType::Primitive(Primitive::Int),
+                &[b.into()],
+            )?
+        }.i()
+    }
+
+    /// Same as `compare_to`, but returns an `Ordering`, e.g. for
+    /// sorting a `Vec<JObject>` by the objects' own ordering.
+    pub fn cmp_objects(&'a self, a: JObject<'a>, b: JObject<'a>) -> Result<Ordering> {
+        Ok(self.compare_to(a, b)?.cmp(&0))
+    }
+
+    /// Convert a java success/error union object into a Rust `Result`, using
+    /// the named accessors: `is_ok` (a `()Z` method) picks whether `value` or
+    /// `error` gets called.
+    ///
+    /// The accessors are looked up on the object's class the first time, and
+    /// cached for good along with a global ref to the class.
+    pub fn unwrap_result(
+        &'a self,
+        obj: JObject<'a>,
+        is_ok: &str,
+        value: &str,
+        error: &str,
+    ) -> Result<::std::result::Result<JObject<'a>, JObject<'a>>> {
+        non_null!(obj, "unwrap_result obj argument");
+        JResultAccessors::unwrap_cached(self, obj, is_ok, value, error)
+    }
+
+    /// Build a `java.util.ArrayList` holding the given objects, in order. If
+    /// the iterator knows its exact length, the list is created with that
+    /// capacity.
+    ///
+    /// The elements are only added to the list, so their local refs are left
+    /// alone.
+    pub fn new_array_list_from<I>(&'a self, iter: I) -> Result<JObject<'a>>
+    where
+        I: IntoIterator<Item = JObject<'a>>,
+    {
+        let ctor = cached_method_id!(self, "java/util/ArrayList", "<init>", "(I)V");
+        let add = cached_method_id!(self, "java/util/ArrayList", "add", "(Ljava/lang/Object;)Z");
+
+        let iter = iter.into_iter();
+        let capacity = match iter.size_hint() {
+            (lower, Some(upper)) if lower == upper => lower,
+            _ => 10,
+        };
+
+        let class = self.auto_local(self.find_class("java/util/ArrayList")?.into());
+        let list = self.new_object_by_id(&class, ctor, &[(capacity as jint).into()])?;
+        for obj in iter {
+            unsafe {
+                self.call_method_unsafe(
+                    list,
+                    add,
+                    JavaType::Primitive(Primitive::Boolean),
+                    &[obj.into()],
+                )?;
+            }
+        }
+        Ok(list)
+    }
+
+    /// Build a `java.util.HashMap` from key/value pairs. Either may be null.
+    /// Later pairs replace earlier ones with an equal key, as with `put`.
+    pub fn new_hash_map_from<I>(&'a self, iter: I) -> Result<JObject<'a>>
+    where
+        I: IntoIterator<Item = (JObject<'a>, JObject<'a>)>,
+    {
+        let map = self.new_hash_map()?;
+        for (key, value) in iter {
+            self.hash_map_put(map, key, value)?;
+        }
+        Ok(map)
+    }
+
+    /// Same as `new_hash_map_from`, with rust strings as keys.
+    pub fn new_hash_map_from_str<'k, I>(&'a self, iter: I) -> Result<JObject<'a>>
+    where
+        I: IntoIterator<Item = (&'k str, JObject<'a>)>,
+    {
+        let map = self.new_hash_map()?;
+        for (key, value) in iter {
+            let key = self.auto_local(self.new_string(key)?.into());
+            self.hash_map_put(map, key.as_obj(), value)?;
+        }
+        Ok(map)
+    }
+
+    fn new_hash_map(&'a self) -> Result<JObject<'a>> {
+        let ctor = cached_method_id!(self, "java/util/HashMap", "<init>", "()V");
+        let class = self.auto_local(self.find_class("java/util/HashMap")?.into());
+        self.new_object_by_id(&class, ctor, &[])
+    }
+
+    fn hash_map_put(&'a self, map: JObject, key: JObject, value: JObject) -> Result<()> {
+        let put = cached_method_id!(
+            self,
+            "java/util/HashMap",
+            "put",
+            "(Ljava/lang/Object;Ljava/lang/Object;)Ljava/lang/Object;"
+        );
+        let prev = unsafe {
+            self.call_method_unsafe(
+                map,
+                put,
+                JavaType::Object("java/lang/Object".into()),
+                &[key.into(), value.into()],
+            )?
+        }.l()?;
+        if !prev.is_null() {
+            self.delete_local_ref(prev)?;
+        }
+        Ok(())
+    }
+
+    /// Snapshot a string-keyed `java.util.Map` into a `HashMap`, decoding each
+    /// key to a Rust `String`.
+    ///
+    /// The values are kept as local refs, so the result can't outlive the
+    /// current frame; promote them with `new_global_ref` if they need to.
+    /// Null values are kept as null `JObject`s. A null key is reported as a
+    /// `NullPtr` error since it has no `String` representation.
+    pub fn map_to_hashmap(&'a self, map: JObject<'a>) -> Result<HashMap<String, JObject<'a>>> {
+        let map = self.get_map(map)?;
+
+        let mut result = HashMap::new();
+        for (key, value) in map.iter()? {
+            if key.is_null() {
+                return Err(ErrorKind::NullPtr("map_to_hashmap key").into());
+            }
+            let key = self.auto_local(key);
+            let key_str: String = self.get_string(key.as_obj().into())?.into();
+            result.insert(key_str, value);
+        }
+        Ok(result)
+    }
+
+    /// Get the name of the java thread this env belongs to
+    /// (`Thread.currentThread().getName()`), e.g. to tell apart callbacks
+    /// coming from different threads in logs. A thread without a name gives
+    /// an empty string.
+    pub fn current_thread_name(&'a self) -> Result<String> {
+        let get_name = cached_method_id!(self, "java/lang/Thread", "getName", "()Ljava/lang/String;");
+
+        let thread = self.auto_local(self.current_thread()?);
+
+        let name = unsafe {
+            self.call_method_unsafe(
+                thread.as_obj(),
+                get_name,
+                JavaType::Object("java/lang/String".into()),
+                &[],
+            )?
+        }.l()?;
+        if name.is_null() {
+            return Ok(String::new());
+        }
+
+        let name = self.auto_local(name);
+        let name_str: String = self.get_string(name.as_obj().into())?.into();
+        Ok(name_str)
+    }
+
+    fn current_thread(&'a self) -> Result<JObject<'a>> {
+        let current_thread = cached_static_method_id!(
+            self,
+            "java/lang/Thread",
+            "currentThread",
+            "()Ljava/lang/Thread;"
+        );
+        let thread = unsafe {
+            self.call_static_method_unsafe(
+                JDK_CLASSES.find_class(self, "java/lang/Thread")?,
+                current_thread,
+                JavaType::Object("java/lang/Thread".into()),
+                &[],
+            )?
+        }.l()?;
+        Ok(non_null!(thread, "current thread"))
+    }
+
+    /// Read a java system property (`System.getProperty`), e.g.
+    /// `java.io.tmpdir`. Returns `None` if the property isn't set.
+    pub fn get_system_property(&'a self, key: &str) -> Result<Option<String>> {
+        let get_property = cached_static_method_id!(
+            self,
+            "java/lang/System",
+            "getProperty",
+            "(Ljava/lang/String;)Ljava/lang/String;"
+        );
+        self.call_system_string_getter(get_property, key)
+    }
+
+    /// Read an environment variable as the JVM sees it (`System.getenv`).
+    /// Returns `None` if the variable isn't set.
+    pub fn get_env_var(&'a self, key: &str) -> Result<Option<String>> {
+        let getenv = cached_static_method_id!(
+            self,
+            "java/lang/System",
+            "getenv",
+            "(Ljava/lang/String;)Ljava/lang/String;"
+        );
+        self.call_system_string_getter(getenv, key)
+    }
+
+    fn call_system_string_getter(
+        &'a self,
+        method_id: JStaticMethodID<'a>,
+        key: &str,
+    ) -> Result<Option<String>> {
+        // kept for the process, like the method ids passed in
+        static SYSTEM: ClassCache = ClassCache::new();
+        let system = SYSTEM.find_class(self, "java/lang/System")?;
+
+        let key = self.auto_local(self.new_string(key)?.into());
+        let value = unsafe {
+            self.call_static_method_unsafe(
+                system,
+                method_id,
+                JavaType::Object("java/lang/String".into()),
+                &[key.as_obj().into()],
+            )?
+        }.l()?;
+        if value.is_null() {
+            return Ok(None);
+        }
+
+        let value = self.auto_local(value);
+        let value_str: String = self.get_string(value.as_obj().into())?.into();
+        Ok(Some(value_str))
+    }
+
+    /// Read a `java.util.Properties` object into a `HashMap`, including the
+    /// values it falls back to from its defaults. Only properties whose key and
+    /// value are both strings are included, as with `stringPropertyNames()`,
+    /// and ones whose value turns out to be null are skipped.
+    pub fn properties_to_hashmap(&'a self, props: JObject<'a>) -> Result<HashMap<String, String>> {
+        non_null!(props, "properties_to_hashmap props argument");
+        let names = cached_method_id!(
+            self,
+            "java/util/Properties",
+            "stringPropertyNames",
+            "()Ljava/util/Set;"
+        );
+        let get_property = cached_method_id!(
+            self,
+            "java/util/Properties",
+            "getProperty",
+            "(Ljava/lang/String;)Ljava/lang/String;"
+        );
+        self.check_receiver(props, "java/util/Properties")?;
+
+        let names = unsafe {
+            self.call_method_unsafe(props, names, JavaType::Object("java/util/Set".into()), &[])?
+        }.l()?;
+        let names = self.auto_local(names);
+
+        let mut result = HashMap::new();
+        for name in self.iterable_iter(names.as_obj())? {
+            let name = self.auto_local(name);
+            let value = unsafe {
+                self.call_method_unsafe(
+                    props,
+                    get_property,
+                    JavaType::Object("java/lang/String".into()),
+                    &[name.as_obj().into()],
+                )?
+            }.l()?;
+            if value.is_null() {
+                continue;
+            }
+            let value = self.auto_local(value);
+
+            let name_str: String = self.get_string(name.as_obj().into())?.into();
+            let value_str: String = self.get_string(value.as_obj().into())?.into();
+            result.insert(name_str, value_str);
+        }
+        // iteration stops without an error if the iterator throws
+        check_exception!(self.internal);
+
+        Ok(result)
+    }
+
+    /// Copy the elements of any `java.util.Collection` into a `Vec` with a
+    /// single `toArray()` call.
+    ///
+    /// Each element of the result is a new local ref; room for all of them is
+    /// reserved with `ensure_local_capacity`, and the intermediate array is
+    /// deleted before returning. Null elements are kept as null `JObject`s.
+    pub fn collection_to_vec(&'a self, coll: JObject) -> Result<Vec<JObject<'a>>> {
+        non_null!(coll, "collection_to_vec coll argument");
+        let array = self.call_method(coll, "toArray", "()[Ljava/lang/Object;", &[])?
+            .l()?;
+        self.object_array_to_vec(array)
     }
 
-    /// Cast a JObject to a JMap. This won't throw exceptions or return errors
-    /// in the event that the object isn't actually a map, but the methods on
-    /// the resulting map object will.
-    pub fn get_map(&self, obj: JObject<'a>) -> Result<JMap> {
-        non_null!(obj, "get_map obj argument");
-        JMap::from_env(self, obj)
+    /// Same as `collection_to_vec`, but goes through `toArray(T[])` so that the
+    /// intermediate array has `element_class` as its component type. This fails
+    /// with an `ArrayStoreException` if an element isn't an instance of
+    /// `element_class`.
+    pub fn collection_to_vec_typed<T>(
+        &'a self,
+        coll: JObject,
+        element_class: T,
+    ) -> Result<Vec<JObject<'a>>>
+    where
+        T: Desc<'a, JClass<'a>>,
+    {
+        non_null!(coll, "collection_to_vec_typed coll argument");
+        let template = self.new_object_array(0, element_class, JObject::null())?;
+        let template = self.auto_local(JObject::from(template));
+        let array = self.call_method(
+            coll,
+            "toArray",
+            "([Ljava/lang/Object;)[Ljava/lang/Object;",
+            &[template.as_obj().into()],
+        )?.l()?;
+        self.object_array_to_vec(array)
+    }
+
+    /// Reads every element of an object array into a `Vec`, then deletes the
+    /// local ref to the array itself.
+    fn object_array_to_vec(&'a self, array: JObject<'a>) -> Result<Vec<JObject<'a>>> {
+        let array = self.auto_local(array);
+        let raw = array.as_obj().into_inner() as jobjectArray;
+
+        let len = self.get_array_length(raw)?;
+        self.ensure_local_capacity(len)?;
+
+        let mut vec = Vec::with_capacity(len as usize);
+        for i in 0..len {
+            // Not `get_object_array_element`, since null elements are fine here.
+            let elem: JObject =
+                jni_non_null_call!(self.internal, GetObjectArrayElement, raw, i).into();
+            vec.push(elem);
+        }
+        Ok(vec)
     }
 
     /// Get a JavaStr from a JString. This allows conversions from java string
@@ -953,6 +2961,135 @@ impl<'a> JNIEnv<'a> {
         JavaStr::from_env(self, obj)
     }
 
+    /// Get the contents of a JString as an id in a caller-owned
+    /// `StringInterner`. Values that have been seen before don't allocate a
+    /// new `String`; the stored one is reused.
+    pub fn get_string_interned(
+        &self,
+        obj: JString<'a>,
+        interner: &mut StringInterner,
+    ) -> Result<InternedId> {
+        let java_str = self.get_string(obj)?;
+        let s: Cow<str> = (&java_str).into();
+        Ok(interner.intern(&s))
+    }
+
+    /// Get the UTF-16 contents of a JString through `GetStringChars`.
+    ///
+    /// This is the non-critical counterpart of `get_string_critical`: the VM
+    /// may copy the string, but the garbage collector keeps running and other
+    /// JNI calls can be made while the returned guard is alive.
+    pub fn get_string_chars(&self, obj: JString<'a>) -> Result<JavaStrChars> {
+        // the non-critical path doesn't restrict other calls
+        unsafe { self.get_string_chars_with(obj, StringAccess::NonCritical) }
+    }
+
+    /// Get the UTF-16 contents of a JString through `GetStringCritical`.
+    ///
+    /// Depending on the GC in use, the VM may pin the string or disable
+    /// garbage collection entirely until the returned guard is dropped, which
+    /// can stall every other thread that allocates. If that's a concern for
+    /// your VM configuration, use `get_string_chars` or pick the path at
+    /// runtime with `get_string_chars_with`.
+    ///
+    /// No other JNI calls may be made while the guard is alive, so this
+    /// borrows the env mutably until it's dropped. See `JavaStrCritical`.
+    pub fn get_string_critical<'b>(&'b mut self, obj: JString<'a>) -> Result<JavaStrCritical<'b>> {
+        non_null!(obj, "get_string_critical obj argument");
+        JavaStrCritical::new(self, obj)
+    }
+
+    /// Get the UTF-16 contents of a JString, using the given access path. This
+    /// is the decision point between `get_string_critical` and
+    /// `get_string_chars` for callers that want to choose at runtime, e.g.
+    /// from configuration.
+    ///
+    /// This is unsafe because, unlike `get_string_critical`, the env isn't
+    /// borrowed mutably: with `StringAccess::Critical` it's up to the caller
+    /// not to make other JNI calls while the guard is alive.
+    pub unsafe fn get_string_chars_with(
+        &self,
+        obj: JString<'a>,
+        access: StringAccess,
+    ) -> Result<JavaStrChars> {
+        non_null!(obj, "get_string_chars_with obj argument");
+        JavaStrChars::from_env(self, obj, access)
+    }
+
+    /// Copy `buf.len()` UTF-16 code units of a JString, starting at `start`,
+    /// into `buf` with `GetStringRegion`. Unlike `get_string_chars`, this
+    /// never pins or copies the whole string.
+    ///
+    /// Returns a `StringRegionOutOfBounds` error if the region doesn't fit in
+    /// the string.
+    pub fn get_string_region(&self, obj: JString<'a>, start: jsize, buf: &mut [jchar]) -> Result<()> {
+        non_null!(obj, "get_string_region obj argument");
+        let len = self.check_string_region(obj, start, buf.len())?;
+        jni_void_call!(
+            self.internal,
+            GetStringRegion,
+            obj.into_inner(),
+            start,
+            len,
+            buf.as_mut_ptr()
+        );
+        Ok(())
+    }
+
+    /// Copy `len` UTF-16 code units of a JString, starting at `start`, into
+    /// `buf` as java's modified UTF-8 with `GetStringUTFRegion`. Returns the
+    /// number of bytes written.
+    ///
+    /// Since the encoded size isn't known up front, `buf` must have room for
+    /// the worst case of 3 bytes per code unit, plus one for the NUL
+    /// terminator some VMs write. Returns a `StringRegionOutOfBounds` error if
+    /// the region doesn't fit in the string.
+    pub fn get_string_utf_region(
+        &self,
+        obj: JString<'a>,
+        start: jsize,
+        len: usize,
+        buf: &mut [u8],
+    ) -> Result<usize> {
+        non_null!(obj, "get_string_utf_region obj argument");
+        let jlen = self.check_string_region(obj, start, len)?;
+        let needed = len * 3 + 1;
+        if buf.len() < needed {
+            return Err(format!(
+                "buffer of {} bytes is too small for {} characters, {} bytes are needed",
+                buf.len(),
+                len,
+                needed
+            ).into());
+        }
+
+        // modified UTF-8 never contains a NUL byte, so the first one marks
+        // the end of what was written
+        let buf = &mut buf[..needed];
+        for b in buf.iter_mut() {
+            *b = 0;
+        }
+        jni_void_call!(
+            self.internal,
+            GetStringUTFRegion,
+            obj.into_inner(),
+            start,
+            jlen,
+            buf.as_mut_ptr() as *mut c_char
+        );
+        Ok(buf.iter().position(|&b| b == 0).unwrap_or(needed))
+    }
+
+    /// Checks that `len` characters starting at `start` fit in `obj`, the
+    /// string counterpart of `check_array_region`.
+    fn check_string_region(&self, obj: JString<'a>, start: jsize, len: usize) -> Result<jsize> {
+        let string_len: jsize = unsafe { jni_unchecked!(self.internal, GetStringLength, obj.into_inner()) };
+        if start < 0 || start > string_len || len > (string_len - start) as usize {
+            return Err(ErrorKind::StringRegionOutOfBounds(start, len, string_len).into());
+        }
+        Ok(len as jsize)
+    }
+
     /// Get a pointer to the character array beneath a JString. This is in
     /// Java's modified UTF-8 and will leak memory if `release_string_utf_chars`
     /// is never called.
@@ -985,7 +3122,34 @@ impl<'a> JNIEnv<'a> {
         Ok(jni_call!(self.internal, NewStringUTF, ffi_str.as_ptr()))
     }
 
-    /// Get the length of a java array
+    /// Create a new java string object from a string that's already in java's
+    /// modified UTF-8, skipping the re-encoding `new_string` does. Useful for
+    /// string constants kept around as `JNIString`s.
+    ///
+    /// Like a `CStr`, a `JNIStr` never contains interior NUL bytes, so its
+    /// bytes are passed to `NewStringUTF` as-is.
+    pub fn new_string_from_jni_str(&self, from: &JNIStr) -> Result<JString<'a>> {
+        Ok(jni_call!(self.internal, NewStringUTF, from.as_ptr()))
+    }
+
+    /// Create a new java string object from UTF-16 code units with
+    /// `NewString`. Java strings are UTF-16 already, so unlike `new_string`
+    /// this needs no re-encoding, which makes it the faster path for large
+    /// strings; `get_string_chars` is the matching way back.
+    ///
+    /// Unpaired surrogates are passed on as-is, as java allows them.
+    pub fn new_string_utf16(&self, from: &[jchar]) -> Result<JString<'a>> {
+        if from.len() > jsize::MAX as usize {
+            return Err(format!("string of {} code units is too long for java", from.len()).into());
+        }
+        Ok(jni_call!(self.internal, NewString, from.as_ptr(), from.len() as jsize))
+    }
+
+    /// Get the length of a java array. This works for arrays of any type,
+    /// object or primitive, without touching the elements, so it can be used
+    /// to size a buffer before copying a region out.
+    ///
+    /// Returns a `NullPtr` error if `array` is null.
     pub fn get_array_length(&self, array: jarray) -> Result<jsize> {
         non_null!(array, "get_array_length array argument");
         let len: jsize = unsafe { jni_unchecked!(self.internal, GetArrayLength, array) };
@@ -1139,12 +3303,13 @@ impl<'a> JNIEnv<'a> {
         buf: &mut [jboolean],
     ) -> Result<()> {
         non_null!(array, "get_boolean_array_region array argument");
+        let len = self.check_array_region(array, start, buf.len())?;
         jni_void_call!(
             self.internal,
             GetBooleanArrayRegion,
             array,
             start,
-            buf.len() as jsize,
+            len,
             buf.as_mut_ptr()
         );
         Ok(())
@@ -1159,12 +3324,13 @@ impl<'a> JNIEnv<'a> {
         buf: &mut [jbyte],
     ) -> Result<()> {
         non_null!(array, "get_byte_array_region array argument");
+        let len = self.check_array_region(array, start, buf.len())?;
         jni_void_call!(
             self.internal,
             GetByteArrayRegion,
             array,
             start,
-            buf.len() as jsize,
+            len,
             buf.as_mut_ptr()
         );
         Ok(())
@@ -1179,12 +3345,13 @@ impl<'a> JNIEnv<'a> {
         buf: &mut [jchar],
     ) -> Result<()> {
         non_null!(array, "get_char_array_region array argument");
+        let len = self.check_array_region(array, start, buf.len())?;
         jni_void_call!(
             self.internal,
             GetCharArrayRegion,
             array,
             start,
-            buf.len() as jsize,
+            len,
             buf.as_mut_ptr()
         );
         Ok(())
@@ -1199,12 +3366,13 @@ impl<'a> JNIEnv<'a> {
         buf: &mut [jshort],
     ) -> Result<()> {
         non_null!(array, "get_short_array_region array argument");
+        let len = self.check_array_region(array, start, buf.len())?;
         jni_void_call!(
             self.internal,
             GetShortArrayRegion,
             array,
             start,
-            buf.len() as jsize,
+            len,
             buf.as_mut_ptr()
         );
         Ok(())
@@ -1219,12 +3387,13 @@ impl<'a> JNIEnv<'a> {
         buf: &mut [jint],
     ) -> Result<()> {
         non_null!(array, "get_int_array_region array argument");
+        let len = self.check_array_region(array, start, buf.len())?;
         jni_void_call!(
             self.internal,
             GetIntArrayRegion,
             array,
             start,
-            buf.len() as jsize,
+            len,
             buf.as_mut_ptr()
         );
         Ok(())
@@ -1239,12 +3408,13 @@ impl<'a> JNIEnv<'a> {
         buf: &mut [jlong],
     ) -> Result<()> {
         non_null!(array, "get_long_array_region array argument");
+        let len = self.check_array_region(array, start, buf.len())?;
         jni_void_call!(
             self.internal,
             GetLongArrayRegion,
             array,
             start,
-            buf.len() as jsize,
+            len,
             buf.as_mut_ptr()
         );
         Ok(())
@@ -1259,12 +3429,13 @@ impl<'a> JNIEnv<'a> {
         buf: &mut [jfloat],
     ) -> Result<()> {
         non_null!(array, "get_float_array_region array argument");
+        let len = self.check_array_region(array, start, buf.len())?;
         jni_void_call!(
             self.internal,
             GetFloatArrayRegion,
             array,
             start,
-            buf.len() as jsize,
+            len,
             buf.as_mut_ptr()
         );
         Ok(())
@@ -1279,17 +3450,172 @@ impl<'a> JNIEnv<'a> {
         buf: &mut [jdouble],
     ) -> Result<()> {
         non_null!(array, "get_double_array_region array argument");
+        let len = self.check_array_region(array, start, buf.len())?;
         jni_void_call!(
             self.internal,
             GetDoubleArrayRegion,
             array,
             start,
-            buf.len() as jsize,
+            len,
             buf.as_mut_ptr()
         );
         Ok(())
     }
 
+    /// Same as `foreach_int_array_chunk`, for boolean arrays.
+    pub fn foreach_boolean_array_chunk<F>(&self, array: jbooleanArray, chunk: usize, f: F) -> Result<()>
+    where
+        F: FnMut(&[jboolean]),
+    {
+        non_null!(array, "foreach_boolean_array_chunk array argument");
+        self.foreach_array_chunk(
+            array,
+            chunk,
+            |start, buf| self.get_boolean_array_region(array, start, buf),
+            f,
+        )
+    }
+
+    /// Same as `foreach_int_array_chunk`, for byte arrays.
+    pub fn foreach_byte_array_chunk<F>(&self, array: jbyteArray, chunk: usize, f: F) -> Result<()>
+    where
+        F: FnMut(&[jbyte]),
+    {
+        non_null!(array, "foreach_byte_array_chunk array argument");
+        self.foreach_array_chunk(
+            array,
+            chunk,
+            |start, buf| self.get_byte_array_region(array, start, buf),
+            f,
+        )
+    }
+
+    /// Same as `foreach_int_array_chunk`, for char arrays.
+    pub fn foreach_char_array_chunk<F>(&self, array: jcharArray, chunk: usize, f: F) -> Result<()>
+    where
+        F: FnMut(&[jchar]),
+    {
+        non_null!(array, "foreach_char_array_chunk array argument");
+        self.foreach_array_chunk(
+            array,
+            chunk,
+            |start, buf| self.get_char_array_region(array, start, buf),
+            f,
+        )
+    }
+
+    /// Same as `foreach_int_array_chunk`, for short arrays.
+    pub fn foreach_short_array_chunk<F>(&self, array: jshortArray, chunk: usize, f: F) -> Result<()>
+    where
+        F: FnMut(&[jshort]),
+    {
+        non_null!(array, "foreach_short_array_chunk array argument");
+        self.foreach_array_chunk(
+            array,
+            chunk,
+            |start, buf| self.get_short_array_region(array, start, buf),
+            f,
+        )
+    }
+
+    /// Pass the contents of a java int array to `f`, `chunk` elements at a
+    /// time, copying each chunk with `GetIntArrayRegion`. Only one chunk is
+    /// held in memory at any point, which keeps scanning huge arrays cheap.
+    /// The last chunk may be shorter.
+    pub fn foreach_int_array_chunk<F>(&self, array: jintArray, chunk: usize, f: F) -> Result<()>
+    where
+        F: FnMut(&[jint]),
+    {
+        non_null!(array, "foreach_int_array_chunk array argument");
+        self.foreach_array_chunk(
+            array,
+            chunk,
+            |start, buf| self.get_int_array_region(array, start, buf),
+            f,
+        )
+    }
+
+    /// Same as `foreach_int_array_chunk`, for long arrays.
+    pub fn foreach_long_array_chunk<F>(&self, array: jlongArray, chunk: usize, f: F) -> Result<()>
+    where
+        F: FnMut(&[jlong]),
+    {
+        non_null!(array, "foreach_long_array_chunk array argument");
+        self.foreach_array_chunk(
+            array,
+            chunk,
+            |start, buf| self.get_long_array_region(array, start, buf),
+            f,
+        )
+    }
+
+    /// Same as `foreach_int_array_chunk`, for float arrays.
+    pub fn foreach_float_array_chunk<F>(&self, array: jfloatArray, chunk: usize, f: F) -> Result<()>
+    where
+        F: FnMut(&[jfloat]),
+    {
+        non_null!(array, "foreach_float_array_chunk array argument");
+        self.foreach_array_chunk(
+            array,
+            chunk,
+            |start, buf| self.get_float_array_region(array, start, buf),
+            f,
+        )
+    }
+
+    /// Same as `foreach_int_array_chunk`, for double arrays.
+    pub fn foreach_double_array_chunk<F>(&self, array: jdoubleArray, chunk: usize, f: F) -> Result<()>
+    where
+        F: FnMut(&[jdouble]),
+    {
+        non_null!(array, "foreach_double_array_chunk array argument");
+        self.foreach_array_chunk(
+            array,
+            chunk,
+            |start, buf| self.get_double_array_region(array, start, buf),
+            f,
+        )
+    }
+
+    fn foreach_array_chunk<T, G, F>(
+        &self,
+        array: jarray,
+        chunk: usize,
+        mut get_region: G,
+        mut f: F,
+    ) -> Result<()>
+    where
+        T: Copy + Default,
+        G: FnMut(jsize, &mut [T]) -> Result<()>,
+        F: FnMut(&[T]),
+    {
+        if chunk == 0 {
+            return Err("chunk size must be greater than 0".into());
+        }
+
+        let len = self.get_array_length(array)? as usize;
+        let mut buf = vec![T::default(); ::std::cmp::min(chunk, len)];
+        let mut start = 0;
+        while start < len {
+            let n = ::std::cmp::min(chunk, len - start);
+            get_region(start as jsize, &mut buf[..n])?;
+            f(&buf[..n]);
+            start += n;
+        }
+        Ok(())
+    }
+
+    /// Checks that `len` elements starting at `start` fit in `array`, so that
+    /// region copies fail with an error instead of a pending
+    /// `ArrayIndexOutOfBoundsException`. Returns `len` as a `jsize`.
+    fn check_array_region(&self, array: jarray, start: jsize, len: usize) -> Result<jsize> {
+        let array_len = self.get_array_length(array)?;
+        if start < 0 || start > array_len || len > (array_len - start) as usize {
+            return Err(ErrorKind::ArrayRegionOutOfBounds(start, len, array_len).into());
+        }
+        Ok(len as jsize)
+    }
+
     /// Copy the contents of the `buf` slice to the java boolean array at the
     /// `start` index.
     pub fn set_boolean_array_region(
@@ -1299,12 +3625,13 @@ impl<'a> JNIEnv<'a> {
         buf: &[jboolean],
     ) -> Result<()> {
         non_null!(array, "set_boolean_array_region array argument");
+        let len = self.check_array_region(array, start, buf.len())?;
         jni_void_call!(
             self.internal,
             SetBooleanArrayRegion,
             array,
             start,
-            buf.len() as jsize,
+            len,
             buf.as_ptr()
         );
         Ok(())
@@ -1319,12 +3646,13 @@ impl<'a> JNIEnv<'a> {
         buf: &[jbyte],
     ) -> Result<()> {
         non_null!(array, "set_byte_array_region array argument");
+        let len = self.check_array_region(array, start, buf.len())?;
         jni_void_call!(
             self.internal,
             SetByteArrayRegion,
             array,
             start,
-            buf.len() as jsize,
+            len,
             buf.as_ptr()
         );
         Ok(())
@@ -1339,12 +3667,13 @@ impl<'a> JNIEnv<'a> {
         buf: &[jchar],
     ) -> Result<()> {
         non_null!(array, "set_char_array_region array argument");
+        let len = self.check_array_region(array, start, buf.len())?;
         jni_void_call!(
             self.internal,
             SetCharArrayRegion,
             array,
             start,
-            buf.len() as jsize,
+            len,
             buf.as_ptr()
         );
         Ok(())
@@ -1359,12 +3688,13 @@ impl<'a> JNIEnv<'a> {
         buf: &[jshort],
     ) -> Result<()> {
         non_null!(array, "set_short_array_region array argument");
+        let len = self.check_array_region(array, start, buf.len())?;
         jni_void_call!(
             self.internal,
             SetShortArrayRegion,
             array,
             start,
-            buf.len() as jsize,
+            len,
             buf.as_ptr()
         );
         Ok(())
@@ -1374,12 +3704,13 @@ impl<'a> JNIEnv<'a> {
     /// `start` index.
     pub fn set_int_array_region(&self, array: jintArray, start: jsize, buf: &[jint]) -> Result<()> {
         non_null!(array, "set_int_array_region array argument");
+        let len = self.check_array_region(array, start, buf.len())?;
         jni_void_call!(
             self.internal,
             SetIntArrayRegion,
             array,
             start,
-            buf.len() as jsize,
+            len,
             buf.as_ptr()
         );
         Ok(())
@@ -1394,12 +3725,13 @@ impl<'a> JNIEnv<'a> {
         buf: &[jlong],
     ) -> Result<()> {
         non_null!(array, "set_long_array_region array argument");
+        let len = self.check_array_region(array, start, buf.len())?;
         jni_void_call!(
             self.internal,
             SetLongArrayRegion,
             array,
             start,
-            buf.len() as jsize,
+            len,
             buf.as_ptr()
         );
         Ok(())
@@ -1414,12 +3746,13 @@ impl<'a> JNIEnv<'a> {
         buf: &[jfloat],
     ) -> Result<()> {
         non_null!(array, "set_float_array_region array argument");
+        let len = self.check_array_region(array, start, buf.len())?;
         jni_void_call!(
             self.internal,
             SetFloatArrayRegion,
             array,
             start,
-            buf.len() as jsize,
+            len,
             buf.as_ptr()
         );
         Ok(())
@@ -1434,32 +3767,142 @@ impl<'a> JNIEnv<'a> {
         buf: &[jdouble],
     ) -> Result<()> {
         non_null!(array, "set_double_array_region array argument");
+        let len = self.check_array_region(array, start, buf.len())?;
         jni_void_call!(
             self.internal,
             SetDoubleArrayRegion,
             array,
             start,
-            buf.len() as jsize,
+            len,
             buf.as_ptr()
         );
         Ok(())
     }
 
+    /// Get the elements of a java boolean array, without copying them if the
+    /// VM allows it. They are released with `mode` when the returned
+    /// `AutoArray` is dropped.
+    ///
+    /// Unlike the `_array_region` methods this doesn't copy the whole array
+    /// up front, which matters for large arrays that are only partly read or
+    /// need to be edited in place.
+    pub fn get_boolean_array_elements(
+        &'a self,
+        array: jbooleanArray,
+        mode: ReleaseMode,
+    ) -> Result<AutoArray<'a, jboolean>> {
+        non_null!(array, "get_boolean_array_elements array argument");
+        AutoArray::new(self, array, mode)
+    }
+
+    /// Get the elements of a java byte array. See
+    /// `get_boolean_array_elements`.
+    pub fn get_byte_array_elements(
+        &'a self,
+        array: jbyteArray,
+        mode: ReleaseMode,
+    ) -> Result<AutoArray<'a, jbyte>> {
+        non_null!(array, "get_byte_array_elements array argument");
+        AutoArray::new(self, array, mode)
+    }
+
+    /// Get the elements of a java char array. See
+    /// `get_boolean_array_elements`.
+    pub fn get_char_array_elements(
+        &'a self,
+        array: jcharArray,
+        mode: ReleaseMode,
+    ) -> Result<AutoArray<'a, jchar>> {
+        non_null!(array, "get_char_array_elements array argument");
+        AutoArray::new(self, array, mode)
+    }
+
+    /// Get the elements of a java short array. See
+    /// `get_boolean_array_elements`.
+    pub fn get_short_array_elements(
+        &'a self,
+        array: jshortArray,
+        mode: ReleaseMode,
+    ) -> Result<AutoArray<'a, jshort>> {
+        non_null!(array, "get_short_array_elements array argument");
+        AutoArray::new(self, array, mode)
+    }
+
+    /// Get the elements of a java int array. See
+    /// `get_boolean_array_elements`.
+    pub fn get_int_array_elements(
+        &'a self,
+        array: jintArray,
+        mode: ReleaseMode,
+    ) -> Result<AutoArray<'a, jint>> {
+        non_null!(array, "get_int_array_elements array argument");
+        AutoArray::new(self, array, mode)
+    }
+
+    /// Get the elements of a java long array. See
+    /// `get_boolean_array_elements`.
+    pub fn get_long_array_elements(
+        &'a self,
+        array: jlongArray,
+        mode: ReleaseMode,
+    ) -> Result<AutoArray<'a, jlong>> {
+        non_null!(array, "get_long_array_elements array argument");
+        AutoArray::new(self, array, mode)
+    }
+
+    /// Get the elements of a java float array. See
+    /// `get_boolean_array_elements`.
+    pub fn get_float_array_elements(
+        &'a self,
+        array: jfloatArray,
+        mode: ReleaseMode,
+    ) -> Result<AutoArray<'a, jfloat>> {
+        non_null!(array, "get_float_array_elements array argument");
+        AutoArray::new(self, array, mode)
+    }
+
+    /// Get the elements of a java double array. See
+    /// `get_boolean_array_elements`.
+    pub fn get_double_array_elements(
+        &'a self,
+        array: jdoubleArray,
+        mode: ReleaseMode,
+    ) -> Result<AutoArray<'a, jdouble>> {
+        non_null!(array, "get_double_array_elements array argument");
+        AutoArray::new(self, array, mode)
+    }
+
+    /// Get the elements of a java primitive array with
+    /// `GetPrimitiveArrayCritical`, which is the most likely to avoid a copy.
+    /// `T` must match the array's element type, e.g. `jint` for an `int[]`.
+    ///
+    /// This borrows the env mutably until the returned guard is dropped,
+    /// since no other JNI calls may be made inside a critical section. See
+    /// `AutoPrimitiveArrayCritical`.
+    pub fn get_primitive_array_critical<'b, T: TypeArray>(
+        &'b mut self,
+        array: jarray,
+        mode: ReleaseMode,
+    ) -> Result<AutoPrimitiveArrayCritical<'b, T>> {
+        AutoPrimitiveArrayCritical::new(self, array, mode)
+    }
+
     /// Get a field without checking the provided type against the actual field.
     #[allow(unused_unsafe)]
     pub unsafe fn get_field_unsafe<T>(&self, obj: JObject, field: T, ty: JavaType) -> Result<JValue>
     where
         T: Desc<'a, JFieldID<'a>>,
     {
-        non_null!(obj, "get_field_typed obj argument");
+        non_null!(obj, "get_field_unsafe obj argument");
 
         let field = field.lookup(self)?.into_inner();
         let obj = obj.into_inner();
 
         // TODO clean this up
         Ok(match ty {
+            // null is a valid field value, so this doesn't use `jni_call!`
             JavaType::Object(_) | JavaType::Array(_) => {
-                let obj: JObject = jni_call!(self.internal, GetObjectField, obj, field);
+                let obj: JObject = jni_non_null_call!(self.internal, GetObjectField, obj, field).into();
                 obj.into()
             }
             // JavaType::Object
@@ -1504,7 +3947,7 @@ impl<'a> JNIEnv<'a> {
     where
         T: Desc<'a, JFieldID<'a>>,
     {
-        non_null!(obj, "set_field_typed obj argument");
+        non_null!(obj, "set_field_unsafe obj argument");
 
         let field = field.lookup(self)?.into_inner();
         let obj = obj.into_inner();
@@ -1548,19 +3991,27 @@ impl<'a> JNIEnv<'a> {
     }
 
     /// Get a field. Requires an object class lookup and a field id lookup
-    /// internally.
+    /// internally. A null object field is returned as a null `JObject`.
+    ///
+    /// To skip the lookups when reading the same field repeatedly, pass a
+    /// `JFieldID` (or a `descriptors::Cached` descriptor) to
+    /// `get_field_unsafe` instead.
     pub fn get_field<S, T>(&self, obj: JObject, name: S, ty: T) -> Result<JValue>
     where
         S: Into<JNIString>,
         T: Into<JNIString> + AsRef<str>,
     {
+        non_null!(obj, "get_field obj argument");
         let class = self.auto_local(self.get_object_class(obj)?.into());
 
         let parsed = JavaType::from_str(ty.as_ref())?;
 
-        let field_id: JFieldID = (&class, name, ty).lookup(self)?;
+        let name = name.into();
+        let field_id: JFieldID = (&class, name.borrowed().to_owned(), ty).lookup(self)?;
 
-        unsafe { self.get_field_unsafe(obj, field_id, parsed) }
+        call_trace::traced(self, "get_field", class.as_obj(), &name, &parsed, || unsafe {
+            self.get_field_unsafe(obj, field_id, parsed.clone())
+        })
     }
 
     /// Set a field. Does the same lookups as `get_field` and ensures that the
@@ -1570,42 +4021,16 @@ impl<'a> JNIEnv<'a> {
         S: Into<JNIString>,
         T: Into<JNIString> + AsRef<str>,
     {
+        non_null!(obj, "set_field obj argument");
         let parsed = JavaType::from_str(ty.as_ref())?;
-        let in_type = val.primitive_type();
-
-        match parsed {
-            JavaType::Object(_) | JavaType::Array(_) => {
-                if let None = in_type {
-                    // we're good here
-                } else {
-                    return Err(
-                        ErrorKind::WrongJValueType(val.type_name(), "see java field").into(),
-                    );
-                }
-            }
-            JavaType::Primitive(p) => {
-                if let Some(in_p) = in_type {
-                    if in_p == p {
-                        // good
-                    } else {
-                        return Err(
-                            ErrorKind::WrongJValueType(val.type_name(), "see java field").into(),
-                        );
-                    }
-                } else {
-                    return Err(
-                        ErrorKind::WrongJValueType(val.type_name(), "see java field").into(),
-                    );
-                }
-            }
-            JavaType::Method(_) => unimplemented!(),
-        }
+        check_field_value(&parsed, &val)?;
 
         let class = self.auto_local(self.get_object_class(obj)?.into());
 
-        let res = unsafe { self.set_field_unsafe(obj, (&class, name, ty), val) };
-
-        res
+        let name = name.into();
+        call_trace::traced(self, "set_field", class.as_obj(), &name, &parsed, || unsafe {
+            self.set_field_unsafe(obj, (&class, name.borrowed().to_owned(), ty), val)
+        })
     }
 
     /// Get a static field without checking the provided type against the actual
@@ -1622,13 +4047,15 @@ impl<'a> JNIEnv<'a> {
         U: Desc<'a, JStaticFieldID<'a>>,
     {
         let class = class.lookup(self)?.into_inner();
+        non_null!(class, "get_static_field_unsafe class argument");
 
         let field_id = field.lookup(self)?.into_inner();
 
         // TODO clean this up
         Ok(match ty {
             JavaType::Object(_) | JavaType::Array(_) => {
-                let obj: JObject = jni_call!(self.internal, GetStaticObjectField, class, field_id);
+                let obj: JObject =
+                    jni_non_null_call!(self.internal, GetStaticObjectField, class, field_id).into();
                 obj.into()
             }
             // JavaType::Object
@@ -1686,7 +4113,77 @@ impl<'a> JNIEnv<'a> {
         // and we'll need that for the next call.
         let class = class.lookup(self)?;
 
-        unsafe { self.get_static_field_unsafe(class, (class, field, sig), ty) }
+        let field = field.into();
+        call_trace::traced(self, "get_static_field", class.into(), &field, &ty, || unsafe {
+            self.get_static_field_unsafe(class, (class, field.borrowed().to_owned(), sig), ty.clone())
+        })
+    }
+
+    /// Set a static field without any type checking.
+    pub unsafe fn set_static_field_unsafe<T, U>(&self, class: T, field: U, val: JValue) -> Result<()>
+    where
+        T: Desc<'a, JClass<'a>>,
+        U: Desc<'a, JStaticFieldID<'a>>,
+    {
+        let class = class.lookup(self)?.into_inner();
+        non_null!(class, "set_static_field_unsafe class argument");
+
+        let field_id = field.lookup(self)?.into_inner();
+
+        // TODO clean this up
+        match val {
+            JValue::Object(o) => {
+                jni_unchecked!(self.internal, SetStaticObjectField, class, field_id, o.into_inner());
+            }
+            JValue::Bool(b) => {
+                jni_unchecked!(self.internal, SetStaticBooleanField, class, field_id, b);
+            }
+            JValue::Char(c) => {
+                jni_unchecked!(self.internal, SetStaticCharField, class, field_id, c);
+            }
+            JValue::Short(s) => {
+                jni_unchecked!(self.internal, SetStaticShortField, class, field_id, s);
+            }
+            JValue::Int(i) => {
+                jni_unchecked!(self.internal, SetStaticIntField, class, field_id, i);
+            }
+            JValue::Long(l) => {
+                jni_unchecked!(self.internal, SetStaticLongField, class, field_id, l);
+            }
+            JValue::Float(f) => {
+                jni_unchecked!(self.internal, SetStaticFloatField, class, field_id, f);
+            }
+            JValue::Double(d) => {
+                jni_unchecked!(self.internal, SetStaticDoubleField, class, field_id, d);
+            }
+            JValue::Byte(b) => {
+                jni_unchecked!(self.internal, SetStaticByteField, class, field_id, b);
+            }
+            JValue::Void => {
+                return Err(ErrorKind::WrongJValueType("void", "see java field").into());
+            }
+        };
+
+        Ok(())
+    }
+
+    /// Set a static field. Does the same lookups as `get_static_field` and
+    /// ensures that the type matches the given value.
+    pub fn set_static_field<T, U, V>(&self, class: T, field: U, sig: V, val: JValue) -> Result<()>
+    where
+        T: Desc<'a, JClass<'a>>,
+        U: Into<JNIString>,
+        V: Into<JNIString> + AsRef<str>,
+    {
+        let ty = JavaType::from_str(sig.as_ref())?;
+        check_field_value(&ty, &val)?;
+
+        let class = class.lookup(self)?;
+
+        let field = field.into();
+        call_trace::traced(self, "set_static_field", class.into(), &field, &ty, || unsafe {
+            self.set_static_field_unsafe(class, (class, field.borrowed().to_owned(), sig), val)
+        })
     }
 
     /// Surrenders ownership of a rust object to Java. Requires an object with a
@@ -1785,10 +4282,13 @@ impl<'a> JNIEnv<'a> {
 
     /// Lock a Java object. The MonitorGuard that this returns is responsible
     /// for ensuring that it gets unlocked.
+    ///
+    /// This takes the same monitor as a java `synchronized (obj)` block, and
+    /// like one, it's reentrant and blocks until the monitor is free.
     pub fn lock_obj(&self, obj: JObject) -> Result<MonitorGuard<'a>> {
-        unsafe {
-            let _ = jni_unchecked!(self.internal, MonitorEnter, obj.into_inner());
-        }
+        non_null!(obj, "lock_obj obj argument");
+        let res = jni_non_null_call!(self.internal, MonitorEnter, obj.into_inner());
+        jni_error_code_to_result(res)?;
 
         Ok(MonitorGuard {
             obj: obj.into_inner(),
@@ -1840,3 +4340,48 @@ impl<'a> Drop for MonitorGuard<'a> {
         }
     }
 }
+
+/// JDK classes the wrapper calls static methods on or checks objects against
+/// often, kept for the process like the method ids looked up on them.
+static JDK_CLASSES: ClassCache = ClassCache::new();
+
+/// The class loader `find_class` falls back to, see `set_app_class_loader`.
+static APP_CLASS_LOADER: RwLock<Option<GlobalRef>> = RwLock::new(None);
+
+fn app_class_loader() -> Option<GlobalRef> {
+    APP_CLASS_LOADER.read().unwrap().clone()
+}
+
+/// Check that a value can be stored in a field of type `ty`, for `set_field`
+/// and `set_static_field`.
+fn check_field_value(ty: &JavaType, val: &JValue) -> Result<()> {
+    if value_matches(ty, val) {
+        Ok(())
+    } else {
+        Err(ErrorKind::WrongJValueType(val.type_name(), "see java field").into())
+    }
+}
+
+/// Check that the arguments passed to a method match the ones in its parsed
+/// signature, so that an int is never passed where the method expects an
+/// object, or the other way around.
+pub(crate) fn check_args(types: &[JavaType], args: &[JValue]) -> Result<()> {
+    if types.len() != args.len() {
+        return Err(ErrorKind::InvalidArgList.into());
+    }
+
+    for (i, (ty, arg)) in types.iter().zip(args).enumerate() {
+        if !value_matches(ty, arg) {
+            return Err(ErrorKind::InvalidArgType(i, ty.to_string(), arg.type_name()).into());
+        }
+    }
+    Ok(())
+}
+
+fn value_matches(ty: &JavaType, val: &JValue) -> bool {
+    match (ty, val.primitive_type()) {
+        (&JavaType::Object(_), None) | (&JavaType::Array(_), None) => true,
+        (&JavaType::Primitive(ref p), Some(ref in_p)) => p == in_p,
+        _ => false,
+    }
+}
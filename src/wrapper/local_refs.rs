@@ -0,0 +1,194 @@
+#[cfg(feature = "local-ref-tracking")]
+use std::cell::RefCell;
+
+#[cfg(feature = "local-ref-tracking")]
+use std::sync::atomic::{
+    AtomicUsize,
+    Ordering,
+};
+
+#[cfg(feature = "local-ref-tracking")]
+use sys::_jobject;
+
+use sys::jobject;
+
+#[cfg(feature = "local-ref-tracking")]
+static LIMIT: AtomicUsize = AtomicUsize::new(DEFAULT_LOCAL_REF_LIMIT);
+
+#[cfg(feature = "local-ref-tracking")]
+thread_local! {
+    // one count per local frame, innermost last
+    static FRAMES: RefCell<Vec<usize>> = RefCell::new(vec![0]);
+}
+
+/// The default number of live local refs per frame at which warnings are
+/// logged, see `set_local_ref_limit`. This is the size of the local reference
+/// table on many VMs, Android's included.
+#[cfg(feature = "local-ref-tracking")]
+pub const DEFAULT_LOCAL_REF_LIMIT: usize = 512;
+
+/// Set the number of live local refs per frame that's considered the limit.
+/// A warning is logged when a frame reaches three quarters of it, and again
+/// when it reaches the limit itself. Nothing is enforced: the VM's own limit
+/// is what makes `local reference table overflow` crashes.
+///
+/// The limit is shared by all threads, the counts are kept per thread.
+#[cfg(feature = "local-ref-tracking")]
+pub fn set_local_ref_limit(limit: usize) {
+    LIMIT.store(limit, Ordering::Relaxed);
+}
+
+/// The number of local refs created through this crate, minus the ones
+/// deleted through it, in the current frame of this thread.
+///
+/// A new frame starts with each `push_local_frame` (and `with_local_frame`)
+/// and for the body of each `native_try!`. Refs created or deleted by calling
+/// the raw `sys` functions directly aren't counted.
+#[cfg(feature = "local-ref-tracking")]
+pub fn local_ref_count() -> usize {
+    FRAMES.with(|frames| *frames.borrow().last().unwrap())
+}
+
+/// Results of raw JNI calls, which may be a new local ref, or an error code.
+#[cfg(feature = "local-ref-tracking")]
+pub(crate) trait JniResult {
+    fn is_ref(&self) -> bool {
+        false
+    }
+
+    fn is_error_code(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(feature = "local-ref-tracking")]
+impl<T: 'static> JniResult for *mut T {
+    fn is_ref(&self) -> bool {
+        ::std::any::TypeId::of::<T>() == ::std::any::TypeId::of::<_jobject>() && !self.is_null()
+    }
+}
+
+#[cfg(feature = "local-ref-tracking")]
+impl<T> JniResult for *const T {}
+
+#[cfg(feature = "local-ref-tracking")]
+macro_rules! jni_result {
+    ( $( $ty:ty ),* ) => {
+        $( impl JniResult for $ty {} )*
+    };
+}
+
+#[cfg(feature = "local-ref-tracking")]
+jni_result!((), i8, u8, i16, u16, i64, f32, f64, ::sys::jobjectRefType);
+
+#[cfg(feature = "local-ref-tracking")]
+impl JniResult for i32 {
+    fn is_error_code(&self) -> bool {
+        *self < 0
+    }
+}
+
+/// Count the result of a raw JNI call. Called by the `jni_unchecked!`,
+/// `jni_non_null_call!` and `jni_void_call!` macros for every call but
+/// `DeleteLocalRef`, see `record_delete`.
+#[cfg(feature = "local-ref-tracking")]
+pub(crate) fn record<T: JniResult>(name: &str, res: &T) {
+    match name {
+        "PushLocalFrame" => if !res.is_error_code() {
+            push_frame();
+        },
+        "PopLocalFrame" => {
+            pop_frame();
+            if res.is_ref() {
+                update(|count| count + 1);
+            }
+        }
+        _ => if res.is_ref() && returns_local_ref(name) {
+            update(|count| count + 1);
+        },
+    }
+}
+
+#[cfg(not(feature = "local-ref-tracking"))]
+#[inline(always)]
+pub(crate) fn record<T>(_name: &str, _res: &T) {}
+
+/// Count a `DeleteLocalRef` call, which deletes nothing when `obj` is null.
+#[cfg(feature = "local-ref-tracking")]
+pub(crate) fn record_delete(obj: jobject) {
+    if !obj.is_null() {
+        update(|count| count.saturating_sub(1));
+    }
+}
+
+#[cfg(not(feature = "local-ref-tracking"))]
+#[inline(always)]
+pub(crate) fn record_delete(_obj: jobject) {}
+
+#[cfg(feature = "local-ref-tracking")]
+fn returns_local_ref(name: &str) -> bool {
+    match name {
+        "NewGlobalRef" | "NewWeakGlobalRef" => false,
+        "FindClass" | "DefineClass" | "GetObjectClass" | "GetSuperclass" | "AllocObject" | "GetObjectField"
+        | "GetStaticObjectField" | "GetObjectArrayElement" | "ExceptionOccurred" | "ToReflectedMethod"
+        | "ToReflectedField" => true,
+        _ => name.starts_with("New") || (name.starts_with("Call") && name.contains("ObjectMethod")),
+    }
+}
+
+#[cfg(feature = "local-ref-tracking")]
+fn update<F: FnOnce(usize) -> usize>(f: F) {
+    let count = FRAMES.with(|frames| {
+        let mut frames = frames.borrow_mut();
+        let count = frames.last_mut().unwrap();
+        *count = f(*count);
+        *count
+    });
+
+    let limit = LIMIT.load(Ordering::Relaxed);
+    if count == limit {
+        warn!("{} live local refs in the current frame, reaching the limit of {}", count, limit);
+    } else if count == limit / 4 * 3 {
+        warn!("{} live local refs in the current frame, approaching the limit of {}", count, limit);
+    }
+}
+
+#[cfg(feature = "local-ref-tracking")]
+fn push_frame() {
+    FRAMES.with(|frames| frames.borrow_mut().push(0));
+}
+
+#[cfg(feature = "local-ref-tracking")]
+fn pop_frame() {
+    FRAMES.with(|frames| {
+        let mut frames = frames.borrow_mut();
+        if frames.len() > 1 {
+            frames.pop();
+        }
+    });
+}
+
+/// Gives the body of a native method its own count for as long as it's
+/// alive, since java frees the method's local refs when it returns.
+pub(crate) struct NativeFrameGuard(());
+
+impl NativeFrameGuard {
+    #[cfg(feature = "local-ref-tracking")]
+    pub(crate) fn enter() -> NativeFrameGuard {
+        push_frame();
+        NativeFrameGuard(())
+    }
+
+    #[cfg(not(feature = "local-ref-tracking"))]
+    #[inline(always)]
+    pub(crate) fn enter() -> NativeFrameGuard {
+        NativeFrameGuard(())
+    }
+}
+
+#[cfg(feature = "local-ref-tracking")]
+impl Drop for NativeFrameGuard {
+    fn drop(&mut self) {
+        pop_frame();
+    }
+}
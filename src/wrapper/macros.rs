@@ -45,28 +45,93 @@ macro_rules! check_exception {
         } == $crate::sys::JNI_TRUE;
         if check {
             trace!("exception found, returning error");
-            return Err($crate::errors::Error::from(
-                $crate::errors::ErrorKind::JavaException).into());
+            return Err($crate::errors::pending_exception_error($jnienv).into());
         }
         trace!("no exception found");
     }
 }
 
 macro_rules! jni_unchecked {
-    ( $jnienv:expr, $name:tt $(, $args:expr )* ) => ({
+    // whether a local ref was deleted depends on the argument, not the result
+    ( @no_exception_check $jnienv:expr, DeleteLocalRef, $obj:expr ) => ({
+        trace!("calling unchecked jni method: DeleteLocalRef");
+        let obj = $obj;
+        let res = jni_method!($jnienv, DeleteLocalRef)($jnienv, obj);
+        $crate::wrapper::local_refs::record_delete(obj);
+        res
+    });
+    // for the few calls that must leave an exception they throw for the
+    // caller to handle, even with the `exception-check` feature
+    ( @no_exception_check $jnienv:expr, $name:tt $(, $args:expr )* ) => ({
         trace!("calling unchecked jni method: {}", stringify!($name));
         let res = jni_method!($jnienv, $name)($jnienv, $($args),*);
+        $crate::wrapper::local_refs::record(stringify!($name), &res);
+        res
+    });
+    ( $jnienv:expr, $name:tt $(, $args:expr )* ) => ({
+        check_exception_before!($jnienv, $name);
+        let res = jni_unchecked!(@no_exception_check $jnienv, $name $(, $args)*);
+        check_exception_after!($jnienv, $name);
         res
     })
 }
 
+/// With the `exception-check` feature, return an error instead of calling a
+/// JNI function while an exception is pending, unless it's one of the
+/// functions the JNI spec allows then.
+macro_rules! check_exception_before {
+    ( $jnienv:expr, ExceptionOccurred ) => {};
+    ( $jnienv:expr, ExceptionDescribe ) => {};
+    ( $jnienv:expr, ExceptionClear ) => {};
+    ( $jnienv:expr, ExceptionCheck ) => {};
+    ( $jnienv:expr, ReleaseStringChars ) => {};
+    ( $jnienv:expr, ReleaseStringUTFChars ) => {};
+    ( $jnienv:expr, ReleaseStringCritical ) => {};
+    ( $jnienv:expr, ReleaseBooleanArrayElements ) => {};
+    ( $jnienv:expr, ReleaseByteArrayElements ) => {};
+    ( $jnienv:expr, ReleaseCharArrayElements ) => {};
+    ( $jnienv:expr, ReleaseShortArrayElements ) => {};
+    ( $jnienv:expr, ReleaseIntArrayElements ) => {};
+    ( $jnienv:expr, ReleaseLongArrayElements ) => {};
+    ( $jnienv:expr, ReleaseFloatArrayElements ) => {};
+    ( $jnienv:expr, ReleaseDoubleArrayElements ) => {};
+    ( $jnienv:expr, ReleasePrimitiveArrayCritical ) => {};
+    ( $jnienv:expr, DeleteLocalRef ) => {};
+    ( $jnienv:expr, DeleteGlobalRef ) => {};
+    ( $jnienv:expr, DeleteWeakGlobalRef ) => {};
+    ( $jnienv:expr, MonitorExit ) => {};
+    ( $jnienv:expr, PushLocalFrame ) => {};
+    ( $jnienv:expr, PopLocalFrame ) => {};
+    ( $jnienv:expr, $name:tt ) => {
+        #[cfg(feature = "exception-check")]
+        check_exception!($jnienv);
+    };
+}
+
+/// With the `exception-check` feature, return an error right after a JNI
+/// function left an exception pending. The functions that may be called
+/// while one is pending aren't checked, since it may not be theirs, and
+/// neither are the ones throwing one on purpose. Nor are the ones entering a
+/// critical region, where checking would be a JNI call itself.
+macro_rules! check_exception_after {
+    ( $jnienv:expr, Throw ) => {};
+    ( $jnienv:expr, ThrowNew ) => {};
+    ( $jnienv:expr, GetPrimitiveArrayCritical ) => {};
+    ( $jnienv:expr, GetStringCritical ) => {};
+    ( $jnienv:expr, $name:tt ) => {
+        check_exception_before!($jnienv, $name);
+    };
+}
+
 macro_rules! jni_non_null_call {
     ( $jnienv:expr, $name:tt $(, $args:expr )* ) => ({
         trace!("calling checked jni method: {}", stringify!($name));
         #[allow(unused_unsafe)]
         unsafe {
             trace!("entering unsafe");
+            check_exception_before!($jnienv, $name);
             let res = jni_method!($jnienv, $name)($jnienv, $($args),*);
+            $crate::wrapper::local_refs::record(stringify!($name), &res);
             check_exception!($jnienv);
             trace!("exiting unsafe");
             res
@@ -80,7 +145,9 @@ macro_rules! jni_void_call {
         #[allow(unused_unsafe)]
         unsafe {
             trace!("entering unsafe");
-            jni_method!($jnienv, $name)($jnienv, $($args),*);
+            check_exception_before!($jnienv, $name);
+            let res = jni_method!($jnienv, $name)($jnienv, $($args),*);
+            $crate::wrapper::local_refs::record(stringify!($name), &res);
             check_exception!($jnienv);
             trace!("exiting unsafe");
         }
@@ -128,3 +195,285 @@ macro_rules! java_vm_unchecked {
         res
     })
 }
+
+/// Looks up a method id the first time it's used and keeps it in a static for
+/// the rest of the process. Only use this with classes loaded by the bootstrap
+/// class loader (i.e. `java.*`), since those can never be unloaded and their
+/// ids never become invalid.
+macro_rules! cached_method_id {
+    ( $env:expr, $class:expr, $name:expr, $sig:expr ) => ({
+        let raw = cached_id!($env, get_method_id, $class, $name, $sig);
+        $crate::objects::JMethodID::from(raw as $crate::sys::jmethodID)
+    })
+}
+
+/// Static method version of `cached_method_id`.
+macro_rules! cached_static_method_id {
+    ( $env:expr, $class:expr, $name:expr, $sig:expr ) => ({
+        let raw = cached_id!($env, get_static_method_id, $class, $name, $sig);
+        $crate::objects::JStaticMethodID::from(raw as $crate::sys::jmethodID)
+    })
+}
+
+macro_rules! cached_id {
+    ( $env:expr, $lookup:ident, $class:expr, $name:expr, $sig:expr ) => ({
+        static ID: ::std::sync::atomic::AtomicUsize = ::std::sync::atomic::AtomicUsize::new(0);
+        match ID.load(::std::sync::atomic::Ordering::Relaxed) {
+            0 => {
+                let env = $env;
+                let class = env.find_class($class)?;
+                let id = env.$lookup(class, $name, $sig);
+                env.delete_local_ref(class.into())?;
+                let id = id?.into_inner() as usize;
+                ID.store(id, ::std::sync::atomic::Ordering::Relaxed);
+                id
+            }
+            id => id,
+        }
+    })
+}
+
+/// Call a java method with the signature worked out from rust types, so that
+/// argument mismatches are caught at compile time.
+///
+/// The argument and return types must implement `JavaArg` and `JavaReturn`
+/// respectively. That covers the primitive types (`bool`, `jint`, `jlong`...)
+/// and `JObject`, `JString`, `JClass` and `JThrowable` (or references to
+/// them), which map to `java.lang.Object`, `String`, `Class` and `Throwable`.
+/// Methods taking other object types still need `JNIEnv::call_method`.
+///
+/// Evaluates to a `Result` with the return type given in the macro.
+///
+/// # Example
+/// ```rust,ignore
+/// let n: i32 = call!(env, obj, "com/example/Foo"::"bar"(i32, &JString) -> i32, 5, &name)?;
+/// ```
+///
+/// Passing an `f64` where the signature says `i32`, or the wrong number of
+/// arguments, fails to compile.
+#[macro_export]
+macro_rules! call {
+    ( $env:expr, $obj:expr, $class:tt :: $name:tt ( $( $arg_ty:ty ),* ) -> $ret:ty $(, $arg:expr )* ) => ({
+        $crate::objects::call_typed::<$ret>(
+            &$env,
+            $obj,
+            $class,
+            $name,
+            &[ $( <$arg_ty as $crate::objects::JavaArg>::descriptor() ),* ],
+            &[ $( {
+                let arg: $arg_ty = $arg;
+                $crate::objects::JavaArg::into_jvalue(arg)
+            } ),* ],
+        )
+    })
+}
+
+/// Wrap the body of a native method so that nothing but java exceptions
+/// escapes it. The body is a block evaluating to a `Result` of the method's
+/// return type and may use `?`.
+///
+/// * `Ok` values are returned as-is.
+/// * On `Err`, a java exception that's already pending is left as it is;
+///   otherwise a `RuntimeException` with the error message is thrown.
+/// * Panics are caught and thrown as a `RuntimeException` as well.
+///
+/// In both failure cases the method returns a zero value of its return type
+/// (null for objects), which java ignores since an exception is pending. The
+/// return type must implement `NativeReturn`: `()`, all the primitive types
+/// and the object types such as `jobject` and `jstring`.
+///
+/// # Example
+/// ```rust,ignore
+/// #[no_mangle]
+/// pub extern "system" fn Java_HelloWorld_hello(env: JNIEnv, _class: JClass, input: JString) -> jstring {
+///     native_try!(env, {
+///         let input: String = env.get_string(input)?.into();
+///         Ok(env.new_string(format!("Hello, {}!", input))?.into_inner())
+///     })
+/// }
+/// ```
+///
+/// The body can also return your own error type, if it implements
+/// `IntoJavaException`, by naming it before the block. The exception class
+/// and message then come from the error:
+///
+/// ```rust,ignore
+/// native_try!(env, MyError, {
+///     let user = lookup_user(id)?;
+///     Ok(user.age)
+/// })
+/// ```
+#[macro_export]
+macro_rules! native_try {
+    ( $env:expr, $body:block ) => {
+        $crate::native_try(&$env, || -> $crate::errors::Result<_> { $body })
+    };
+    ( $env:expr, $err:ty, $body:block ) => {
+        $crate::native_try(&$env, || -> ::std::result::Result<_, $err> { $body })
+    };
+}
+
+/// Define the `JNI_OnLoad` function java calls when it loads this library.
+///
+/// The body gets the `JavaVM` and the reserved pointer, and returns a
+/// `Result<()>`. On success, `JNI_OnLoad` returns the JNI version the library
+/// needs, `JNIVersion::V6` unless given with `version = `, and on an error or
+/// a panic it returns `JNI_ERR` (which makes `System.loadLibrary` throw) after
+/// logging why. With `store_vm`, the VM is kept for `loaded_java_vm()`, so
+/// that threads started later by the library can attach to it.
+///
+/// # Example
+/// ```rust,ignore
+/// jni_on_load!(version = JNIVersion::V8, store_vm, |vm, _reserved| {
+///     let env = vm.get_env()?;
+///     env.register_native_methods("com/example/Lib", &methods())?;
+///     Ok(())
+/// });
+///
+/// // later, on any thread
+/// let vm = jni::loaded_java_vm().unwrap();
+/// let env = vm.attach_current_thread()?;
+/// ```
+#[macro_export]
+macro_rules! jni_on_load {
+    ( $( version = $version:expr, )? store_vm, | $vm:pat, $reserved:pat | $body:expr ) => {
+        $crate::jni_on_load!(@export true, [ $( $version )? ], | $vm, $reserved | $body);
+    };
+    ( $( version = $version:expr, )? | $vm:pat, $reserved:pat | $body:expr ) => {
+        $crate::jni_on_load!(@export false, [ $( $version )? ], | $vm, $reserved | $body);
+    };
+    ( @export $store_vm:expr, [ $( $version:expr )? ], | $vm:pat, $reserved:pat | $body:expr ) => {
+        #[no_mangle]
+        #[allow(non_snake_case)]
+        // only the VM calls it, with a valid VM pointer
+        #[allow(clippy::not_unsafe_ptr_arg_deref)]
+        pub extern "system" fn JNI_OnLoad(
+            vm: *mut $crate::sys::JavaVM,
+            reserved: *mut ::std::os::raw::c_void,
+        ) -> $crate::sys::jint {
+            #[allow(unused_variables)]
+            let version: $crate::JNIVersion = $crate::JNIVersion::V6;
+            $( let version: $crate::JNIVersion = $version; )?
+            // built outside the `unsafe` block, so that it doesn't cover the body
+            let f = |$vm: &$crate::JavaVM, $reserved: *mut ::std::os::raw::c_void| -> $crate::errors::Result<()> {
+                $body
+            };
+            unsafe { $crate::on_load(vm, reserved, version, $store_vm, f) }
+        }
+    };
+}
+
+/// Define the `JNI_OnUnload` function java calls when the class loader of
+/// this library is garbage collected. The body gets the `JavaVM` and the
+/// reserved pointer. The VM stored by `jni_on_load!` is cleared first, and
+/// panics are caught and logged.
+///
+/// # Example
+/// ```rust,ignore
+/// jni_on_unload!(|_vm, _reserved| {
+///     shutdown_worker_threads();
+/// });
+/// ```
+#[macro_export]
+macro_rules! jni_on_unload {
+    ( | $vm:pat, $reserved:pat | $body:expr ) => {
+        #[no_mangle]
+        #[allow(non_snake_case)]
+        // only the VM calls it, with a valid VM pointer
+        #[allow(clippy::not_unsafe_ptr_arg_deref)]
+        pub extern "system" fn JNI_OnUnload(
+            vm: *mut $crate::sys::JavaVM,
+            reserved: *mut ::std::os::raw::c_void,
+        ) {
+            let f = |$vm: &$crate::JavaVM, $reserved: *mut ::std::os::raw::c_void| {
+                $body
+            };
+            unsafe { $crate::on_unload(vm, reserved, f) }
+        }
+    };
+}
+
+/// Build a type signature string at compile time from java-like syntax, so
+/// that typos are compile errors instead of `NoSuchMethodError`s at runtime.
+///
+/// Classes are written with their fully qualified name, primitives with their
+/// java keyword, and arrays with `[]` after the element type. Without the
+/// parentheses and arrow, it builds the signature of a single (field) type.
+///
+/// # Example
+/// ```rust,ignore
+/// assert_eq!(sig!((java.lang.String, int) -> boolean), "(Ljava/lang/String;I)Z");
+/// assert_eq!(sig!((byte[][], java.lang.Object[]) -> void), "([[B[Ljava/lang/Object;)V");
+/// assert_eq!(sig!(java.util.List), "Ljava/util/List;");
+///
+/// env.call_method(s, "startsWith", sig!((java.lang.String) -> boolean), &[prefix.into()])?;
+/// ```
+///
+/// Class names without a package (such as `String` for `java.lang.String`),
+/// unknown primitive names and `void` anywhere but the return type are
+/// rejected. Nested classes, whose names contain a `$`, can't be written
+/// with this macro.
+#[macro_export]
+macro_rules! sig {
+    ( ( $( $args:tt )* ) -> $( $ret:tt )+ ) => {
+        concat!(
+            "(",
+            $crate::__jni_sig_args!([] $( $args )*),
+            ")",
+            $crate::__jni_sig_type!(@ret $( $ret )+)
+        )
+    };
+    ( $( $ty:tt )+ ) => {
+        $crate::__jni_sig_type!(@arg $( $ty )+)
+    };
+}
+
+/// Splits the arguments of `sig!` on commas.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __jni_sig_args {
+    ( [] ) => { "" };
+    ( [ $( $cur:tt )+ ] ) => { $crate::__jni_sig_type!(@arg $( $cur )+) };
+    ( [ $( $cur:tt )+ ] , $( $rest:tt )+ ) => {
+        concat!($crate::__jni_sig_type!(@arg $( $cur )+), $crate::__jni_sig_args!([] $( $rest )+))
+    };
+    ( [ $( $cur:tt )* ] $next:tt $( $rest:tt )* ) => {
+        $crate::__jni_sig_args!([ $( $cur )* $next ] $( $rest )*)
+    };
+}
+
+/// Turns a single type of `sig!` into its descriptor.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __jni_sig_type {
+    ( @ret void ) => { "V" };
+    ( @ret $( $ty:tt )+ ) => { $crate::__jni_sig_type!(@arg $( $ty )+) };
+
+    ( @arg boolean $( $dims:tt )* ) => { concat!($crate::__jni_sig_type!(@dims $( $dims )*), "Z") };
+    ( @arg byte $( $dims:tt )* ) => { concat!($crate::__jni_sig_type!(@dims $( $dims )*), "B") };
+    ( @arg char $( $dims:tt )* ) => { concat!($crate::__jni_sig_type!(@dims $( $dims )*), "C") };
+    ( @arg short $( $dims:tt )* ) => { concat!($crate::__jni_sig_type!(@dims $( $dims )*), "S") };
+    ( @arg int $( $dims:tt )* ) => { concat!($crate::__jni_sig_type!(@dims $( $dims )*), "I") };
+    ( @arg long $( $dims:tt )* ) => { concat!($crate::__jni_sig_type!(@dims $( $dims )*), "J") };
+    ( @arg float $( $dims:tt )* ) => { concat!($crate::__jni_sig_type!(@dims $( $dims )*), "F") };
+    ( @arg double $( $dims:tt )* ) => { concat!($crate::__jni_sig_type!(@dims $( $dims )*), "D") };
+    ( @arg $first:ident . $( $rest:tt )+ ) => {
+        $crate::__jni_sig_type!(@class [ stringify!($first), ] $( $rest )+)
+    };
+    ( @arg $( $ty:tt )* ) => {
+        compile_error!(concat!("invalid java type in signature: `", stringify!($( $ty )*), "`"))
+    };
+
+    ( @class [ $( $acc:expr, )* ] $seg:ident . $( $rest:tt )+ ) => {
+        $crate::__jni_sig_type!(@class [ $( $acc, )* "/", stringify!($seg), ] $( $rest )+)
+    };
+    ( @class [ $( $acc:expr, )* ] $seg:ident $( $dims:tt )* ) => {
+        concat!($crate::__jni_sig_type!(@dims $( $dims )*), "L", $( $acc, )* "/", stringify!($seg), ";")
+    };
+
+    ( @dims ) => { "" };
+    ( @dims [] $( $rest:tt )* ) => { concat!("[", $crate::__jni_sig_type!(@dims $( $rest )*)) };
+    ( @dims $( $bad:tt )+ ) => {
+        compile_error!(concat!("expected `[]` after a java type, found `", stringify!($( $bad )+), "`"))
+    };
+}
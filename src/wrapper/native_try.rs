@@ -0,0 +1,336 @@
+use std::any::Any;
+use std::panic::{
+    self,
+    AssertUnwindSafe,
+};
+use std::ptr;
+
+use JNIEnv;
+
+use errors::*;
+
+use exceptions::RUNTIME_EXCEPTION;
+
+use wrapper::local_refs::NativeFrameGuard;
+
+use objects::{
+    JClass,
+    JObject,
+    JString,
+};
+
+use sys::{
+    jboolean,
+    jbyte,
+    jchar,
+    jdouble,
+    jfloat,
+    jint,
+    jlong,
+    jobject,
+    jshort,
+};
+
+/// Return types of native methods that `native_try!` knows how to make a
+/// dummy value for. Java ignores the value returned along with a thrown
+/// exception, so it only has to be something of the right type.
+pub trait NativeReturn {
+    /// The value returned to java when the method fails.
+    fn zero() -> Self;
+}
+
+impl NativeReturn for () {
+    fn zero() -> Self {}
+}
+
+impl NativeReturn for jboolean {
+    fn zero() -> Self {
+        0
+    }
+}
+
+impl NativeReturn for jbyte {
+    fn zero() -> Self {
+        0
+    }
+}
+
+impl NativeReturn for jchar {
+    fn zero() -> Self {
+        0
+    }
+}
+
+impl NativeReturn for jshort {
+    fn zero() -> Self {
+        0
+    }
+}
+
+impl NativeReturn for jint {
+    fn zero() -> Self {
+        0
+    }
+}
+
+impl NativeReturn for jlong {
+    fn zero() -> Self {
+        0
+    }
+}
+
+impl NativeReturn for jfloat {
+    fn zero() -> Self {
+        0.0
+    }
+}
+
+impl NativeReturn for jdouble {
+    fn zero() -> Self {
+        0.0
+    }
+}
+
+/// Covers `jstring`, `jclass` and the other object types too, which are all
+/// aliases of `jobject`.
+impl NativeReturn for jobject {
+    fn zero() -> Self {
+        ptr::null_mut()
+    }
+}
+
+/// Argument types of functions exported with `#[jni_export]`, converted from
+/// the raw JNI value java passes.
+///
+/// The primitive types are passed as-is (`bool` from a `jboolean`), the
+/// object wrappers wrap the reference, `String` decodes a `java.lang.String`
+/// and `Vec<u8>` copies a `byte[]`. `Option` of an object type is `None` for
+/// null.
+pub trait NativeArg: Sized {
+    /// The JNI type of the argument.
+    type Raw;
+
+    /// Convert the raw value.
+    fn from_native_arg(env: &JNIEnv, raw: Self::Raw) -> Result<Self>;
+}
+
+/// Return types of functions exported with `#[jni_export]`, the reverse of
+/// `NativeArg`: `String` becomes a new `java.lang.String`, `Vec<u8>` a new
+/// `byte[]` and `None` null.
+pub trait IntoNativeReturn {
+    /// The JNI type returned to java.
+    type Raw: NativeReturn;
+
+    /// Convert the value into what's returned to java.
+    fn into_native_return(self, env: &JNIEnv) -> Result<Self::Raw>;
+}
+
+macro_rules! native_primitive {
+    ( $( $jni_type:ty ),* ) => {
+        $(
+            impl NativeArg for $jni_type {
+                type Raw = $jni_type;
+
+                fn from_native_arg(_env: &JNIEnv, raw: Self::Raw) -> Result<Self> {
+                    Ok(raw)
+                }
+            }
+
+            impl IntoNativeReturn for $jni_type {
+                type Raw = $jni_type;
+
+                fn into_native_return(self, _env: &JNIEnv) -> Result<Self::Raw> {
+                    Ok(self)
+                }
+            }
+        )*
+    };
+}
+
+native_primitive!(jboolean, jbyte, jchar, jshort, jint, jlong, jfloat, jdouble);
+
+impl IntoNativeReturn for () {
+    type Raw = ();
+
+    fn into_native_return(self, _env: &JNIEnv) -> Result<Self::Raw> {
+        Ok(())
+    }
+}
+
+impl NativeArg for bool {
+    type Raw = jboolean;
+
+    fn from_native_arg(_env: &JNIEnv, raw: Self::Raw) -> Result<Self> {
+        Ok(raw != 0)
+    }
+}
+
+impl IntoNativeReturn for bool {
+    type Raw = jboolean;
+
+    fn into_native_return(self, _env: &JNIEnv) -> Result<Self::Raw> {
+        Ok(self as jboolean)
+    }
+}
+
+macro_rules! native_object {
+    ( $( $wrapper:ident ),* ) => {
+        $(
+            impl<'a> NativeArg for $wrapper<'a> {
+                type Raw = jobject;
+
+                fn from_native_arg(_env: &JNIEnv, raw: Self::Raw) -> Result<Self> {
+                    Ok($wrapper::from(raw))
+                }
+            }
+
+            impl<'a> IntoNativeReturn for $wrapper<'a> {
+                type Raw = jobject;
+
+                fn into_native_return(self, _env: &JNIEnv) -> Result<Self::Raw> {
+                    Ok(self.into_inner())
+                }
+            }
+        )*
+    };
+}
+
+native_object!(JObject, JString, JClass);
+
+impl NativeArg for String {
+    type Raw = jobject;
+
+    fn from_native_arg(env: &JNIEnv, raw: Self::Raw) -> Result<Self> {
+        Ok(env.get_string(JString::from(raw))?.into())
+    }
+}
+
+impl IntoNativeReturn for String {
+    type Raw = jobject;
+
+    fn into_native_return(self, env: &JNIEnv) -> Result<Self::Raw> {
+        Ok(env.new_string(self)?.into_inner())
+    }
+}
+
+impl NativeArg for Vec<u8> {
+    type Raw = jobject;
+
+    fn from_native_arg(env: &JNIEnv, raw: Self::Raw) -> Result<Self> {
+        env.convert_byte_array(raw)
+    }
+}
+
+impl IntoNativeReturn for Vec<u8> {
+    type Raw = jobject;
+
+    fn into_native_return(self, env: &JNIEnv) -> Result<Self::Raw> {
+        env.byte_array_from_slice(&self)
+    }
+}
+
+impl<T: NativeArg<Raw = jobject>> NativeArg for Option<T> {
+    type Raw = jobject;
+
+    fn from_native_arg(env: &JNIEnv, raw: Self::Raw) -> Result<Self> {
+        if raw.is_null() {
+            Ok(None)
+        } else {
+            T::from_native_arg(env, raw).map(Some)
+        }
+    }
+}
+
+impl<T: IntoNativeReturn<Raw = jobject>> IntoNativeReturn for Option<T> {
+    type Raw = jobject;
+
+    fn into_native_return(self, env: &JNIEnv) -> Result<Self::Raw> {
+        match self {
+            Some(val) => val.into_native_return(env),
+            None => Ok(ptr::null_mut()),
+        }
+    }
+}
+
+/// Errors that `native_try!` can turn into a java exception. Implement this
+/// for your own error type to choose which exception each error becomes:
+///
+/// ```rust,ignore
+/// impl IntoJavaException for MyError {
+///     fn exception_class(&self) -> &str {
+///         match *self {
+///             MyError::NotFound(_) => exceptions::NO_SUCH_ELEMENT_EXCEPTION,
+///             MyError::Io(_) => exceptions::IO_EXCEPTION,
+///         }
+///     }
+///
+///     fn exception_message(&self) -> String {
+///         self.to_string()
+///     }
+/// }
+/// ```
+pub trait IntoJavaException {
+    /// The class of the exception to throw, as a class name such as
+    /// `"java/lang/IllegalArgumentException"`. Defaults to
+    /// `RuntimeException`.
+    fn exception_class(&self) -> &str {
+        RUNTIME_EXCEPTION
+    }
+
+    /// The message of the exception to throw.
+    fn exception_message(&self) -> String;
+}
+
+impl IntoJavaException for Error {
+    fn exception_message(&self) -> String {
+        self.to_string()
+    }
+}
+
+/// The function behind `native_try!`.
+#[doc(hidden)]
+pub fn native_try<T, E, F>(env: &JNIEnv, f: F) -> T
+where
+    T: NativeReturn,
+    E: IntoJavaException,
+    F: FnOnce() -> ::std::result::Result<T, E>,
+{
+    let _frame = NativeFrameGuard::enter();
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(val)) => return val,
+        Ok(Err(e)) => throw_native_error(env, &e),
+        Err(payload) => throw_unless_pending(
+            env,
+            RUNTIME_EXCEPTION,
+            format!("panic in native method: {}", panic_message(&payload)),
+        ),
+    }
+    T::zero()
+}
+
+/// Throw an error returned by a native method as a java exception. Used by
+/// `native_try!` and the wrappers `#[jni_export]` generates.
+#[doc(hidden)]
+pub fn throw_native_error<E: IntoJavaException>(env: &JNIEnv, err: &E) {
+    throw_unless_pending(env, err.exception_class(), err.exception_message())
+}
+
+fn throw_unless_pending(env: &JNIEnv, class: &str, msg: String) {
+    match env.exception_check() {
+        // keep the original java exception, it's more useful than our error
+        Ok(true) => {}
+        _ => if let Err(e) = env.throw_new(class, msg) {
+            warn!("error throwing exception from native method: {}", e);
+        },
+    }
+}
+
+pub(crate) fn panic_message(payload: &Box<dyn Any + Send>) -> &str {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        msg
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg
+    } else {
+        "unknown panic"
+    }
+}
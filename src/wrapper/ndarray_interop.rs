@@ -0,0 +1,97 @@
+use ndarray::{
+    Array1,
+    Array2,
+    ArrayBase,
+    ArrayView1,
+    Data,
+    Ix1,
+    Ix2,
+};
+
+use JNIEnv;
+
+use objects::JObject;
+
+use errors::*;
+
+use sys::{
+    jdoubleArray,
+    jobjectArray,
+    jsize,
+};
+
+impl<'a> JNIEnv<'a> {
+    /// Read a java `double[]` into an `Array1`. The elements are copied once,
+    /// straight into the buffer backing the returned array.
+    pub fn get_double_array_as_array1(&self, array: jdoubleArray) -> Result<Array1<f64>> {
+        let len = self.get_array_length(array)?;
+        let mut buf = vec![0.0; len as usize];
+        self.get_double_array_region(array, 0, &mut buf)?;
+        Ok(Array1::from(buf))
+    }
+
+    /// Read a java `double[][]` into an `Array2`, with the inner arrays as
+    /// rows. All rows must have the same length; a ragged array gives an
+    /// error naming the first row that doesn't fit, as does a null row.
+    pub fn get_double_array_as_array2(&'a self, array: jobjectArray) -> Result<Array2<f64>> {
+        let rows = self.get_array_length(array)? as usize;
+        let mut cols = 0;
+        let mut buf = Vec::new();
+
+        for i in 0..rows {
+            let row = self.auto_local(self.get_object_array_element(array, i as jsize)?);
+            let row = row.as_obj().into_inner() as jdoubleArray;
+            let len = self.get_array_length(row)? as usize;
+            if i == 0 {
+                cols = len;
+                buf = vec![0.0; rows * cols];
+            } else if len != cols {
+                return Err(format!(
+                    "ragged double[][]: row {} has length {}, expected {}",
+                    i, len, cols
+                ).into());
+            }
+            self.get_double_array_region(row, 0, &mut buf[i * cols..(i + 1) * cols])?;
+        }
+
+        Array2::from_shape_vec((rows, cols), buf)
+            .map_err(|e| format!("error building Array2 from double[][]: {}", e).into())
+    }
+
+    /// Build a java `double[]` from a 1D array or view. Contiguous arrays are
+    /// copied straight from their buffer; strided ones (e.g. a column view)
+    /// are gathered into a temporary buffer first.
+    pub fn array1_to_double_array<S>(&self, a: &ArrayBase<S, Ix1>) -> Result<jdoubleArray>
+    where
+        S: Data<Elem = f64>,
+    {
+        let array = self.new_double_array(a.len() as jsize)?;
+        self.set_double_array_from_view(array, a.view())?;
+        Ok(array)
+    }
+
+    /// Build a java `double[][]` from a 2D array or view, with each row
+    /// becoming an inner `double[]`. Rows are copied as for
+    /// `array1_to_double_array`.
+    pub fn array2_to_double_array_2d<S>(&'a self, a: &ArrayBase<S, Ix2>) -> Result<jobjectArray>
+    where
+        S: Data<Elem = f64>,
+    {
+        let (rows, cols) = a.dim();
+        let array = self.new_object_array(rows as jsize, "[D", JObject::null())?;
+        for (i, row) in a.outer_iter().enumerate() {
+            let inner = self.new_double_array(cols as jsize)?;
+            let inner = self.auto_local(JObject::from(inner));
+            self.set_double_array_from_view(inner.as_obj().into_inner() as jdoubleArray, row)?;
+            self.set_object_array_element(array, i as jsize, inner.as_obj())?;
+        }
+        Ok(array)
+    }
+
+    fn set_double_array_from_view(&self, array: jdoubleArray, view: ArrayView1<f64>) -> Result<()> {
+        match view.as_slice() {
+            Some(slice) => self.set_double_array_region(array, 0, slice),
+            None => self.set_double_array_region(array, 0, &view.to_vec()),
+        }
+    }
+}
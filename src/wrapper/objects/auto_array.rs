@@ -0,0 +1,173 @@
+use std::ops::{Deref, DerefMut};
+use std::slice;
+
+use JNIEnv;
+
+use errors::*;
+
+use sys::{
+    self,
+    jarray,
+    jboolean,
+    jbyte,
+    jchar,
+    jdouble,
+    jfloat,
+    jint,
+    jlong,
+    jshort,
+};
+
+/// What happens to changes made through an `AutoArray` when it's dropped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReleaseMode {
+    /// Copy the elements back to the java array (if the VM handed out a copy)
+    /// and free the buffer. Release mode `0`.
+    CopyBack,
+    /// Free the buffer without copying anything back, discarding changes made
+    /// since the last `commit`. Release mode `JNI_ABORT`.
+    NoCopyBack,
+}
+
+/// Primitive types that can be accessed with `Get<Type>ArrayElements`. This
+/// picks the matching get/release pair for `AutoArray`.
+pub trait TypeArray: Copy {
+    #[doc(hidden)]
+    fn array_class() -> &'static str;
+
+    #[doc(hidden)]
+    unsafe fn get(env: *mut sys::JNIEnv, array: jarray, is_copy: *mut jboolean) -> Result<*mut Self>;
+
+    #[doc(hidden)]
+    unsafe fn release(env: *mut sys::JNIEnv, array: jarray, elems: *mut Self, mode: jint) -> Result<()>;
+}
+
+macro_rules! type_array {
+    ( $jni_type:ty, $class:expr, $get:ident, $release:ident ) => {
+        impl TypeArray for $jni_type {
+            fn array_class() -> &'static str {
+                $class
+            }
+
+            unsafe fn get(env: *mut sys::JNIEnv, array: jarray, is_copy: *mut jboolean) -> Result<*mut Self> {
+                Ok(jni_unchecked!(env, $get, array, is_copy))
+            }
+
+            unsafe fn release(env: *mut sys::JNIEnv, array: jarray, elems: *mut Self, mode: jint) -> Result<()> {
+                jni_unchecked!(env, $release, array, elems, mode);
+                Ok(())
+            }
+        }
+    };
+}
+
+type_array!(jboolean, "[Z", GetBooleanArrayElements, ReleaseBooleanArrayElements);
+type_array!(jbyte, "[B", GetByteArrayElements, ReleaseByteArrayElements);
+type_array!(jchar, "[C", GetCharArrayElements, ReleaseCharArrayElements);
+type_array!(jshort, "[S", GetShortArrayElements, ReleaseShortArrayElements);
+type_array!(jint, "[I", GetIntArrayElements, ReleaseIntArrayElements);
+type_array!(jlong, "[J", GetLongArrayElements, ReleaseLongArrayElements);
+type_array!(jfloat, "[F", GetFloatArrayElements, ReleaseFloatArrayElements);
+type_array!(jdouble, "[D", GetDoubleArrayElements, ReleaseDoubleArrayElements);
+
+/// The elements of a java primitive array, as returned by
+/// `Get<Type>ArrayElements`. Calls the matching `Release<Type>ArrayElements`
+/// with the chosen `ReleaseMode` on Drop.
+///
+/// Derefs to `&[T]` and `&mut [T]`. The VM may hand out a copy instead of the
+/// array's own memory (see `is_copy`), in which case writes only reach the
+/// java array on `commit` or when the guard is dropped with
+/// `ReleaseMode::CopyBack`.
+pub struct AutoArray<'a, T: TypeArray> {
+    array: jarray,
+    ptr: *mut T,
+    len: usize,
+    is_copy: bool,
+    mode: ReleaseMode,
+    env: &'a JNIEnv<'a>,
+}
+
+impl<'a, T: TypeArray> AutoArray<'a, T> {
+    /// Get the elements of `array`, which is checked to be a java array of
+    /// `T` first: the `j<type>Array` types are all aliases of `jobject`, so
+    /// the `JNIEnv::get_<type>_array_elements` methods that call this can't
+    /// rule out a mismatch at compile time.
+    pub(crate) fn new(env: &'a JNIEnv<'a>, array: jarray, mode: ReleaseMode) -> Result<Self> {
+        let class = env.find_class(T::array_class())?;
+        let matches = env.is_instance_of(array.into(), class);
+        env.delete_local_ref(class.into())?;
+        if !matches? {
+            return Err(format!("array is not a {}", T::array_class()).into());
+        }
+
+        let len = env.get_array_length(array)? as usize;
+        let internal = env.get_native_interface();
+
+        let mut is_copy: jboolean = sys::JNI_FALSE;
+        let ptr = unsafe {
+            let ptr = T::get(internal, array, &mut is_copy)?;
+            check_exception!(internal);
+            ptr
+        };
+        non_null!(ptr, "AutoArray::new result");
+
+        Ok(AutoArray {
+            array: array,
+            ptr: ptr,
+            len: len,
+            is_copy: is_copy == sys::JNI_TRUE,
+            mode: mode,
+            env: env,
+        })
+    }
+
+    /// Whether the VM handed out a copy of the elements rather than the
+    /// array's own memory.
+    pub fn is_copy(&self) -> bool {
+        self.is_copy
+    }
+
+    /// The mode the elements will be released with when this is dropped.
+    pub fn release_mode(&self) -> ReleaseMode {
+        self.mode
+    }
+
+    /// Copy the current elements back to the java array without releasing
+    /// them (release mode `JNI_COMMIT`). Does nothing if the elements aren't a
+    /// copy.
+    pub fn commit(&mut self) -> Result<()> {
+        unsafe { T::release(self.env.get_native_interface(), self.array, self.ptr, sys::JNI_COMMIT) }
+    }
+
+    /// Extract the raw pointer to the elements.
+    pub fn as_ptr(&self) -> *mut T {
+        self.ptr
+    }
+}
+
+impl<'a, T: TypeArray> Deref for AutoArray<'a, T> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { slice::from_raw_parts(self.ptr, self.len) }
+    }
+}
+
+impl<'a, T: TypeArray> DerefMut for AutoArray<'a, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { slice::from_raw_parts_mut(self.ptr, self.len) }
+    }
+}
+
+impl<'a, T: TypeArray> Drop for AutoArray<'a, T> {
+    fn drop(&mut self) {
+        let mode = match self.mode {
+            ReleaseMode::CopyBack => 0,
+            ReleaseMode::NoCopyBack => sys::JNI_ABORT,
+        };
+        let res = unsafe { T::release(self.env.get_native_interface(), self.array, self.ptr, mode) };
+        if let Err(e) = res {
+            warn!("error releasing array elements: {}", e);
+        }
+    }
+}
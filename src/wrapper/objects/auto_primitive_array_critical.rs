@@ -0,0 +1,126 @@
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+use std::slice;
+
+use JNIEnv;
+
+use errors::*;
+
+use objects::{
+    ReleaseMode,
+    TypeArray,
+};
+
+use sys::{
+    self,
+    jarray,
+    jboolean,
+};
+
+/// The elements of a java primitive array, as returned by
+/// `GetPrimitiveArrayCritical`. Calls `ReleasePrimitiveArrayCritical` with
+/// the chosen `ReleaseMode` on Drop.
+///
+/// While this is alive the VM may have paused garbage collection, and no
+/// other JNI calls are allowed. To enforce that, the guard mutably borrows
+/// the `JNIEnv` it came from for its whole lifetime, so the env can't be used
+/// until the guard is dropped. Keep critical sections short, and don't block
+/// on other java threads while holding one.
+///
+/// Derefs to `&[T]` and `&mut [T]`. Use
+/// `JNIEnv::get_primitive_array_critical` to get one.
+pub struct AutoPrimitiveArrayCritical<'a, T: TypeArray> {
+    internal: *mut sys::JNIEnv,
+    array: jarray,
+    ptr: *mut T,
+    len: usize,
+    is_copy: bool,
+    mode: ReleaseMode,
+    env: PhantomData<&'a mut ()>,
+}
+
+impl<'a, T: TypeArray> AutoPrimitiveArrayCritical<'a, T> {
+    /// Enter a critical section for `array`. Checks that `array` is a java
+    /// array of `T` first, since `GetPrimitiveArrayCritical` itself takes any
+    /// primitive array.
+    pub(crate) fn new<'e>(env: &'a mut JNIEnv<'e>, array: jarray, mode: ReleaseMode) -> Result<Self> {
+        non_null!(array, "AutoPrimitiveArrayCritical::new array argument");
+
+        let class = env.find_class(T::array_class())?;
+        let matches = env.is_instance_of(array.into(), class);
+        env.delete_local_ref(class.into())?;
+        if !matches? {
+            return Err(format!("array is not a {}", T::array_class()).into());
+        }
+
+        // The length has to be read first: no JNI calls are allowed once we
+        // are in a critical region.
+        let len = env.get_array_length(array)? as usize;
+        let internal = env.get_native_interface();
+
+        let mut is_copy: jboolean = sys::JNI_FALSE;
+        let ptr = unsafe { jni_unchecked!(internal, GetPrimitiveArrayCritical, array, &mut is_copy) };
+        non_null!(ptr, "AutoPrimitiveArrayCritical::new result");
+
+        Ok(AutoPrimitiveArrayCritical {
+            internal: internal,
+            array: array,
+            ptr: ptr as *mut T,
+            len: len,
+            is_copy: is_copy == sys::JNI_TRUE,
+            mode: mode,
+            env: PhantomData,
+        })
+    }
+
+    /// Whether the VM handed out a copy of the elements rather than the
+    /// array's own memory.
+    pub fn is_copy(&self) -> bool {
+        self.is_copy
+    }
+
+    /// Extract the raw pointer to the elements.
+    pub fn as_ptr(&self) -> *mut T {
+        self.ptr
+    }
+
+    fn release(&mut self) -> Result<()> {
+        let mode = match self.mode {
+            ReleaseMode::CopyBack => 0,
+            ReleaseMode::NoCopyBack => sys::JNI_ABORT,
+        };
+        unsafe {
+            jni_unchecked!(
+                self.internal,
+                ReleasePrimitiveArrayCritical,
+                self.array,
+                self.ptr as *mut _,
+                mode
+            );
+        }
+        Ok(())
+    }
+}
+
+impl<'a, T: TypeArray> Deref for AutoPrimitiveArrayCritical<'a, T> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { slice::from_raw_parts(self.ptr, self.len) }
+    }
+}
+
+impl<'a, T: TypeArray> DerefMut for AutoPrimitiveArrayCritical<'a, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { slice::from_raw_parts_mut(self.ptr, self.len) }
+    }
+}
+
+impl<'a, T: TypeArray> Drop for AutoPrimitiveArrayCritical<'a, T> {
+    fn drop(&mut self) {
+        match self.release() {
+            Ok(()) => {}
+            Err(e) => warn!("error releasing primitive array critical: {}", e),
+        }
+    }
+}
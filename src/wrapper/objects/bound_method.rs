@@ -0,0 +1,61 @@
+use JNIEnv;
+
+use errors::*;
+
+use objects::GlobalRef;
+use objects::JMethodID;
+use objects::JObject;
+use objects::JValue;
+
+use signature::JavaType;
+
+use sys::jmethodID;
+
+/// A method bound to a long-lived object: a `GlobalRef` to the receiver, the
+/// `JMethodID` to call on it, and its return type. Invoking it skips all class
+/// and method lookups, which makes it the cheapest way to repeatedly call a
+/// callback held by Rust.
+///
+/// The object stays valid for as long as this handle (and so the `GlobalRef`)
+/// is alive, and can be used from any attached thread. The method id stays
+/// valid for as long as the object's class stays loaded, which the global ref
+/// also guarantees.
+pub struct BoundMethod {
+    obj: GlobalRef,
+    method_id: jmethodID,
+    ret: JavaType,
+}
+
+// The method id is an opaque handle that's valid on any thread.
+unsafe impl Send for BoundMethod {}
+
+impl BoundMethod {
+    /// Bind a method to an object. Nothing is checked here; see `invoke`.
+    pub fn new(obj: GlobalRef, method_id: JMethodID, ret: JavaType) -> Self {
+        BoundMethod {
+            obj: obj,
+            method_id: method_id.into_inner(),
+            ret: ret,
+        }
+    }
+
+    /// Call the method on the bound object.
+    ///
+    /// This is a thin wrapper around `JNIEnv::call_method_unsafe`, with the
+    /// same caveats: the method id must belong to the object's class, the
+    /// return type must match, and `args` must match the method's parameters.
+    pub unsafe fn invoke<'a>(&self, env: &'a JNIEnv<'a>, args: &[JValue]) -> Result<JValue<'a>> {
+        let method_id: JMethodID<'a> = self.method_id.into();
+        env.call_method_unsafe(self.obj.as_obj(), method_id, self.ret.clone(), args)
+    }
+
+    /// Get the bound object.
+    pub fn as_obj<'a>(&'a self) -> JObject<'a> {
+        self.obj.as_obj()
+    }
+
+    /// Get the return type of the bound method.
+    pub fn ret_type(&self) -> &JavaType {
+        &self.ret
+    }
+}
@@ -0,0 +1,142 @@
+use JNIEnv;
+use JavaVM;
+
+use errors::*;
+
+use objects::GlobalRef;
+use objects::JMethodID;
+use objects::JObject;
+use objects::JValue;
+
+use signature::TypeSignature;
+
+use strings::JNIString;
+
+use sys::{
+    jchar,
+    jmethodID,
+};
+
+use wrapper::jnienv::check_args;
+
+/// An argument for `CallbackHandle::invoke`. Unlike `JValue`, it doesn't
+/// borrow from an env, so it can be created on one thread and passed to a
+/// callback on another. `Null` passes a null object.
+#[allow(missing_docs)]
+#[derive(Clone)]
+pub enum OwnedArg {
+    Bool(bool),
+    Byte(i8),
+    Char(jchar),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    Object(GlobalRef),
+    Null,
+}
+
+impl OwnedArg {
+    fn as_jvalue<'a>(&'a self) -> JValue<'a> {
+        match *self {
+            OwnedArg::Bool(b) => JValue::from(b),
+            OwnedArg::Byte(b) => JValue::Byte(b),
+            OwnedArg::Char(c) => JValue::Char(c),
+            OwnedArg::Short(s) => JValue::Short(s),
+            OwnedArg::Int(i) => JValue::Int(i),
+            OwnedArg::Long(l) => JValue::Long(l),
+            OwnedArg::Float(f) => JValue::Float(f),
+            OwnedArg::Double(d) => JValue::Double(d),
+            OwnedArg::Object(ref obj) => JValue::Object(obj.as_obj()),
+            OwnedArg::Null => JValue::Object(JObject::null()),
+        }
+    }
+}
+
+/// A method on a long-lived object that can be called from any rust thread,
+/// attached to the VM or not, e.g. a listener that native event sources
+/// notify.
+///
+/// It holds the `JavaVM`, a `GlobalRef` to the receiver and the method id.
+/// `invoke` uses the env of the current thread if it's already attached;
+/// otherwise it attaches the thread for the duration of the call and detaches
+/// it again afterwards. Threads that fire many callbacks are better attached
+/// once up front, since attaching is slow.
+pub struct CallbackHandle {
+    vm: JavaVM,
+    obj: GlobalRef,
+    method_id: jmethodID,
+    sig: TypeSignature,
+}
+
+// The method id is an opaque handle that's valid on any thread, and `invoke`
+// only reads it, so a handle can be shared between threads too.
+unsafe impl Send for CallbackHandle {}
+unsafe impl Sync for CallbackHandle {}
+
+impl CallbackHandle {
+    /// Look up a method on an object's class and create a handle calling it
+    /// on that object. This creates a new global ref to the object.
+    pub fn new<'a, S>(env: &'a JNIEnv<'a>, obj: JObject<'a>, name: S, sig: &str) -> Result<CallbackHandle>
+    where
+        S: Into<JNIString>,
+    {
+        non_null!(obj, "CallbackHandle::new obj argument");
+        let parsed = TypeSignature::from_str(sig)?;
+        let class = env.auto_local(env.get_object_class(obj)?.into());
+        let method_id: JMethodID = env.get_method_id(&class, name, sig)?;
+
+        Ok(CallbackHandle {
+            vm: env.get_java_vm()?,
+            obj: env.new_global_ref(obj)?,
+            method_id: method_id.into_inner(),
+            sig: parsed,
+        })
+    }
+
+    /// Call the method with `args`, from whatever thread this is. The return
+    /// value is discarded.
+    ///
+    /// The arguments are checked against the signature before the thread is
+    /// attached. If the method throws, the exception is cleared, since
+    /// there's usually no java code up the stack to handle it, and a
+    /// `JavaException` error is returned.
+    pub fn invoke(&self, args: &[OwnedArg]) -> Result<()> {
+        let args: Vec<JValue> = args.iter().map(OwnedArg::as_jvalue).collect();
+        check_args(&self.sig.args, &args)?;
+        match self.vm.get_env() {
+            Ok(env) => self.invoke_with(&env, &args),
+            Err(_) => {
+                let env = self.vm.attach_current_thread()?;
+                self.invoke_with(&env, &args)
+            }
+        }
+    }
+
+    fn invoke_with<'a>(&self, env: &'a JNIEnv<'a>, args: &[JValue]) -> Result<()> {
+        let method_id: JMethodID<'a> = self.method_id.into();
+        let res = unsafe { env.call_method_unsafe(self.obj.as_obj(), method_id, self.sig.ret.clone(), &args) };
+        match res {
+            Ok(ret) => {
+                if let JValue::Object(obj) = ret {
+                    if !obj.is_null() {
+                        env.delete_local_ref(obj)?;
+                    }
+                }
+                Ok(())
+            }
+            Err(e) => {
+                if env.exception_check()? {
+                    env.exception_clear()?;
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Get the object the method is called on.
+    pub fn as_obj<'a>(&'a self) -> JObject<'a> {
+        self.obj.as_obj()
+    }
+}
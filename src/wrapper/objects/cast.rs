@@ -0,0 +1,89 @@
+use JNIEnv;
+
+use errors::*;
+
+use descriptors::ClassCache;
+
+use objects::{
+    JByteBuffer,
+    JClass,
+    JList,
+    JMap,
+    JObject,
+    JString,
+    JThrowable,
+};
+
+/// Object wrappers that a `JObject` can be checked and converted to with
+/// `JObject::cast_to`.
+pub trait JavaCast<'a>: Sized {
+    /// The class, such as `java/lang/String`, that an object must be an
+    /// instance of to be wrapped.
+    fn class_name() -> &'static str;
+
+    /// Wrap an object that's known to be an instance of the class.
+    fn from_checked(env: &'a JNIEnv<'a>, obj: JObject<'a>) -> Result<Self>;
+}
+
+impl<'a> JObject<'a> {
+    /// Convert to a more specific wrapper, checking with `IsInstanceOf` that
+    /// the object is an instance of its class first:
+    ///
+    /// ```rust,ignore
+    /// let s: JString = obj.cast_to(&env)?;
+    /// let list = obj.cast_to::<JList>(&env)?;
+    /// ```
+    ///
+    /// Fails with `WrongObjectType` if it isn't. As with a cast in java, a
+    /// null object can be cast to anything.
+    pub fn cast_to<T: JavaCast<'a>>(self, env: &'a JNIEnv<'a>) -> Result<T> {
+        // shared by all the wrappers, which are few
+        static CLASSES: ClassCache = ClassCache::new();
+        if !env.is_instance_of(self, CLASSES.class(T::class_name()))? {
+            let class = env.auto_local(env.get_object_class(self)?.into());
+            let actual = env.call_string_method(class.as_obj(), "getName", "()Ljava/lang/String;", &[])?
+                .unwrap_or_default();
+            return Err(ErrorKind::WrongObjectType(T::class_name(), actual).into());
+        }
+        T::from_checked(env, self)
+    }
+}
+
+macro_rules! java_cast {
+    ( $ty:ident, $class:expr ) => {
+        impl<'a> JavaCast<'a> for $ty<'a> {
+            fn class_name() -> &'static str {
+                $class
+            }
+
+            fn from_checked(_env: &'a JNIEnv<'a>, obj: JObject<'a>) -> Result<Self> {
+                Ok(obj.into())
+            }
+        }
+    };
+}
+
+java_cast!(JString, "java/lang/String");
+java_cast!(JClass, "java/lang/Class");
+java_cast!(JThrowable, "java/lang/Throwable");
+java_cast!(JByteBuffer, "java/nio/ByteBuffer");
+
+impl<'a> JavaCast<'a> for JList<'a> {
+    fn class_name() -> &'static str {
+        "java/util/List"
+    }
+
+    fn from_checked(env: &'a JNIEnv<'a>, obj: JObject<'a>) -> Result<Self> {
+        JList::from_env(env, obj)
+    }
+}
+
+impl<'a> JavaCast<'a> for JMap<'a> {
+    fn class_name() -> &'static str {
+        "java/util/Map"
+    }
+
+    fn from_checked(env: &'a JNIEnv<'a>, obj: JObject<'a>) -> Result<Self> {
+        JMap::from_env(env, obj)
+    }
+}
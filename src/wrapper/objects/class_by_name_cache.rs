@@ -0,0 +1,65 @@
+use errors::*;
+
+use objects::GlobalRef;
+use objects::JClass;
+use objects::JObject;
+
+use JNIEnv;
+
+/// Global refs to classes, along with their binary name (as returned by
+/// `Class.getName()`, e.g. `java.lang.String`). See `JNIEnv::class_of`.
+///
+/// `class_of` compares classes by identity, so classes with the same name
+/// loaded through different class loaders are kept apart; `get` returns the
+/// first one cached. Lookups scan the cached classes, so the cache is meant
+/// for a handful of them. It never evicts anything, and keeps the classes it
+/// holds from being unloaded.
+#[derive(Default)]
+pub struct ClassByNameCache {
+    classes: Vec<(String, GlobalRef)>,
+}
+
+impl ClassByNameCache {
+    /// Create an empty cache.
+    pub fn new() -> ClassByNameCache {
+        Default::default()
+    }
+
+    /// Get a cached class by name.
+    pub fn get<'c>(&'c self, name: &str) -> Option<JClass<'c>> {
+        self.classes
+            .iter()
+            .find(|&(cached, _)| cached == name)
+            .map(|(_, class)| class.as_obj().into())
+    }
+
+    /// The number of classes in the cache.
+    pub fn len(&self) -> usize {
+        self.classes.len()
+    }
+
+    /// Whether the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.classes.is_empty()
+    }
+
+    /// Drop all cached classes.
+    pub fn clear(&mut self) {
+        self.classes.clear()
+    }
+
+    /// Find the cached global ref to `class`, which is compared by identity.
+    pub(crate) fn find(&self, env: &JNIEnv, class: JObject) -> Result<Option<GlobalRef>> {
+        for (_, cached) in &self.classes {
+            if env.is_same_object(class, cached.as_obj())? {
+                return Ok(Some(cached.clone()));
+            }
+        }
+        Ok(None)
+    }
+
+    pub(crate) fn insert(&mut self, name: String, class: GlobalRef) -> GlobalRef {
+        self.classes.push((name, class.clone()));
+        class
+    }
+}
@@ -5,6 +5,8 @@ use JavaVM;
 use JNIEnv;
 use errors::Result;
 use objects::JObject;
+#[cfg(debug_assertions)]
+use objects::RefType;
 use sys;
 
 
@@ -37,7 +39,10 @@ struct GlobalRefGuard {
 }
 
 
+// Global refs may be used from any thread, concurrently too; the JNI calls
+// taking them are thread safe.
 unsafe impl Send for GlobalRef {}
+unsafe impl Sync for GlobalRef {}
 
 
 impl<'a> From<&'a GlobalRef> for JObject<'a> {
@@ -50,7 +55,19 @@ impl<'a> From<&'a GlobalRef> for JObject<'a> {
 impl GlobalRef {
     /// Creates a new global reference. This assumes that `NewGlobalRef`
     /// has already been called.
+    ///
+    /// In debug builds, this panics if the object is a local or weak ref
+    /// instead, when the current thread is attached to check it.
     pub unsafe fn from_raw(vm: JavaVM, obj: sys::jobject) -> Self {
+        #[cfg(debug_assertions)]
+        {
+            if let Ok(env) = vm.get_env() {
+                if let Ok(ref_type) = env.get_object_ref_type(JObject::from(obj)) {
+                    assert_eq!(ref_type, RefType::Global, "GlobalRef::from_raw called with a non-global ref");
+                }
+            }
+        }
+
         GlobalRef {
             inner: Arc::new(GlobalRefGuard::from_raw(vm, obj)),
         }
@@ -63,6 +80,11 @@ impl GlobalRef {
     pub fn as_obj<'a>(&'a self) -> JObject<'a> {
         self.inner.as_obj()
     }
+
+    /// The VM the global ref belongs to.
+    pub fn java_vm(&self) -> &JavaVM {
+        &self.inner.vm
+    }
 }
 
 
@@ -22,6 +22,12 @@ impl<'a> ::std::ops::Deref for JByteBuffer<'a> {
     }
 }
 
+impl<'a> AsRef<JObject<'a>> for JByteBuffer<'a> {
+    fn as_ref(&self) -> &JObject<'a> {
+        &self.0
+    }
+}
+
 impl<'a> From<JByteBuffer<'a>> for JObject<'a> {
     fn from(other: JByteBuffer) -> JObject {
         other.0
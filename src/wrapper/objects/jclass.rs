@@ -25,6 +25,12 @@ impl<'a> ::std::ops::Deref for JClass<'a> {
     }
 }
 
+impl<'a> AsRef<JObject<'a>> for JClass<'a> {
+    fn as_ref(&self) -> &JObject<'a> {
+        &self.0
+    }
+}
+
 impl<'a> From<JClass<'a>> for JObject<'a> {
     fn from(other: JClass) -> JObject {
         other.0
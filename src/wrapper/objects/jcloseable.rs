@@ -0,0 +1,88 @@
+use std::mem;
+
+use errors::*;
+
+use objects::JObject;
+use objects::JThrowable;
+
+use signature::JavaType;
+use signature::Primitive;
+
+use sys::jthrowable;
+
+use JNIEnv;
+
+/// Closes a `java.lang.AutoCloseable` (a stream, a connection...) when it goes
+/// out of scope, like java's try-with-resources. Get one with
+/// `JNIEnv::auto_closeable`.
+///
+/// If `close()` throws when the guard is dropped, the exception is logged and
+/// cleared. An exception that was already pending at that point is set aside
+/// for the call and thrown again afterwards, so it reaches the java caller
+/// as usual. Use `close` instead to get at the error.
+///
+/// The guard holds on to the env and the local ref it was created with, so it
+/// must be dropped before the native method returns (or before the local
+/// frame holding the ref is popped), on the same thread.
+pub struct JCloseable<'a> {
+    obj: JObject<'a>,
+    env: &'a JNIEnv<'a>,
+}
+
+impl<'a> JCloseable<'a> {
+    /// Wrap an `AutoCloseable` object. You probably want to use
+    /// `JNIEnv::auto_closeable` instead, which checks the object's type.
+    pub fn new(env: &'a JNIEnv<'a>, obj: JObject<'a>) -> Self {
+        JCloseable { obj: obj, env: env }
+    }
+
+    /// Get a reference to the wrapped object.
+    pub fn as_obj<'b>(&'b self) -> JObject<'b>
+    where
+        'a: 'b,
+    {
+        self.obj
+    }
+
+    /// Close the object now, returning any error.
+    pub fn close(self) -> Result<()> {
+        let res = close(self.env, self.obj);
+        mem::forget(self);
+        res
+    }
+
+    /// Forget the wrapper, returning the original object without closing it.
+    pub fn forget(self) -> JObject<'a> {
+        let obj = self.obj;
+        mem::forget(self);
+        obj
+    }
+}
+
+fn close<'a>(env: &'a JNIEnv<'a>, obj: JObject) -> Result<()> {
+    let close = cached_method_id!(env, "java/lang/AutoCloseable", "close", "()V");
+    unsafe { env.call_method_unsafe(obj, close, JavaType::Primitive(Primitive::Void), &[])? }.v()
+}
+
+impl<'a> Drop for JCloseable<'a> {
+    fn drop(&mut self) {
+        let pending = self.env.exception_occurred().map(|ex| ex.into_inner());
+        let pending = match pending {
+            Ok(ex) if !ex.is_null() => {
+                let _ = self.env.exception_clear();
+                Some(ex)
+            }
+            _ => None,
+        };
+
+        if let Err(e) = close(self.env, self.obj) {
+            warn!("error closing java object: {}", e);
+            let _ = self.env.exception_clear();
+        }
+
+        if let Some(ex) = pending {
+            let _ = self.env.throw(JThrowable::from(ex as jthrowable));
+            let _ = self.env.delete_local_ref(ex.into());
+        }
+    }
+}
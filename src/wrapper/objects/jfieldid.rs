@@ -7,13 +7,22 @@ use sys::jfieldID;
 /// under us. It matches C's representation of the raw pointer, so it can be
 /// used in any of the extern function argument positions that would take a
 /// `jfieldid`.
+///
+/// Field ids may be used from any thread. One for a class that's never
+/// unloaded, or that a `GlobalRef` keeps loaded, can be turned into a
+/// `JFieldID<'static>` with `into_inner().into()` and kept in a static.
 #[repr(C)]
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug)]
 pub struct JFieldID<'a> {
     internal: jfieldID,
     lifetime: PhantomData<&'a ()>,
 }
 
+// Field ids are plain values, valid on every thread until their class is
+// unloaded, so they may be cached in statics and shared between threads.
+unsafe impl<'a> Send for JFieldID<'a> {}
+unsafe impl<'a> Sync for JFieldID<'a> {}
+
 impl<'a> From<jfieldID> for JFieldID<'a> {
     fn from(other: jfieldID) -> Self {
         JFieldID {
@@ -0,0 +1,121 @@
+use std::hash::{
+    Hash,
+    Hasher,
+};
+
+use JNIEnv;
+use JavaVM;
+
+use errors::*;
+
+use objects::GlobalRef;
+use objects::JObject;
+
+/// A key for Rust collections that delegates `Hash` to the java object's
+/// `hashCode()` and `Eq` to its `equals(Object)`.
+///
+/// Since `Hash` and `Eq` don't get an env passed in, the key holds onto the
+/// `JavaVM` and uses the env of the current thread, attaching it for the
+/// duration of the call if needed. As with `GlobalRef`, it's best to use these
+/// keys from threads that are already attached, since attaching on every
+/// lookup is slow.
+///
+/// Neither trait can report errors. If `hashCode()` fails, a warning is
+/// logged and the key hashes to `0`; if `equals` fails, a warning is logged
+/// and the keys are compared by identity instead. A java exception thrown by
+/// either call is cleared, since nothing up the stack could handle it and the
+/// next lookup couldn't call into java with it pending.
+pub struct JHashKey {
+    obj: GlobalRef,
+    vm: JavaVM,
+}
+
+impl JHashKey {
+    /// Create a key for an object. This creates a new global ref to it.
+    pub fn new(env: &JNIEnv, obj: JObject) -> Result<JHashKey> {
+        Ok(JHashKey {
+            obj: env.new_global_ref(obj)?,
+            vm: env.get_java_vm()?,
+        })
+    }
+
+    /// Get the object this key wraps.
+    pub fn as_obj<'a>(&'a self) -> JObject<'a> {
+        self.obj.as_obj()
+    }
+
+    /// Get the global ref this key wraps.
+    pub fn global_ref(&self) -> &GlobalRef {
+        &self.obj
+    }
+
+    fn with_env<F, T>(&self, f: F) -> Result<T>
+    where
+        F: for<'e> FnOnce(&'e JNIEnv<'e>) -> Result<T>,
+    {
+        match self.vm.get_env() {
+            Ok(env) => f(&env),
+            Err(_) => {
+                let env = self.vm.attach_current_thread()?;
+                f(&env)
+            }
+        }
+    }
+
+    fn hash_code(&self) -> Result<i32> {
+        self.with_env(|env| {
+            let hash = env.call_method(self.as_obj(), "hashCode", "()I", &[])
+                .and_then(|hash| hash.i());
+            if hash.is_err() {
+                env.exception_clear()?;
+            }
+            hash
+        })
+    }
+
+    fn java_equals(&self, other: &JHashKey) -> Result<bool> {
+        self.with_env(|env| {
+            let eq = env.call_method(
+                self.as_obj(),
+                "equals",
+                "(Ljava/lang/Object;)Z",
+                &[other.as_obj().into()],
+            ).and_then(|eq| eq.z());
+            match eq {
+                Ok(eq) => Ok(eq),
+                Err(e) => {
+                    env.exception_clear()?;
+                    warn!("error calling equals for JHashKey: {}", e);
+                    env.is_same_object(self.as_obj(), other.as_obj())
+                }
+            }
+        })
+    }
+}
+
+impl Hash for JHashKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let hash = match self.hash_code() {
+            Ok(hash) => hash,
+            Err(e) => {
+                warn!("error calling hashCode for JHashKey: {}", e);
+                0
+            }
+        };
+        state.write_i32(hash);
+    }
+}
+
+impl PartialEq for JHashKey {
+    fn eq(&self, other: &JHashKey) -> bool {
+        match self.java_equals(other) {
+            Ok(eq) => eq,
+            Err(e) => {
+                warn!("error comparing JHashKeys: {}", e);
+                ::std::ptr::eq(self, other)
+            }
+        }
+    }
+}
+
+impl Eq for JHashKey {}
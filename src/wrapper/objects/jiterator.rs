@@ -0,0 +1,83 @@
+use JNIEnv;
+
+use errors::*;
+
+use objects::JMethodID;
+use objects::JObject;
+
+use signature::JavaType;
+use signature::Primitive;
+
+/// Wrapper for JObjects that implement `java/util/Iterator`, allowing them to
+/// be used as Rust iterators.
+///
+/// Iteration stops at the first error, such as an exception thrown by
+/// `hasNext` or `next`. The exception is left pending.
+pub struct JIterator<'a> {
+    internal: JObject<'a>,
+    has_next: JMethodID<'a>,
+    next: JMethodID<'a>,
+    env: &'a JNIEnv<'a>,
+}
+
+impl<'a> ::std::ops::Deref for JIterator<'a> {
+    type Target = JObject<'a>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.internal
+    }
+}
+
+impl<'a> From<JIterator<'a>> for JObject<'a> {
+    fn from(other: JIterator) -> JObject {
+        other.internal
+    }
+}
+
+impl<'a> JIterator<'a> {
+    /// Create an iterator from the environment and a `java/util/Iterator`
+    /// object.
+    pub fn from_env(env: &'a JNIEnv<'a>, obj: JObject<'a>) -> Result<JIterator<'a>> {
+        non_null!(obj, "JIterator::from_env obj argument");
+        Ok(JIterator {
+            internal: obj,
+            has_next: cached_method_id!(env, "java/util/Iterator", "hasNext", "()Z"),
+            next: cached_method_id!(env, "java/util/Iterator", "next", "()Ljava/lang/Object;"),
+            env: env,
+        })
+    }
+
+    pub(crate) fn get_next(&self) -> Result<Option<JObject<'a>>> {
+        let has_next = unsafe {
+            self.env.call_method_unsafe(
+                self.internal,
+                self.has_next,
+                JavaType::Primitive(Primitive::Boolean),
+                &[],
+            )?
+        }.z()?;
+
+        if !has_next {
+            return Ok(None);
+        }
+
+        let next = unsafe {
+            self.env.call_method_unsafe(
+                self.internal,
+                self.next,
+                JavaType::Object("java/lang/Object".into()),
+                &[],
+            )?
+        }.l()?;
+
+        Ok(Some(next))
+    }
+}
+
+impl<'a> Iterator for JIterator<'a> {
+    type Item = JObject<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.get_next().unwrap_or_default()
+    }
+}
@@ -2,6 +2,7 @@ use JNIEnv;
 
 use errors::*;
 
+use objects::JClass;
 use objects::JMethodID;
 use objects::JObject;
 
@@ -10,8 +11,8 @@ use sys::jint;
 use signature::JavaType;
 use signature::Primitive;
 
-/// Wrapper for JObjects that implement `java/util/Map`. Provides methods to get
-/// and set entries and a way to iterate over key/value pairs.
+/// Wrapper for JObjects that implement `java/util/List`. Provides methods to
+/// get, add, and remove elements and a way to iterate over them.
 ///
 /// Looks up the class and method ids on creation rather than for every method
 /// call.
@@ -33,6 +34,12 @@ impl<'a> ::std::ops::Deref for JList<'a> {
     }
 }
 
+impl<'a> AsRef<JObject<'a>> for JList<'a> {
+    fn as_ref(&self) -> &JObject<'a> {
+        &self.internal
+    }
+}
+
 impl<'a> From<JList<'a>> for JObject<'a> {
     fn from(other: JList) -> JObject {
         other.internal
@@ -40,11 +47,12 @@ impl<'a> From<JList<'a>> for JObject<'a> {
 }
 
 impl<'a> JList<'a> {
-    /// Create a map from the environment and an object. This looks up the
+    /// Create a list from the environment and an object. This looks up the
     /// necessary class and method ids to call all of the methods on it so that
     /// exra work doesn't need to be done on every method call.
     pub fn from_env(env: &'a JNIEnv<'a>, obj: JObject<'a>) -> Result<JList<'a>> {
-        let class = env.find_class("java/util/List")?;
+        let class = env.auto_local(env.find_class("java/util/List")?.into());
+        let class = JClass::from(class.as_obj());
 
         let get = env.get_method_id(class, "get", "(I)Ljava/lang/Object;")?;
         let add = env.get_method_id(class, "add", "(Ljava/lang/Object;)Z")?;
@@ -63,8 +71,8 @@ impl<'a> JList<'a> {
         })
     }
 
-    /// Look up the value for a key. Returns `Some` if it's found and `None` if
-    /// a null pointer would be returned.
+    /// Get the element at an index. Returns `None` if a null pointer would be
+    /// returned.
     pub fn get(&self, idx: jint) -> Result<Option<JObject>> {
         let result = unsafe {
             self.env.call_method_unsafe(
@@ -175,8 +183,9 @@ impl<'a> JList<'a> {
         }
     }
 
-    /// Get key/value iterator for the map. This is done by getting the
-    /// `EntrySet` from java and iterating over it.
+    /// Get an iterator over the elements of the list. This calls `size()` once
+    /// up front and then `get` for each index, so the list shouldn't change
+    /// while it's being iterated.
     pub fn iter(&'a self) -> Result<JListIter<'a>> {
         Ok(JListIter {
             list: &self,
@@ -186,7 +195,7 @@ impl<'a> JList<'a> {
     }
 }
 
-/// An iterator over the keys and values in a map.
+/// An iterator over the elements in a list.
 ///
 /// TODO: make the iterator implementation for java iterators its own thing
 /// and generic enough to use elsewhere.
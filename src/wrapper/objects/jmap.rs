@@ -20,6 +20,7 @@ pub struct JMap<'a> {
     get: JMethodID<'a>,
     put: JMethodID<'a>,
     remove: JMethodID<'a>,
+    contains_key: JMethodID<'a>,
     env: &'a JNIEnv<'a>,
 }
 
@@ -31,6 +32,12 @@ impl<'a> ::std::ops::Deref for JMap<'a> {
     }
 }
 
+impl<'a> AsRef<JObject<'a>> for JMap<'a> {
+    fn as_ref(&self) -> &JObject<'a> {
+        &self.internal
+    }
+}
+
 impl<'a> From<JMap<'a>> for JObject<'a> {
     fn from(other: JMap) -> JObject {
         other.internal
@@ -53,6 +60,7 @@ impl<'a> JMap<'a> {
         )?;
 
         let remove = env.get_method_id(class, "remove", "(Ljava/lang/Object;)Ljava/lang/Object;")?;
+        let contains_key = env.get_method_id(class, "containsKey", "(Ljava/lang/Object;)Z")?;
 
         Ok(JMap {
             internal: obj,
@@ -60,6 +68,7 @@ impl<'a> JMap<'a> {
             get: get,
             put: put,
             remove: remove,
+            contains_key: contains_key,
             env: env,
         })
     }
@@ -85,7 +94,7 @@ impl<'a> JMap<'a> {
         }
     }
 
-    /// Look up the value for a key. Returns `Some` with the old value if the
+    /// Set the value for a key. Returns `Some` with the old value if the
     /// key already existed and `None` if it's a new key.
     pub fn put(&self, key: JObject<'a>, value: JObject<'a>) -> Result<Option<JObject>> {
         let result = unsafe {
@@ -127,9 +136,24 @@ impl<'a> JMap<'a> {
         }
     }
 
+    /// Check whether the map has an entry for a key. Unlike `get`, this tells
+    /// a key mapped to null apart from a missing one.
+    pub fn contains_key(&self, key: JObject<'a>) -> Result<bool> {
+        let result = unsafe {
+            self.env.call_method_unsafe(
+                self.internal,
+                self.contains_key,
+                JavaType::Primitive(Primitive::Boolean),
+                &[key.into()],
+            )
+        };
+
+        result.and_then(|v| v.z())
+    }
+
     /// Get key/value iterator for the map. This is done by getting the
     /// `EntrySet` from java and iterating over it.
-    pub fn iter(&'a self) -> Result<JMapIter<'a>> {
+    pub fn iter(&self) -> Result<JMapIter<'a>> {
         let set = unsafe {
             let set = self.env.call_method_unsafe(
                 self.internal,
@@ -149,6 +173,7 @@ impl<'a> JMap<'a> {
             )?;
             iter.l()?
         };
+        self.env.delete_local_ref(set)?;
 
         let iter_class = self.env.find_class("java/util/Iterator")?;
 
@@ -166,7 +191,7 @@ impl<'a> JMap<'a> {
             .get_method_id(entry_class, "getValue", "()Ljava/lang/Object;")?;
 
         Ok(JMapIter {
-            map: &self,
+            env: self.env,
             has_next: has_next,
             next: next,
             get_key: get_key,
@@ -181,7 +206,7 @@ impl<'a> JMap<'a> {
 /// TODO: make the iterator implementation for java iterators its own thing
 /// and generic enough to use elsewhere.
 pub struct JMapIter<'a> {
-    map: &'a JMap<'a>,
+    env: &'a JNIEnv<'a>,
     has_next: JMethodID<'a>,
     next: JMethodID<'a>,
     get_key: JMethodID<'a>,
@@ -192,7 +217,7 @@ pub struct JMapIter<'a> {
 impl<'a> JMapIter<'a> {
     fn get_next(&self) -> Result<Option<(JObject<'a>, JObject<'a>)>> {
         let has_next = unsafe {
-            let val = self.map.env.call_method_unsafe(
+            let val = self.env.call_method_unsafe(
                 self.iter,
                 self.has_next,
                 JavaType::Primitive(Primitive::Boolean),
@@ -205,7 +230,7 @@ impl<'a> JMapIter<'a> {
             return Ok(None);
         }
         let next = unsafe {
-            let next = self.map.env.call_method_unsafe(
+            let next = self.env.call_method_unsafe(
                 self.iter,
                 self.next,
                 JavaType::Object("java/util/Map$Entry".into()),
@@ -215,7 +240,7 @@ impl<'a> JMapIter<'a> {
         };
 
         let key = unsafe {
-            let key = self.map.env.call_method_unsafe(
+            let key = self.env.call_method_unsafe(
                 next,
                 self.get_key,
                 JavaType::Object("java/lang/Object".into()),
@@ -225,7 +250,7 @@ impl<'a> JMapIter<'a> {
         };
 
         let value = unsafe {
-            let value = self.map.env.call_method_unsafe(
+            let value = self.env.call_method_unsafe(
                 next,
                 self.get_value,
                 JavaType::Object("java/lang/Object".into()),
@@ -233,6 +258,7 @@ impl<'a> JMapIter<'a> {
             )?;
             value.l()?
         };
+        self.env.delete_local_ref(next)?;
 
         Ok(Some((key, value)))
     }
@@ -7,6 +7,10 @@ use sys::jmethodID;
 /// under us. It matches C's representation of the raw pointer, so it can be
 /// used in any of the extern function argument positions that would take a
 /// `jmethodid`.
+///
+/// Method ids may be used from any thread. One for a class that's never
+/// unloaded, or that a `GlobalRef` keeps loaded, can be turned into a
+/// `JMethodID<'static>` with `into_inner().into()` and kept in a static.
 #[repr(C)]
 #[derive(Copy, Clone, Debug)]
 pub struct JMethodID<'a> {
@@ -14,6 +18,11 @@ pub struct JMethodID<'a> {
     lifetime: PhantomData<&'a ()>,
 }
 
+// Method ids are plain values, valid on every thread until their class is
+// unloaded, so they may be cached in statics and shared between threads.
+unsafe impl<'a> Send for JMethodID<'a> {}
+unsafe impl<'a> Sync for JMethodID<'a> {}
+
 impl<'a> From<jmethodID> for JMethodID<'a> {
     fn from(other: jmethodID) -> Self {
         JMethodID {
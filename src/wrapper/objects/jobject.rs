@@ -34,6 +34,13 @@ impl<'a> ::std::ops::Deref for JObject<'a> {
     }
 }
 
+// So that code generic over `AsRef<JObject>` takes the plain object too.
+impl<'a> AsRef<JObject<'a>> for JObject<'a> {
+    fn as_ref(&self) -> &JObject<'a> {
+        self
+    }
+}
+
 impl<'a> JObject<'a> {
     /// Unwrap to the internal jni type.
     pub fn into_inner(self) -> jobject {
@@ -44,4 +51,10 @@ impl<'a> JObject<'a> {
     pub fn null() -> JObject<'a> {
         (::std::ptr::null_mut() as jobject).into()
     }
+
+    /// Whether this is the null object. Methods of `JNIEnv` taking objects
+    /// return a `NullPtr` error when they're passed one they can't use.
+    pub fn is_null(&self) -> bool {
+        self.internal.is_null()
+    }
 }
@@ -0,0 +1,108 @@
+use JNIEnv;
+
+use errors::*;
+
+use objects::JObject;
+
+use sys::{
+    jobjectArray,
+    jsize,
+};
+
+/// Wrapper for a java object array (`jobjectArray`). Provides methods to get
+/// and set elements and a way to iterate over them.
+///
+/// The length is read once on creation, since java arrays can't be resized.
+pub struct JObjectArray<'a> {
+    internal: jobjectArray,
+    len: jsize,
+    env: &'a JNIEnv<'a>,
+}
+
+impl<'a> From<JObjectArray<'a>> for JObject<'a> {
+    fn from(other: JObjectArray) -> JObject {
+        other.internal.into()
+    }
+}
+
+impl<'a> JObjectArray<'a> {
+    /// Create an array wrapper from the environment and a `jobjectArray`.
+    pub fn from_env(env: &'a JNIEnv<'a>, array: jobjectArray) -> Result<JObjectArray<'a>> {
+        non_null!(array, "JObjectArray::from_env array argument");
+        Ok(JObjectArray {
+            internal: array,
+            len: env.get_array_length(array)?,
+            env: env,
+        })
+    }
+
+    /// Extract the raw array pointer.
+    pub fn into_inner(self) -> jobjectArray {
+        self.internal
+    }
+
+    /// Get the length of the array
+    pub fn len(&self) -> jsize {
+        self.len
+    }
+
+    /// Whether the array has no elements
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get the element at an index as a new local ref. Unlike
+    /// `JNIEnv::get_object_array_element`, a null element is returned as a
+    /// null `JObject` rather than an error.
+    pub fn get(&self, index: jsize) -> Result<JObject<'a>> {
+        let internal = self.env.get_native_interface();
+        let elem = unsafe {
+            let elem = jni_unchecked!(internal, GetObjectArrayElement, self.internal, index);
+            check_exception!(internal);
+            elem
+        };
+        Ok(JObject::from(elem))
+    }
+
+    /// Set the element at an index. `value` may be null.
+    pub fn set(&self, index: jsize, value: JObject) -> Result<()> {
+        self.env.set_object_array_element(self.internal, index, value)
+    }
+
+    /// Get an iterator over the elements of the array. Each element is a new
+    /// local ref.
+    pub fn iter<'b>(&'b self) -> JObjectArrayIter<'b, 'a> {
+        JObjectArrayIter {
+            array: self,
+            current: 0,
+        }
+    }
+}
+
+/// An iterator over the elements of an object array.
+///
+/// Iteration stops at the first error; the exception is left pending.
+pub struct JObjectArrayIter<'a, 'b: 'a> {
+    array: &'a JObjectArray<'b>,
+    current: jsize,
+}
+
+impl<'a, 'b> Iterator for JObjectArrayIter<'a, 'b> {
+    type Item = JObject<'b>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.current >= self.array.len {
+            return None;
+        }
+        match self.array.get(self.current) {
+            Ok(elem) => {
+                self.current += 1;
+                Some(elem)
+            }
+            Err(_) => {
+                self.current = self.array.len;
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use std::sync::RwLock;
+
+use JNIEnv;
+
+use errors::*;
+
+use descriptors::Desc;
+
+use exceptions::CLASS_CAST_EXCEPTION;
+
+use objects::GlobalRef;
+use objects::JClass;
+use objects::JMethodID;
+use objects::JObject;
+
+use signature::JavaType;
+use signature::Primitive;
+
+use sys::jmethodID;
+
+/// Method ids for a java "result" class: a success/error union with a boolean
+/// `isOk`-style accessor and getters for the value and the error. Used to
+/// turn such objects into a Rust `Result`.
+///
+/// Looks up the method ids on creation rather than for every conversion, so
+/// create one of these per result class and reuse it. Holds a `GlobalRef` to
+/// the class, to check the objects it's given against.
+pub struct JResultAccessors<'a> {
+    class: GlobalRef,
+    is_ok: JMethodID<'a>,
+    value: JMethodID<'a>,
+    error: JMethodID<'a>,
+    env: &'a JNIEnv<'a>,
+}
+
+impl<'a> JResultAccessors<'a> {
+    /// Look up the accessors on a result class. `is_ok` must name a `()Z`
+    /// method; `value` and `error` must name public no-argument methods that
+    /// return an object of any type.
+    pub fn from_env<'c, T>(
+        env: &'a JNIEnv<'a>,
+        class: T,
+        is_ok: &str,
+        value: &str,
+        error: &str,
+    ) -> Result<JResultAccessors<'a>>
+    where
+        T: Desc<'a, JClass<'c>>,
+    {
+        // Looking the class up by name makes a local ref, which goes with the
+        // frame. Nothing else made here outlives it but the global ref.
+        env.push_local_frame(16)?;
+        let accessors = catch!({
+            let class = class.lookup(env)?;
+
+            Ok(JResultAccessors {
+                class: env.new_global_ref(class.into())?,
+                is_ok: env.get_method_id(class, is_ok, "()Z")?,
+                value: object_getter(env, class, value)?,
+                error: object_getter(env, class, error)?,
+                env: env,
+            })
+        });
+        env.pop_local_frame(JObject::null())?;
+        accessors
+    }
+
+    /// Convert a result object: `Ok` with the value if `is_ok` returns true,
+    /// `Err` with the error otherwise. Either may be a null `JObject`.
+    ///
+    /// If `obj` isn't an instance of the result class, a
+    /// `ClassCastException` is thrown and a `JavaException` error returned.
+    pub fn unwrap(
+        &self,
+        obj: JObject<'a>,
+    ) -> Result<::std::result::Result<JObject<'a>, JObject<'a>>> {
+        non_null!(obj, "JResultAccessors::unwrap obj argument");
+        if !self.env.is_instance_of(obj, JClass::from(self.class.as_obj()))? {
+            self.env.throw_new(CLASS_CAST_EXCEPTION, "object is not an instance of the result class")?;
+            return Err(ErrorKind::JavaException.into());
+        }
+        self.call(obj)
+    }
+
+    /// Convert a result object as `JNIEnv::unwrap_result` does, with the
+    /// accessors looked up on its class the first time they're asked for, and
+    /// cached after that.
+    pub(crate) fn unwrap_cached(
+        env: &'a JNIEnv<'a>,
+        obj: JObject<'a>,
+        is_ok: &str,
+        value: &str,
+        error: &str,
+    ) -> Result<::std::result::Result<JObject<'a>, JObject<'a>>> {
+        let names = [is_ok, value, error];
+        {
+            let cached = CACHED.read().unwrap();
+            for entry in cached.iter().filter(|entry| entry.names == names) {
+                if env.is_instance_of(obj, JClass::from(entry.class.as_obj()))? {
+                    let accessors = JResultAccessors {
+                        class: entry.class.clone(),
+                        is_ok: JMethodID::from(entry.ids[0] as jmethodID),
+                        value: JMethodID::from(entry.ids[1] as jmethodID),
+                        error: JMethodID::from(entry.ids[2] as jmethodID),
+                        env: env,
+                    };
+                    return accessors.call(obj);
+                }
+            }
+        }
+
+        let class = env.auto_local(env.get_object_class(obj)?.into());
+        let accessors = JResultAccessors::from_env(env, &class, is_ok, value, error)?;
+        CACHED.write().unwrap().push(CachedAccessors {
+            class: accessors.class.clone(),
+            names: [is_ok.to_owned(), value.to_owned(), error.to_owned()],
+            ids: [
+                accessors.is_ok.into_inner() as usize,
+                accessors.value.into_inner() as usize,
+                accessors.error.into_inner() as usize,
+            ],
+        });
+        accessors.call(obj)
+    }
+
+    fn call(&self, obj: JObject<'a>) -> Result<::std::result::Result<JObject<'a>, JObject<'a>>> {
+        let is_ok = unsafe {
+            self.env.call_method_unsafe(
+                obj,
+                self.is_ok,
+                JavaType::Primitive(Primitive::Boolean),
+                &[],
+            )?
+        }.z()?;
+
+        let getter = if is_ok { self.value } else { self.error };
+        let val = unsafe {
+            self.env.call_method_unsafe(
+                obj,
+                getter,
+                JavaType::Object("java/lang/Object".into()),
+                &[],
+            )?
+        }.l()?;
+
+        Ok(if is_ok { Ok(val) } else { Err(val) })
+    }
+}
+
+// The accessors `JNIEnv::unwrap_result` has looked up so far, with the
+// classes they were looked up on. The global refs keep the classes, and so
+// the method ids, alive.
+static CACHED: RwLock<Vec<CachedAccessors>> = RwLock::new(Vec::new());
+
+struct CachedAccessors {
+    class: GlobalRef,
+    names: [String; 3],
+    // method ids aren't `Sync`, but can be used from any thread
+    ids: [usize; 3],
+}
+
+/// Find a public no-argument method by name alone, as long as it returns an
+/// object. This goes through `Class.getMethod` since the exact return type
+/// isn't known, which `GetMethodID` would need.
+fn object_getter<'a>(env: &'a JNIEnv<'a>, class: JClass, name: &str) -> Result<JMethodID<'a>> {
+    let name_str = env.auto_local(env.new_string(name)?.into());
+    let no_params = env.new_object_array(0, "java/lang/Class", JObject::null())?;
+    let no_params = env.auto_local(JObject::from(no_params));
+
+    let method = env.call_method(
+        class.into(),
+        "getMethod",
+        "(Ljava/lang/String;[Ljava/lang/Class;)Ljava/lang/reflect/Method;",
+        &[name_str.as_obj().into(), no_params.as_obj().into()],
+    )?.l()?;
+    let method = env.auto_local(method);
+
+    let ret = env.call_method(method.as_obj(), "getReturnType", "()Ljava/lang/Class;", &[])?
+        .l()?;
+    let ret = env.auto_local(ret);
+    if env.call_method(ret.as_obj(), "isPrimitive", "()Z", &[])?.z()? {
+        let class_name = env.class_name(class.into())?;
+        return Err(ErrorKind::MethodNotFound(class_name, name.into(), "()Ljava/lang/Object;".into()).into());
+    }
+
+    env.from_reflected_method(method.as_obj())
+}
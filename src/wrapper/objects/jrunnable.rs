@@ -0,0 +1,48 @@
+use JNIEnv;
+
+use errors::*;
+
+use objects::GlobalRef;
+use objects::JObject;
+
+use signature::JavaType;
+use signature::Primitive;
+
+/// A `java.lang.Runnable` kept around by Rust, to be run later, e.g. a task
+/// callback handed to a native scheduler.
+///
+/// This holds a `GlobalRef`, so it can be stored anywhere and sent to other
+/// threads. Running it needs the env of the current thread, which must be
+/// attached to the VM. The `run()` implementation itself may of course have
+/// threading requirements of its own.
+#[derive(Clone)]
+pub struct JRunnable {
+    obj: GlobalRef,
+}
+
+impl JRunnable {
+    /// Wrap a runnable object, creating a new global ref to it.
+    pub fn new(env: &JNIEnv, obj: JObject) -> Result<JRunnable> {
+        non_null!(obj, "JRunnable::new obj argument");
+        if !env.is_instance_of(obj, "java/lang/Runnable")? {
+            return Err("JRunnable::new obj argument is not a Runnable".into());
+        }
+        Ok(JRunnable {
+            obj: env.new_global_ref(obj)?,
+        })
+    }
+
+    /// Call `run()` on the wrapped object. An exception thrown by it is left
+    /// pending and returned as a `JavaException` error.
+    pub fn run(&self, env: &JNIEnv) -> Result<()> {
+        let run = cached_method_id!(env, "java/lang/Runnable", "run", "()V");
+        unsafe {
+            env.call_method_unsafe(self.obj.as_obj(), run, JavaType::Primitive(Primitive::Void), &[])?
+        }.v()
+    }
+
+    /// Get the wrapped object.
+    pub fn as_obj<'a>(&'a self) -> JObject<'a> {
+        self.obj.as_obj()
+    }
+}
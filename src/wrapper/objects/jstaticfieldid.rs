@@ -7,13 +7,20 @@ use sys::jfieldID;
 /// from under us. It matches C's representation of the raw pointer, so it can
 /// be used in any of the extern function argument positions that would take a
 /// `jstaticfieldid`.
+///
+/// Like a `JFieldID`, it can be cached in a static and used from any thread.
 #[repr(C)]
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug)]
 pub struct JStaticFieldID<'a> {
     internal: jfieldID,
     lifetime: PhantomData<&'a ()>,
 }
 
+// Like field ids, static field ids are valid on every thread until their
+// class is unloaded.
+unsafe impl<'a> Send for JStaticFieldID<'a> {}
+unsafe impl<'a> Sync for JStaticFieldID<'a> {}
+
 impl<'a> From<jfieldID> for JStaticFieldID<'a> {
     fn from(other: jfieldID) -> Self {
         JStaticFieldID {
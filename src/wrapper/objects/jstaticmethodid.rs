@@ -9,13 +9,20 @@ use sys::jmethodID;
 /// used in any of the extern function argument positions that would take a
 /// `jmethodid`. This represents static methods only since they require a
 /// different set of JNI signatures.
+///
+/// Like a `JMethodID`, it can be cached in a static and used from any thread.
 #[repr(C)]
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug)]
 pub struct JStaticMethodID<'a> {
     internal: jmethodID,
     lifetime: PhantomData<&'a ()>,
 }
 
+// Like method ids, static method ids are valid on every thread until their
+// class is unloaded.
+unsafe impl<'a> Send for JStaticMethodID<'a> {}
+unsafe impl<'a> Sync for JStaticMethodID<'a> {}
+
 impl<'a> From<jmethodID> for JStaticMethodID<'a> {
     fn from(other: jmethodID) -> Self {
         JStaticMethodID {
@@ -25,6 +25,12 @@ impl<'a> ::std::ops::Deref for JString<'a> {
     }
 }
 
+impl<'a> AsRef<JObject<'a>> for JString<'a> {
+    fn as_ref(&self) -> &JObject<'a> {
+        &self.0
+    }
+}
+
 impl<'a> From<JString<'a>> for JObject<'a> {
     fn from(other: JString) -> JObject {
         other.0
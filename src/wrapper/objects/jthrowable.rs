@@ -24,6 +24,12 @@ impl<'a> ::std::ops::Deref for JThrowable<'a> {
     }
 }
 
+impl<'a> AsRef<JObject<'a>> for JThrowable<'a> {
+    fn as_ref(&self) -> &JObject<'a> {
+        &self.0
+    }
+}
+
 impl<'a> From<JThrowable<'a>> for JObject<'a> {
     fn from(other: JThrowable) -> JObject {
         other.0
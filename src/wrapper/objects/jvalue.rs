@@ -1,3 +1,4 @@
+use std::convert::TryFrom;
 use std::mem::transmute;
 
 use signature::Primitive;
@@ -237,3 +238,29 @@ impl<'a> From<()> for JValue<'a> {
         JValue::Void
     }
 }
+
+// The reverse conversions, so that call results can be unwrapped with
+// `try_into()?`. They fail with `ErrorKind::WrongJValueType` just like the
+// accessors they use.
+macro_rules! try_from_jvalue {
+    ( $rust_type:ty, $accessor:ident ) => {
+        impl<'a> TryFrom<JValue<'a>> for $rust_type {
+            type Error = Error;
+
+            fn try_from(value: JValue<'a>) -> Result<Self> {
+                value.$accessor()
+            }
+        }
+    };
+}
+
+try_from_jvalue!(JObject<'a>, l);
+try_from_jvalue!(bool, z);
+try_from_jvalue!(jbyte, b);
+try_from_jvalue!(jchar, c);
+try_from_jvalue!(jshort, s);
+try_from_jvalue!(jint, i);
+try_from_jvalue!(jlong, j);
+try_from_jvalue!(jfloat, f);
+try_from_jvalue!(jdouble, d);
+try_from_jvalue!((), v);
@@ -32,13 +32,82 @@ pub use self::jmap::*;
 mod jlist;
 pub use self::jlist::*;
 
+mod jiterator;
+pub use self::jiterator::*;
+
+mod jobject_array;
+pub use self::jobject_array::*;
+
 mod jbytebuffer;
 pub use self::jbytebuffer::*;
 
+// For checked conversions between the object wrappers
+mod cast;
+pub use self::cast::*;
+
+mod jresult;
+pub use self::jresult::*;
+
+// Rust types with a fixed java type, for the `call!` macro
+mod typed_call;
+pub use self::typed_call::*;
+
+// For telling local, global and weak global refs apart
+mod ref_type;
+pub use self::ref_type::*;
+
 // For storing a reference to a java object
 mod global_ref;
 pub use self::global_ref::*;
 
+// For passing a java object to another thread
+mod owned_object;
+pub use self::owned_object::*;
+
+// For referring to a java object without keeping it alive
+mod weak_ref;
+pub use self::weak_ref::*;
+
+// For calling a method on a stored object without any lookups
+mod bound_method;
+pub use self::bound_method::*;
+
+// For calling static factory methods without any lookups
+mod static_factory;
+pub use self::static_factory::*;
+
+// For calling java from any rust thread
+mod callback_handle;
+pub use self::callback_handle::*;
+
+// For looking up classes of objects without piling up local refs
+mod class_by_name_cache;
+pub use self::class_by_name_cache::*;
+
+// For binding rust functions to java native methods
+mod native_method;
+pub use self::native_method::*;
+
+// For running java callbacks from rust
+mod jrunnable;
+pub use self::jrunnable::*;
+
+// For using java objects as keys in rust collections
+mod jhash_key;
+pub use self::jhash_key::*;
+
 // For automatic local ref deletion
 mod auto_local;
 pub use self::auto_local::*;
+
+// For accessing the elements of primitive arrays
+mod auto_array;
+pub use self::auto_array::*;
+
+// For accessing primitive arrays without copying, in a critical section
+mod auto_primitive_array_critical;
+pub use self::auto_primitive_array_critical::*;
+
+// For closing java resources on scope exit
+mod jcloseable;
+pub use self::jcloseable::*;
@@ -0,0 +1,68 @@
+use std::os::raw::c_void;
+
+use errors::*;
+
+use signature::TypeSignature;
+
+use strings::JNIString;
+
+use sys;
+
+/// A rust function to bind to a java `native` method with
+/// `JNIEnv::register_native_methods`, as an alternative to relying on the
+/// `Java_<class>_<method>` name mangling.
+///
+/// ```rust,ignore
+/// extern "system" fn add(_env: JNIEnv, _class: JClass, a: jint, b: jint) -> jint {
+///     a + b
+/// }
+///
+/// let add = unsafe { NativeMethod::new("add", "(II)I", add as *mut c_void)? };
+/// env.register_native_methods("com/example/Native", &[add])?;
+/// ```
+pub struct NativeMethod {
+    name: JNIString,
+    sig: JNIString,
+    fn_ptr: *mut c_void,
+}
+
+impl NativeMethod {
+    /// Describe a native method by name and signature. Fails if `sig` isn't a
+    /// valid method signature.
+    ///
+    /// This is unsafe because nothing checks `fn_ptr`: it must be an
+    /// `extern "system" fn` taking a `JNIEnv` and a `JObject` (or `JClass`,
+    /// for static methods) followed by arguments matching `sig`, and
+    /// returning the matching type.
+    pub unsafe fn new<N, S>(name: N, sig: S, fn_ptr: *mut c_void) -> Result<NativeMethod>
+    where
+        N: Into<JNIString>,
+        S: Into<JNIString> + AsRef<str>,
+    {
+        TypeSignature::from_str(sig.as_ref())?;
+
+        Ok(NativeMethod {
+            name: name.into(),
+            sig: sig.into(),
+            fn_ptr: fn_ptr,
+        })
+    }
+
+    /// The name of the java method.
+    pub fn name(&self) -> &JNIString {
+        &self.name
+    }
+
+    /// The signature of the java method.
+    pub fn signature(&self) -> &JNIString {
+        &self.sig
+    }
+
+    pub(crate) fn to_jni(&self) -> sys::JNINativeMethod {
+        sys::JNINativeMethod {
+            name: self.name.as_ptr() as *mut _,
+            signature: self.sig.as_ptr() as *mut _,
+            fnPtr: self.fn_ptr,
+        }
+    }
+}
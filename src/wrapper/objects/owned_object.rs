@@ -0,0 +1,79 @@
+use JavaVM;
+use JNIEnv;
+
+use errors::Result;
+
+use objects::GlobalRef;
+use objects::JObject;
+
+/// An object that can be handed to another thread, e.g. to a rust thread
+/// pool, and used there. Unlike `JObject`, which is only valid on the thread
+/// and in the local frame it came from, this holds a `GlobalRef` along with
+/// the `JavaVM`, and gives out new local refs on whatever thread it ends up.
+///
+/// ```rust,ignore
+/// let listener = OwnedObject::new(&env, listener)?;
+/// thread::spawn(move || {
+///     listener.with_attached(|env, listener| {
+///         env.call_method(listener, "onDone", "()V", &[])?;
+///         Ok(())
+///     })
+/// });
+/// ```
+#[derive(Clone)]
+pub struct OwnedObject {
+    global: GlobalRef,
+}
+
+impl OwnedObject {
+    /// Take a new global ref to the object.
+    pub fn new(env: &JNIEnv, obj: JObject) -> Result<Self> {
+        Ok(OwnedObject {
+            global: env.new_global_ref(obj)?,
+        })
+    }
+
+    /// Wrap an existing global ref.
+    pub fn from_global(global: GlobalRef) -> Self {
+        OwnedObject { global: global }
+    }
+
+    /// Get a local ref to the object for use with the env of the current
+    /// thread. The local ref lives until it's deleted or the current local
+    /// frame ends, independently of `self`.
+    pub fn as_obj<'a>(&self, env: &'a JNIEnv<'a>) -> Result<JObject<'a>> {
+        env.new_local_ref::<JObject>(self.global.as_obj())
+    }
+
+    /// The global ref behind the object.
+    pub fn global_ref(&self) -> &GlobalRef {
+        &self.global
+    }
+
+    /// The VM the object belongs to.
+    pub fn java_vm(&self) -> &JavaVM {
+        self.global.java_vm()
+    }
+
+    /// Run the closure with the env of the current thread, attaching it for
+    /// the duration of the call if needed, and a local ref to the object.
+    /// The local refs created by the closure, that one included, are freed
+    /// when it returns.
+    pub fn with_attached<F, R>(&self, f: F) -> Result<R>
+    where
+        F: for<'a> FnOnce(&'a JNIEnv<'a>, JObject<'a>) -> Result<R>,
+    {
+        let env = self.java_vm().attach_current_thread()?;
+
+        env.push_local_frame(16)?;
+        let res = self.as_obj(&env).and_then(|obj| f(&env, obj));
+        env.pop_local_frame(JObject::null())?;
+        res
+    }
+}
+
+impl From<GlobalRef> for OwnedObject {
+    fn from(global: GlobalRef) -> Self {
+        OwnedObject::from_global(global)
+    }
+}
@@ -0,0 +1,28 @@
+use sys::jobjectRefType;
+
+/// The kind of a reference to a java object, as returned by
+/// `JNIEnv::get_object_ref_type`.
+///
+/// This maps to the `jni_sys::jobjectRefType` variants.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RefType {
+    /// Not a valid reference, e.g. a local ref that was already deleted.
+    Invalid,
+    /// A local ref, valid until it's deleted or its local frame ends.
+    Local,
+    /// A global ref, see `GlobalRef`.
+    Global,
+    /// A weak global ref, see `WeakRef`.
+    WeakGlobal,
+}
+
+impl From<jobjectRefType> for RefType {
+    fn from(other: jobjectRefType) -> Self {
+        match other {
+            jobjectRefType::JNILocalRefType => RefType::Local,
+            jobjectRefType::JNIGlobalRefType => RefType::Global,
+            jobjectRefType::JNIWeakGlobalRefType => RefType::WeakGlobal,
+            _ => RefType::Invalid,
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use JNIEnv;
+
+use descriptors::Desc;
+
+use errors::*;
+
+use objects::GlobalRef;
+use objects::JClass;
+use objects::JObject;
+use objects::JStaticMethodID;
+use objects::JValue;
+
+use signature::JavaType;
+use signature::TypeSignature;
+
+use strings::JNIString;
+
+use sys::jmethodID;
+
+use wrapper::jnienv::check_args;
+
+/// A static method returning an object, such as `Optional.of` or `List.of`,
+/// looked up once and kept ready to call. Holds a `GlobalRef` to the class,
+/// which keeps it loaded and so the method id valid.
+///
+/// The handle can be kept around for the life of the program and used from
+/// any attached thread.
+pub struct StaticFactory {
+    class: GlobalRef,
+    method_id: jmethodID,
+    sig: TypeSignature,
+}
+
+// The method id is an opaque handle that's valid on any thread, and calls
+// only read it, so a factory can be shared between threads too.
+unsafe impl Send for StaticFactory {}
+unsafe impl Sync for StaticFactory {}
+
+impl StaticFactory {
+    /// Look up a static factory method. The signature must return an object
+    /// or an array.
+    pub fn new<'a, 'c, T, S>(env: &'a JNIEnv<'a>, class: T, name: S, sig: &str) -> Result<StaticFactory>
+    where
+        T: Desc<'a, JClass<'c>>,
+        S: Into<JNIString>,
+    {
+        let parsed = TypeSignature::from_str(sig)?;
+        match parsed.ret {
+            JavaType::Object(_) | JavaType::Array(_) => {}
+            _ => return Err(format!("static factory {} doesn't return an object", sig).into()),
+        }
+
+        let class = class.lookup(env)?;
+        let method_id = env.get_static_method_id(class, name, sig)?;
+        Ok(StaticFactory {
+            class: env.new_global_ref(class.into())?,
+            method_id: method_id.into_inner(),
+            sig: parsed,
+        })
+    }
+
+    /// Call the factory. `args` are checked against the parameters of the
+    /// signature given to `new`.
+    pub fn create<'a>(&self, env: &'a JNIEnv<'a>, args: &[JValue]) -> Result<JObject<'a>> {
+        check_args(&self.sig.args, args)?;
+        let method_id: JStaticMethodID<'a> = self.method_id.into();
+        // the result doesn't borrow from the class, which `self` keeps alive
+        // for the duration of the call anyway
+        let class: JClass<'a> = JClass::from(self.class.as_obj().into_inner());
+        unsafe { env.call_static_method_unsafe(class, method_id, self.sig.ret.clone(), args) }?
+            .l()
+    }
+
+    /// Get the class the factory belongs to.
+    pub fn class<'a>(&'a self) -> JClass<'a> {
+        JClass::from(self.class.as_obj())
+    }
+
+    /// Get the signature of the factory method.
+    pub fn signature(&self) -> &TypeSignature {
+        &self.sig
+    }
+}
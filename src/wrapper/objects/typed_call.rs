@@ -0,0 +1,155 @@
+use JNIEnv;
+
+use errors::*;
+
+use objects::JClass;
+use objects::JObject;
+use objects::JString;
+use objects::JThrowable;
+use objects::JValue;
+
+use signature::JavaType;
+
+use sys::{
+    jbyte,
+    jchar,
+    jdouble,
+    jfloat,
+    jint,
+    jlong,
+    jobject,
+    jshort,
+};
+
+/// A rust type that maps to exactly one java type, and so can be passed as an
+/// argument to a `call!` without spelling out its signature.
+pub trait JavaArg<'a> {
+    /// The java type descriptor, e.g. `I` or `Ljava/lang/String;`.
+    fn descriptor() -> &'static str;
+
+    /// Convert to a `JValue` to pass to java.
+    fn into_jvalue(self) -> JValue<'a>;
+}
+
+/// A rust type that maps to exactly one java type, and so can be used as the
+/// return type of a `call!`.
+pub trait JavaReturn<'a>: Sized {
+    /// The java type descriptor, e.g. `I` or `Ljava/lang/String;`.
+    fn descriptor() -> &'static str;
+
+    /// Extract the value from what the method returned.
+    fn from_jvalue(val: JValue<'a>) -> Result<Self>;
+}
+
+macro_rules! impl_java_primitive {
+    ( $ty:ty, $desc:expr, $getter:ident ) => {
+        impl<'a> JavaArg<'a> for $ty {
+            fn descriptor() -> &'static str {
+                $desc
+            }
+
+            fn into_jvalue(self) -> JValue<'a> {
+                self.into()
+            }
+        }
+
+        impl<'a> JavaReturn<'a> for $ty {
+            fn descriptor() -> &'static str {
+                $desc
+            }
+
+            fn from_jvalue(val: JValue<'a>) -> Result<Self> {
+                val.$getter()
+            }
+        }
+    };
+}
+
+impl_java_primitive!(bool, "Z", z);
+impl_java_primitive!(jbyte, "B", b);
+impl_java_primitive!(jchar, "C", c);
+impl_java_primitive!(jshort, "S", s);
+impl_java_primitive!(jint, "I", i);
+impl_java_primitive!(jlong, "J", j);
+impl_java_primitive!(jfloat, "F", f);
+impl_java_primitive!(jdouble, "D", d);
+
+impl<'a> JavaReturn<'a> for () {
+    fn descriptor() -> &'static str {
+        "V"
+    }
+
+    fn from_jvalue(val: JValue<'a>) -> Result<Self> {
+        val.v()
+    }
+}
+
+macro_rules! impl_java_object {
+    ( $ty:ident, $desc:expr ) => {
+        impl<'a> JavaArg<'a> for $ty<'a> {
+            fn descriptor() -> &'static str {
+                $desc
+            }
+
+            fn into_jvalue(self) -> JValue<'a> {
+                JValue::Object(self.into())
+            }
+        }
+
+        impl<'a, 'b> JavaArg<'a> for &'b $ty<'a> {
+            fn descriptor() -> &'static str {
+                $desc
+            }
+
+            fn into_jvalue(self) -> JValue<'a> {
+                JValue::Object(JObject::from(self.into_inner() as jobject))
+            }
+        }
+
+        impl<'a> JavaReturn<'a> for $ty<'a> {
+            fn descriptor() -> &'static str {
+                $desc
+            }
+
+            fn from_jvalue(val: JValue<'a>) -> Result<Self> {
+                Ok(val.l()?.into())
+            }
+        }
+    };
+}
+
+impl_java_object!(JObject, "Ljava/lang/Object;");
+impl_java_object!(JString, "Ljava/lang/String;");
+impl_java_object!(JClass, "Ljava/lang/Class;");
+impl_java_object!(JThrowable, "Ljava/lang/Throwable;");
+
+/// Implementation of the `call!` macro. The argument types have already been
+/// checked by the compiler at this point; all that's left is to put the
+/// signature together and make the call.
+#[doc(hidden)]
+pub fn call_typed<'a, R>(
+    env: &'a JNIEnv<'a>,
+    obj: JObject<'a>,
+    class: &str,
+    name: &str,
+    arg_descriptors: &[&str],
+    args: &[JValue<'a>],
+) -> Result<R>
+where
+    R: JavaReturn<'a>,
+{
+    non_null!(obj, "call! obj argument");
+
+    // the method is looked up in `class` rather than in the object's own
+    // class, so check that the object is one
+    env.check_receiver(obj, class)?;
+
+    let sig = format!("({}){}", arg_descriptors.concat(), R::descriptor());
+    let class = env.find_class(class)?;
+    let method_id = env.get_method_id(class, name, sig.as_str());
+    env.delete_local_ref(class.into())?;
+
+    let ret = JavaType::from_str(R::descriptor())?;
+    let val = unsafe { env.call_method_unsafe(obj, method_id?, ret, args)? };
+    R::from_jvalue(val)
+}
@@ -0,0 +1,120 @@
+use std::ptr;
+use std::sync::Arc;
+
+use JavaVM;
+use JNIEnv;
+
+use errors::Result;
+
+use objects::JObject;
+#[cfg(debug_assertions)]
+use objects::RefType;
+
+use sys;
+
+/// A weak global JVM reference. Unlike a `GlobalRef`, this doesn't keep the
+/// object from being garbage collected, so it has to be upgraded to a local
+/// reference with `upgrade` before it can be used.
+///
+/// Like `GlobalRef`, it can be cloned to share _the same_ weak reference, and
+/// may be sent to and used from other threads. The underlying weak reference
+/// is deleted when the last clone is dropped, with the same caveat about
+/// dropping it from a detached thread.
+#[derive(Clone)]
+pub struct WeakRef {
+    inner: Arc<WeakRefGuard>
+}
+
+struct WeakRefGuard {
+    raw: sys::jweak,
+    vm: JavaVM,
+}
+
+// Weak global refs are valid on every thread, just like global refs.
+unsafe impl Send for WeakRef {}
+unsafe impl Sync for WeakRef {}
+
+impl WeakRef {
+    /// Creates a new weak reference. This assumes that `NewWeakGlobalRef`
+    /// has already been called, which is checked in debug builds like for
+    /// `GlobalRef::from_raw`.
+    pub unsafe fn from_raw(vm: JavaVM, raw: sys::jweak) -> Self {
+        #[cfg(debug_assertions)]
+        {
+            if let Ok(env) = vm.get_env() {
+                if let Ok(ref_type) = env.get_object_ref_type(JObject::from(raw)) {
+                    assert_eq!(ref_type, RefType::WeakGlobal, "WeakRef::from_raw called with a non-weak ref");
+                }
+            }
+        }
+
+        WeakRef {
+            inner: Arc::new(WeakRefGuard { raw: raw, vm: vm }),
+        }
+    }
+
+    /// Get a local reference to the object, or `None` if it has already
+    /// been garbage collected.
+    ///
+    /// The returned local reference keeps the object alive until it's
+    /// deleted or the current local frame ends.
+    pub fn upgrade<'a>(&self, env: &'a JNIEnv<'a>) -> Result<Option<JObject<'a>>> {
+        if self.is_garbage_collected(env)? {
+            return Ok(None);
+        }
+
+        // The object may still be collected between the check above and
+        // taking the new reference, in which case the new ref is null.
+        let internal = env.get_native_interface();
+        let local = unsafe {
+            let local = jni_unchecked!(internal, NewLocalRef, self.inner.raw);
+            check_exception!(internal);
+            local
+        };
+
+        if local.is_null() {
+            Ok(None)
+        } else {
+            Ok(Some(JObject::from(local)))
+        }
+    }
+
+    /// Check whether the object this reference points to has been garbage
+    /// collected.
+    pub fn is_garbage_collected(&self, env: &JNIEnv) -> Result<bool> {
+        let internal = env.get_native_interface();
+        let same = unsafe {
+            let same = jni_unchecked!(internal, IsSameObject, self.inner.raw, ptr::null_mut());
+            check_exception!(internal);
+            same
+        };
+        Ok(same == sys::JNI_TRUE)
+    }
+}
+
+impl Drop for WeakRefGuard {
+    fn drop(&mut self) {
+        fn drop_impl(env: &JNIEnv, raw: sys::jweak) -> Result<()> {
+            let internal = env.get_native_interface();
+            unsafe {
+                jni_unchecked!(internal, DeleteWeakGlobalRef, raw);
+                check_exception!(internal);
+            }
+            Ok(())
+        }
+
+        let res = match self.vm.get_env() {
+            Ok(env) => drop_impl(&env, self.raw),
+            Err(_) => {
+                warn!("Dropping a WeakRef in a detached thread. Fix your code if this message appears frequently (see the GlobalRef docs).");
+                self.vm
+                    .attach_current_thread()
+                    .and_then(|env| drop_impl(&env, self.raw))
+            }
+        };
+
+        if let Err(err) = res {
+            debug!("error dropping weak ref: {:#?}", err);
+        }
+    }
+}
@@ -66,6 +66,18 @@ impl ::std::fmt::Display for JavaType {
     }
 }
 
+/// The kind of value a method returns, which tells which `Call*Method`
+/// function to use to call it. See `JNIEnv::return_type_kind`.
+#[allow(missing_docs)]
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub enum ReturnKind {
+    /// Any primitive other than void.
+    Primitive(Primitive),
+    Object,
+    Array,
+    Void,
+}
+
 /// A method type signature. This is the structure representation of something
 /// like `(Ljava/lang/String;)Z`. Used by the `call_(object|static)_method`
 /// functions on jnienv to ensure safety.
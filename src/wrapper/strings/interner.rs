@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Compact handle for a string stored in a `StringInterner`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct InternedId(u32);
+
+impl InternedId {
+    /// The position of the string in the interner, counting from 0 in the
+    /// order the strings were first seen.
+    pub fn index(&self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// Deduplicating store for decoded java strings. Each distinct value is kept
+/// once, and handed out as an `InternedId` every time it's seen again. See
+/// `JNIEnv::get_string_interned`.
+///
+/// The interner is owned by the caller and never clears itself, so it grows
+/// with the number of distinct values. It works best for columns of values
+/// with low cardinality.
+#[derive(Debug, Default)]
+pub struct StringInterner {
+    ids: HashMap<Arc<str>, InternedId>,
+    strings: Vec<Arc<str>>,
+}
+
+impl StringInterner {
+    /// Create an empty interner.
+    pub fn new() -> StringInterner {
+        Default::default()
+    }
+
+    /// Get the id for a string, storing it if it hasn't been seen before.
+    pub fn intern(&mut self, s: &str) -> InternedId {
+        if let Some(id) = self.ids.get(s) {
+            return *id;
+        }
+
+        let id = InternedId(self.strings.len() as u32);
+        let s: Arc<str> = s.into();
+        self.strings.push(s.clone());
+        self.ids.insert(s, id);
+        id
+    }
+
+    /// Get the id of a string without storing it.
+    pub fn get(&self, s: &str) -> Option<InternedId> {
+        self.ids.get(s).cloned()
+    }
+
+    /// Get the string for an id. Returns `None` if the id came from a
+    /// different interner.
+    pub fn resolve(&self, id: InternedId) -> Option<&str> {
+        self.strings.get(id.index()).map(|s| &**s)
+    }
+
+    /// The number of distinct strings stored.
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    /// Whether no strings have been stored yet.
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+}
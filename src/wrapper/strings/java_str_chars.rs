@@ -0,0 +1,188 @@
+use std::slice;
+
+use JNIEnv;
+
+use objects::JString;
+
+use errors::*;
+
+use sys::{
+    jboolean,
+    jchar,
+};
+
+/// Selects how the UTF-16 contents of a java string are obtained. See
+/// `JNIEnv::get_string_chars_with`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StringAccess {
+    /// Use `GetStringCritical`/`ReleaseStringCritical`.
+    ///
+    /// This is the most likely to avoid a copy, but the VM may pin the string
+    /// or disable garbage collection altogether until the guard is dropped.
+    /// Under GC configurations that can't pin individual objects, every other
+    /// thread that needs to allocate will stall while the guard is alive. No
+    /// other JNI calls may be made while holding a critical guard.
+    Critical,
+    /// Use `GetStringChars`/`ReleaseStringChars`.
+    ///
+    /// The VM is free to hand back a copy, but the GC keeps running and other
+    /// JNI calls may be made while the guard is alive.
+    NonCritical,
+}
+
+/// Reference to the UTF-16 contents of a string in the JVM. Holds the pointer
+/// returned by either `GetStringChars` or `GetStringCritical`, and calls the
+/// matching release function on Drop.
+///
+/// Derefs to `&[jchar]`.
+pub struct JavaStrChars<'a> {
+    internal: *const jchar,
+    len: usize,
+    access: StringAccess,
+    obj: JString<'a>,
+    env: &'a JNIEnv<'a>,
+}
+
+impl<'a> JavaStrChars<'a> {
+    /// Build a `JavaStrChars` from an object and a reference to the
+    /// environment. You probably want to use `JNIEnv::get_string_chars` or
+    /// `JNIEnv::get_string_critical` instead.
+    ///
+    /// This is unsafe because with `StringAccess::Critical` nothing stops
+    /// other JNI calls from being made through `env` while the result is
+    /// alive.
+    pub unsafe fn from_env(env: &'a JNIEnv<'a>, obj: JString<'a>, access: StringAccess) -> Result<Self> {
+        non_null!(obj, "JavaStrChars::from_env obj argument");
+        let internal = env.get_native_interface();
+
+        // The length has to be read first: no JNI calls are allowed once we
+        // are in a critical region.
+        let len = jni_unchecked!(internal, GetStringLength, obj.into_inner());
+
+        let ptr: *const jchar = match access {
+            // checking for an exception would be a JNI call inside the
+            // critical region; a null result is all there is to go by
+            StringAccess::Critical => jni_unchecked!(
+                internal,
+                GetStringCritical,
+                obj.into_inner(),
+                ::std::ptr::null::<jboolean>() as *mut jboolean
+            ),
+            StringAccess::NonCritical => jni_non_null_call!(
+                internal,
+                GetStringChars,
+                obj.into_inner(),
+                ::std::ptr::null::<jboolean>() as *mut jboolean
+            ),
+        };
+        non_null!(ptr, "JavaStrChars::from_env result");
+
+        Ok(JavaStrChars {
+            internal: ptr,
+            len: len as usize,
+            access: access,
+            obj: obj,
+            env: env,
+        })
+    }
+
+    /// Which access path was used to get at the string contents.
+    pub fn access(&self) -> StringAccess {
+        self.access
+    }
+
+    /// Extract the raw pointer to the UTF-16 code units. This is *not* null
+    /// terminated.
+    pub fn get_raw(&self) -> *const jchar {
+        self.internal
+    }
+
+    fn release(&mut self) -> Result<()> {
+        let internal = self.env.get_native_interface();
+        unsafe {
+            match self.access {
+                StringAccess::Critical => jni_unchecked!(
+                    internal,
+                    ReleaseStringCritical,
+                    self.obj.into_inner(),
+                    self.internal
+                ),
+                StringAccess::NonCritical => jni_unchecked!(
+                    internal,
+                    ReleaseStringChars,
+                    self.obj.into_inner(),
+                    self.internal
+                ),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a> ::std::ops::Deref for JavaStrChars<'a> {
+    type Target = [jchar];
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { slice::from_raw_parts(self.internal, self.len) }
+    }
+}
+
+impl<'a> From<JavaStrChars<'a>> for String {
+    fn from(other: JavaStrChars) -> String {
+        String::from_utf16_lossy(&other)
+    }
+}
+
+impl<'a> Drop for JavaStrChars<'a> {
+    fn drop(&mut self) {
+        match self.release() {
+            Ok(()) => {}
+            Err(e) => warn!("error dropping java str chars: {}", e),
+        }
+    }
+}
+
+/// Reference to the UTF-16 contents of a string in the JVM, held in a
+/// critical section (`GetStringCritical`/`ReleaseStringCritical`), as
+/// returned by `JNIEnv::get_string_critical`.
+///
+/// No other JNI calls are allowed until this is dropped. To enforce that, it
+/// mutably borrows the `JNIEnv` it came from for its whole lifetime, like
+/// `AutoPrimitiveArrayCritical` does for arrays.
+///
+/// Derefs to `&[jchar]`.
+pub struct JavaStrCritical<'a> {
+    chars: JavaStrChars<'a>,
+}
+
+impl<'a> JavaStrCritical<'a> {
+    /// Enter a critical section for `obj`. You probably want to use
+    /// `JNIEnv::get_string_critical` instead.
+    pub fn new<'e: 'a>(env: &'a mut JNIEnv<'e>, obj: JString<'e>) -> Result<Self> {
+        let env: &'a JNIEnv<'a> = env;
+        // the env stays mutably borrowed while the guard is alive
+        Ok(JavaStrCritical {
+            chars: unsafe { JavaStrChars::from_env(env, obj, StringAccess::Critical)? },
+        })
+    }
+
+    /// Extract the raw pointer to the UTF-16 code units. This is *not* null
+    /// terminated.
+    pub fn get_raw(&self) -> *const jchar {
+        self.chars.get_raw()
+    }
+}
+
+impl<'a> ::std::ops::Deref for JavaStrCritical<'a> {
+    type Target = [jchar];
+
+    fn deref(&self) -> &Self::Target {
+        &self.chars
+    }
+}
+
+impl<'a> From<JavaStrCritical<'a>> for String {
+    fn from(other: JavaStrCritical) -> String {
+        String::from_utf16_lossy(&other)
+    }
+}
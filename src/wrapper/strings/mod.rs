@@ -4,3 +4,9 @@ pub use self::ffi_str::*;
 
 mod java_str;
 pub use self::java_str::*;
+
+mod java_str_chars;
+pub use self::java_str_chars::*;
+
+mod interner;
+pub use self::interner::*;
@@ -0,0 +1,173 @@
+use std::sync::mpsc::{
+    self,
+    RecvTimeoutError,
+    Receiver,
+    Sender,
+};
+use std::sync::{
+    Arc,
+    Condvar,
+    Mutex,
+};
+use std::thread;
+use std::time::{
+    Duration,
+    Instant,
+};
+
+use JNIEnv;
+use JavaVM;
+
+use errors::*;
+
+use objects::GlobalRef;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum State {
+    Running,
+    Finished,
+    Interrupting,
+    Interrupted,
+}
+
+// The state of a call, and a condvar signalled when it leaves `Interrupting`.
+type SharedState = Arc<(Mutex<State>, Condvar)>;
+
+struct Watch {
+    deadline: Instant,
+    thread: GlobalRef,
+    state: SharedState,
+}
+
+enum Message {
+    Watch(Watch),
+    // a call finished, so its watch can be dropped
+    Finished,
+}
+
+/// A call watched by `watch`, which has to be `finish`ed once it returns.
+pub(crate) struct WatchHandle {
+    state: SharedState,
+    tx: Sender<Message>,
+}
+
+impl WatchHandle {
+    /// Stop watching the call, and tell whether the thread got interrupted
+    /// before that. Once this returns, it won't be.
+    pub(crate) fn finish(self) -> bool {
+        let interrupted = {
+            let (ref state, ref delivered) = *self.state;
+            let mut state = state.lock().unwrap();
+            // The interrupt is on its way: wait for it to land, or the flag
+            // it sets would be left for the caller's next blocking call.
+            while *state == State::Interrupting {
+                state = delivered.wait(state).unwrap();
+            }
+            if *state == State::Running {
+                *state = State::Finished;
+            }
+            *state == State::Interrupted
+        };
+        // have the watchdog drop the watch, and the global ref to the thread,
+        // right away rather than at the next deadline
+        let _ = self.tx.send(Message::Finished);
+        interrupted
+    }
+}
+
+// All watched calls share one thread, started by the first of them. It's
+// attached to the VM as a daemon for good, so it can interrupt threads and
+// delete the global refs to them without attaching each time.
+static WATCHDOG: Mutex<Option<Sender<Message>>> = Mutex::new(None);
+
+/// Have the watchdog call `Thread.interrupt()` on `java_thread` if the call
+/// it's making hasn't finished after `timeout`.
+pub(crate) fn watch(vm: JavaVM, java_thread: GlobalRef, timeout: Duration) -> Result<WatchHandle> {
+    let state = Arc::new((Mutex::new(State::Running), Condvar::new()));
+    let mut msg = Message::Watch(Watch {
+        deadline: Instant::now() + timeout,
+        thread: java_thread,
+        state: state.clone(),
+    });
+
+    let mut watchdog = WATCHDOG.lock().unwrap();
+    if let Some(ref tx) = *watchdog {
+        match tx.send(msg) {
+            Ok(()) => {
+                return Ok(WatchHandle {
+                    state: state,
+                    tx: tx.clone(),
+                })
+            }
+            // the thread is gone, start another one
+            Err(mpsc::SendError(unsent)) => msg = unsent,
+        }
+    }
+
+    let (tx, rx) = mpsc::channel();
+    thread::Builder::new()
+        .name("jni-interrupt-watchdog".into())
+        .spawn(move || run(vm, rx))
+        .map_err(|e| format!("error starting the interrupt watchdog: {}", e))?;
+    tx.send(msg).map_err(|_| "the interrupt watchdog exited")?;
+    *watchdog = Some(tx.clone());
+    Ok(WatchHandle {
+        state: state,
+        tx: tx,
+    })
+}
+
+fn run(vm: JavaVM, rx: Receiver<Message>) {
+    let env = match vm.attach_current_thread_as_daemon() {
+        Ok(env) => env,
+        Err(e) => {
+            warn!("error attaching the interrupt watchdog: {}", e);
+            return;
+        }
+    };
+
+    let mut watches: Vec<Watch> = Vec::new();
+    loop {
+        // calls that finished in time are dropped, along with their global
+        // refs, whenever the watchdog wakes up
+        watches.retain(|w| *w.state.0.lock().unwrap() == State::Running);
+
+        let next = match watches.iter().map(|w| w.deadline).min() {
+            Some(deadline) => rx.recv_timeout(deadline.saturating_duration_since(Instant::now())),
+            None => rx.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+        match next {
+            Ok(Message::Watch(watch)) => watches.push(watch),
+            Ok(Message::Finished) | Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+
+        let now = Instant::now();
+        for watch in watches.iter().filter(|w| w.deadline <= now) {
+            interrupt(&env, watch);
+        }
+        watches.retain(|w| w.deadline > now);
+    }
+}
+
+fn interrupt(env: &JNIEnv, watch: &Watch) {
+    let (ref state, ref delivered) = *watch.state;
+    {
+        let mut state = state.lock().unwrap();
+        if *state != State::Running {
+            return;
+        }
+        *state = State::Interrupting;
+    }
+
+    let interrupted = match env.call_method(watch.thread.as_obj(), "interrupt", "()V", &[]) {
+        Ok(_) => true,
+        Err(e) => {
+            let _ = env.exception_clear();
+            warn!("error interrupting thread after timeout: {}", e);
+            false
+        }
+    };
+    *state.lock().unwrap() = if interrupted { State::Interrupted } else { State::Finished };
+    delivered.notify_all();
+}
@@ -0,0 +1,32 @@
+#![cfg(all(feature = "invocation", feature = "call-depth"))]
+
+extern crate error_chain;
+extern crate jni;
+
+use jni::errors::ErrorKind;
+
+mod util;
+use util::{attach_current_thread, unwrap};
+
+#[test]
+pub fn calls_past_the_depth_limit_fail() {
+    let env = attach_current_thread();
+    let s = unwrap(&env, env.new_string("abc"));
+    assert_eq!(0, jni::call_depth());
+
+    jni::set_call_depth_limit(0);
+    let res = env.call_method(s.into(), "length", "()I", &[]);
+    jni::set_call_depth_limit(jni::DEFAULT_CALL_DEPTH_LIMIT);
+
+    match res {
+        Err(ref e) => match *e.kind() {
+            ErrorKind::CallDepthExceeded(0) => {}
+            ref kind => panic!("unexpected error kind: {:?}", kind),
+        },
+        Ok(_) => panic!("call should have been refused"),
+    }
+
+    let len = unwrap(&env, env.call_method(s.into(), "length", "()I", &[]));
+    assert_eq!(3, len.i().unwrap());
+    assert_eq!(0, jni::call_depth());
+}
@@ -0,0 +1,29 @@
+#![cfg(all(feature = "invocation", feature = "call-stats"))]
+
+extern crate error_chain;
+extern crate jni;
+
+mod util;
+use util::{attach_current_thread, unwrap};
+
+#[test]
+pub fn call_method_calls_are_counted() {
+    let env = attach_current_thread();
+    let s = unwrap(&env, env.new_string("abc"));
+
+    env.reset_call_stats();
+    for _ in 0..3 {
+        unwrap(&env, env.call_method(s.into(), "length", "()I", &[]));
+    }
+    unwrap(&env, env.call_method(s.into(), "isEmpty", "()Z", &[]));
+
+    let stats = env.call_stats();
+    assert_eq!(2, stats.len());
+    let (key, count) = stats.iter().find(|&(key, _)| key.name == "length").unwrap();
+    assert_eq!("java.lang.String", key.class);
+    assert_eq!("()I", key.sig);
+    assert_eq!(3, *count);
+
+    env.reset_call_stats();
+    assert!(env.call_stats().is_empty());
+}
@@ -0,0 +1,63 @@
+#![cfg(all(feature = "invocation", feature = "trace"))]
+
+extern crate error_chain;
+extern crate jni;
+extern crate log;
+
+use std::sync::Mutex;
+
+use jni::objects::JValue;
+use log::{LogLevel, LogLevelFilter, LogMetadata, LogRecord};
+
+mod util;
+use util::{attach_current_thread, unwrap};
+
+struct CapturingLogger {
+    records: Mutex<Vec<String>>,
+}
+
+impl log::Log for CapturingLogger {
+    fn enabled(&self, _: &LogMetadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &LogRecord) {
+        if record.level() == LogLevel::Trace && record.target() == "jni::trace" {
+            self.records.lock().unwrap().push(record.args().to_string());
+        }
+    }
+}
+
+#[test]
+pub fn calls_are_traced() {
+    static mut LOGGER: *const CapturingLogger = 0 as *const _;
+    log::set_logger(|max| {
+        max.set(LogLevelFilter::Trace);
+        let logger = Box::new(CapturingLogger { records: Mutex::new(Vec::new()) });
+        unsafe { LOGGER = &*logger };
+        logger
+    }).unwrap();
+    let logger = unsafe { &*LOGGER };
+
+    let env = attach_current_thread();
+    let list = unwrap(&env, env.new_object("java/util/ArrayList", "()V", &[]));
+    unwrap(&env, env.call_method(list, "size", "()I", &[]));
+    unwrap(&env, env.call_static_method("java/lang/Math", "abs", "(I)I", &[JValue::Int(-1)]));
+    unwrap(&env, env.get_static_field("java/lang/Integer", "MAX_VALUE", "I"));
+    assert!(env.call_method(list, "get", "(I)Ljava/lang/Object;", &[JValue::Int(3)]).is_err());
+    unwrap(&env, env.exception_clear());
+
+    let records = logger.records.lock().unwrap();
+    let prefixes = [
+        "new_object java.util.ArrayList.<init> ()V in ",
+        "call_method java.util.ArrayList.size ()I in ",
+        "call_static_method java.lang.Math.abs (I)I in ",
+        "get_static_field java.lang.Integer.MAX_VALUE I in ",
+        "call_method java.util.ArrayList.get (I)Ljava/lang/Object; in ",
+    ];
+    assert_eq!(prefixes.len(), records.len(), "{:?}", *records);
+    for (record, prefix) in records.iter().zip(prefixes.iter()) {
+        assert!(record.starts_with(prefix), "{:?} doesn't start with {:?}", record, prefix);
+    }
+    assert!(records[4].ends_with(" (failed)"));
+}
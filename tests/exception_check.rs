@@ -0,0 +1,68 @@
+#![cfg(all(feature = "invocation", feature = "exception-check"))]
+
+extern crate error_chain;
+extern crate jni;
+
+use jni::errors::{ErrorKind, Result};
+use jni::objects::{JObject, JValue, ReleaseMode};
+use jni::JNIEnv;
+
+mod util;
+use util::{attach_current_thread, unwrap};
+
+fn assert_java_exception<T>(res: Result<T>) {
+    match *res.err().expect("the exception should be reported").kind() {
+        ErrorKind::JavaException => {}
+        ref kind => panic!("unexpected error kind: {:?}", kind),
+    }
+}
+
+#[test]
+pub fn exceptions_are_checked_after_every_call() {
+    let env = attach_current_thread();
+
+    // `CallStaticIntMethodA` isn't otherwise checked by the unsafe call
+    let s = unwrap(&env, env.new_string("not a number"));
+    assert_java_exception(env.call_static_method(
+        "java/lang/Integer",
+        "parseInt",
+        "(Ljava/lang/String;)I",
+        &[JValue::from(JObject::from(s))],
+    ));
+    assert!(unwrap(&env, env.exception_check()));
+    unwrap(&env, env.exception_clear());
+}
+
+#[test]
+pub fn no_calls_are_made_with_an_exception_pending() {
+    let env = attach_current_thread();
+    let obj = unwrap(&env, env.new_object("java/lang/Object", "()V", &[]));
+
+    unwrap(&env, env.throw_new("java/lang/IllegalStateException", "pending"));
+    assert_java_exception(env.get_object_class(obj));
+    assert_java_exception(env.new_string("x"));
+
+    // the pending exception is left alone, and may still be handled
+    let desc = unwrap(&env, env.pending_exception()).unwrap();
+    assert_eq!("java/lang/IllegalStateException", desc.class);
+    unwrap(&env, env.exception_clear());
+    unwrap(&env, env.get_object_class(obj));
+}
+
+#[test]
+pub fn critical_regions_are_entered_without_checking() {
+    let guard = attach_current_thread();
+    let mut env = unsafe { JNIEnv::from_raw(guard.get_native_interface()).unwrap() };
+    let s = unwrap(&env, env.new_string("h\u{4e16}llo"));
+    let array = unwrap(&env, env.new_int_array(3));
+
+    // checking for an exception in the critical region would be reported
+    // by -Xcheck:jni
+    let text: String = env.get_string_critical(s).unwrap().into();
+    assert_eq!("h\u{4e16}llo", text);
+    {
+        let elems = env.get_primitive_array_critical::<i32>(array, ReleaseMode::NoCopyBack).unwrap();
+        assert_eq!(&[0, 0, 0], &*elems);
+    }
+    assert!(!unwrap(&env, env.exception_check()));
+}
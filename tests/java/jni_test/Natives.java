@@ -0,0 +1,6 @@
+package jni_test;
+
+// Native methods for tests to bind with RegisterNatives.
+public class Natives {
+    public static native int add(int a, int b);
+}
@@ -0,0 +1,13 @@
+package jni_test;
+
+// Overloads for the resolve_overload tests: a String fits both, and neither
+// parameter type is more specific than the other.
+public class Overloads {
+    public String describe(Comparable<?> value) {
+        return "comparable";
+    }
+
+    public String describe(CharSequence value) {
+        return "char sequence";
+    }
+}
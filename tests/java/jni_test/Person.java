@@ -0,0 +1,11 @@
+package jni_test;
+
+// Built and read through its fields by the IntoJava/FromJava derive tests.
+public class Person {
+    public String name;
+    public int age;
+    public boolean active;
+    public byte[] avatar;
+    public String nickname;
+    public Point home;
+}
@@ -0,0 +1,21 @@
+package jni_test;
+
+// Built through its constructor and read through its getters by the
+// IntoJava/FromJava derive tests.
+public class Point {
+    private final int x;
+    private final int y;
+
+    public Point(int x, int y) {
+        this.x = x;
+        this.y = y;
+    }
+
+    public int getX() {
+        return x;
+    }
+
+    public int getY() {
+        return y;
+    }
+}
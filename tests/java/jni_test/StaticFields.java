@@ -0,0 +1,7 @@
+package jni_test;
+
+// Static fields for tests to read and write.
+public class StaticFields {
+    public static int count;
+    public static String name = "initial";
+}
@@ -0,0 +1,103 @@
+#![cfg(feature = "invocation")]
+
+extern crate error_chain;
+extern crate jni;
+extern crate jni_macros;
+
+use std::sync::{Once, ONCE_INIT};
+
+use jni::objects::{JObject, JString};
+use jni::sys::jint;
+use jni::{FromJava, IntoJava, JNIEnv};
+use jni_macros::{FromJava, IntoJava};
+
+mod util;
+use util::{attach_current_thread, unwrap};
+
+#[derive(Debug, PartialEq, IntoJava, FromJava)]
+#[jni(class = "jni_test.Point", ctor)]
+struct Point {
+    #[jni(getter = "getX")]
+    x: jint,
+    #[jni(getter = "getY")]
+    y: jint,
+}
+
+#[derive(Debug, PartialEq, IntoJava, FromJava)]
+#[jni(class = "jni_test.Person")]
+struct Person {
+    name: String,
+    #[jni(name = "age")]
+    years: jint,
+    active: bool,
+    avatar: Vec<u8>,
+    nickname: Option<String>,
+    home: Option<Point>,
+}
+
+fn define_test_classes(env: &JNIEnv) {
+    static DEFINE: Once = ONCE_INIT;
+    DEFINE.call_once(|| {
+        let loader = unwrap(env, env.call_static_method(
+            "java/lang/ClassLoader",
+            "getSystemClassLoader",
+            "()Ljava/lang/ClassLoader;",
+            &[],
+        )).l().unwrap();
+        unwrap(env, env.define_class("jni_test/Point", loader, include_bytes!("java/jni_test/Point.class")));
+        unwrap(env, env.define_class("jni_test/Person", loader, include_bytes!("java/jni_test/Person.class")));
+    });
+}
+
+#[test]
+pub fn derived_conversions_round_trip() {
+    let env = attach_current_thread();
+    define_test_classes(&env);
+
+    let person = Person {
+        name: "Ada".to_owned(),
+        years: 36,
+        active: true,
+        avatar: vec![1, 2, 3],
+        nickname: None,
+        home: Some(Point { x: 3, y: -4 }),
+    };
+
+    let obj = unwrap(&env, person.to_java(&env));
+    assert!(unwrap(&env, env.is_instance_of(obj, "jni_test/Person")));
+    assert_eq!(36, unwrap(&env, env.get_field(obj, "age", "I")).i().unwrap());
+    assert!(unwrap(&env, env.get_field(obj, "nickname", "Ljava/lang/String;")).l().unwrap().is_null());
+
+    let home = unwrap(&env, env.get_field(obj, "home", "Ljni_test/Point;")).l().unwrap();
+    assert_eq!(-4, unwrap(&env, env.call_method(home, "getY", "()I", &[])).i().unwrap());
+
+    assert_eq!(person, unwrap(&env, Person::from_java(&env, obj)));
+}
+
+#[test]
+pub fn derived_from_java_reads_java_values() {
+    let env = attach_current_thread();
+    define_test_classes(&env);
+
+    let obj = unwrap(&env, env.new_object("jni_test/Person", "()V", &[]));
+    let name = unwrap(&env, env.new_string("Grace"));
+    let nickname: JString = unwrap(&env, env.new_string("Amazing Grace"));
+    unwrap(&env, env.set_field(obj, "name", "Ljava/lang/String;", JObject::from(name).into()));
+    unwrap(&env, env.set_field(obj, "nickname", "Ljava/lang/String;", JObject::from(nickname).into()));
+    let avatar = unwrap(&env, env.byte_array_from_slice(&[]));
+    unwrap(&env, env.set_field(obj, "avatar", "[B", JObject::from(avatar).into()));
+
+    let person = unwrap(&env, Person::from_java(&env, obj));
+    assert_eq!(person, Person {
+        name: "Grace".to_owned(),
+        years: 0,
+        active: false,
+        avatar: vec![],
+        nickname: Some("Amazing Grace".to_owned()),
+        home: None,
+    });
+
+    // a null String field can't be read as a String
+    let empty = unwrap(&env, env.new_object("jni_test/Person", "()V", &[]));
+    assert!(Person::from_java(&env, empty).is_err());
+}
@@ -0,0 +1,66 @@
+#![cfg(feature = "invocation")]
+
+extern crate error_chain;
+extern crate jni;
+extern crate log;
+
+use std::sync::Mutex;
+
+use jni::objects::{JObject, JValue};
+use log::{LogLevel, LogLevelFilter, LogMetadata, LogRecord};
+
+mod util;
+use util::{attach_current_thread, unwrap};
+
+struct CapturingLogger {
+    records: Mutex<Vec<(LogLevel, String, String)>>,
+}
+
+impl log::Log for CapturingLogger {
+    fn enabled(&self, _: &LogMetadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &LogRecord) {
+        self.records.lock().unwrap().push((
+            record.level(),
+            record.target().to_owned(),
+            record.args().to_string(),
+        ));
+    }
+}
+
+#[test]
+pub fn java_logs_reach_rust_logger() {
+    static mut LOGGER: *const CapturingLogger = 0 as *const _;
+    log::set_logger(|max| {
+        max.set(LogLevelFilter::Trace);
+        let logger = Box::new(CapturingLogger { records: Mutex::new(Vec::new()) });
+        unsafe { LOGGER = &*logger };
+        logger
+    }).unwrap();
+    let logger = unsafe { &*LOGGER };
+
+    let env = attach_current_thread();
+    unwrap(&env, env.install_java_log_handler());
+    // a second install doesn't duplicate records
+    unwrap(&env, env.install_java_log_handler());
+
+    // `Logger.getLogger` needs a java caller, which this thread doesn't have
+    let java_logger = unwrap(&env, env.call_static_method(
+        "java/util/logging/Logger",
+        "getGlobal",
+        "()Ljava/util/logging/Logger;",
+        &[],
+    )).l().unwrap();
+    let msg = unwrap(&env, env.new_string("disk is getting full"));
+    unwrap(&env, env.call_method(java_logger, "warning", "(Ljava/lang/String;)V", &[JValue::from(JObject::from(msg))]));
+    // below the root logger's default level
+    unwrap(&env, env.call_method(java_logger, "fine", "(Ljava/lang/String;)V", &[JValue::from(JObject::from(msg))]));
+
+    let records = logger.records.lock().unwrap();
+    let java_records: Vec<_> = records.iter().filter(|r| r.1 == "global").collect();
+    assert_eq!(1, java_records.len());
+    assert_eq!(LogLevel::Warn, java_records[0].0);
+    assert_eq!("disk is getting full", java_records[0].2);
+}
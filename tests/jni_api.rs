@@ -1,12 +1,31 @@
 #![cfg(feature = "invocation")]
 
 extern crate error_chain;
+#[macro_use]
 extern crate jni;
 
-use jni::objects::{AutoLocal, JObject};
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::future::{self, Future};
+use std::os::raw::c_void;
+use std::sync::{Arc, OnceLock};
+use std::task::{Context, Waker};
+use std::thread;
+
+use jni::descriptors::{Cached, ClassCache, Desc};
+use jni::errors::ErrorKind;
+use jni::exceptions;
+use jni::objects::{
+    AutoLocal, CallbackHandle, ClassByNameCache, JClass, JFieldID, JHashKey, JList, JMethodID, JObject, JObjectArray, JResultAccessors, JRunnable, JString, JThrowable, JValue,
+    GlobalRef, NativeMethod, OwnedArg, OwnedObject, RefType, ReleaseMode, StaticFactory,
+};
+use jni::signature::{JavaType, Primitive, ReturnKind};
+use jni::strings::{JNIString, StringAccess, StringInterner};
+use jni::sys::{jbyte, jdouble, jint, jlong, jobject};
+use jni::{Executor, IntoJavaException, JNIEnv, JavaVM};
 
 mod util;
-use util::{attach_current_thread, unwrap};
+use util::{attach_current_thread, jvm, unwrap};
 
 static ARRAYLIST_CLASS: &str = "java/util/ArrayList";
 static EXCEPTION_CLASS: &str = "java/lang/Exception";
@@ -61,3 +80,2199 @@ pub fn is_instance_of_null() {
     assert!(unwrap(&env, env.is_instance_of(obj, EXCEPTION_CLASS)));
     assert!(unwrap(&env, env.is_instance_of(obj, ARITHMETIC_EXCEPTION_CLASS)));
 }
+
+#[test]
+pub fn get_string_chars_with_both_access_paths() {
+    let env = attach_current_thread();
+    let s = unwrap(&env, env.new_string("héllo wörld"));
+    let expected: Vec<u16> = "héllo wörld".encode_utf16().collect();
+
+    for access in &[StringAccess::Critical, StringAccess::NonCritical] {
+        // no other calls are made while the chars are held
+        let chars = unwrap(&env, unsafe { env.get_string_chars_with(s, *access) });
+        assert_eq!(*access, chars.access());
+        assert_eq!(&expected[..], &*chars);
+    }
+}
+
+#[test]
+pub fn string_critical_borrows_env() {
+    let guard = attach_current_thread();
+    let mut env = unsafe { JNIEnv::from_raw(guard.get_native_interface()).unwrap() };
+    // Latin-1 strings are usually stored compactly and copied out, while
+    // others may be handed out in place
+    for text in &["h\u{e9}llo", "h\u{4e16}llo"] {
+        let s = unwrap(&env, env.new_string(*text));
+        let expected: Vec<u16> = text.encode_utf16().collect();
+
+        {
+            let chars = env.get_string_critical(s).unwrap();
+            assert_eq!(&expected[..], &*chars);
+        }
+        let decoded: String = env.get_string_critical(s).unwrap().into();
+        assert_eq!(*text, decoded);
+        assert!(!unwrap(&env, env.exception_check()));
+    }
+}
+
+#[test]
+pub fn new_string_utf16_round_trips() {
+    let env = attach_current_thread();
+    let units: Vec<u16> = "h\u{e9}llo \u{1f600}\0".encode_utf16().collect();
+
+    let s = unwrap(&env, env.new_string_utf16(&units));
+    assert_eq!(&units[..], &*unwrap(&env, env.get_string_chars(s)));
+    let decoded: String = unwrap(&env, env.get_string(s)).into();
+    assert_eq!("h\u{e9}llo \u{1f600}\0", decoded);
+
+    // java strings may hold unpaired surrogates
+    let s = unwrap(&env, env.new_string_utf16(&[0xd800, 0x41]));
+    assert_eq!(2, unwrap(&env, env.call_method(s.into(), "length", "()I", &[])).i().unwrap());
+
+    let empty = unwrap(&env, env.new_string_utf16(&[]));
+    assert_eq!(0, unwrap(&env, env.get_string_chars(empty)).len());
+}
+
+#[test]
+pub fn get_string_region_copies_substrings() {
+    let env = attach_current_thread();
+    let s = unwrap(&env, env.new_string("h\u{e9}llo w\u{f6}rld"));
+
+    let mut buf = [0u16; 5];
+    unwrap(&env, env.get_string_region(s, 6, &mut buf));
+    let expected: Vec<u16> = "w\u{f6}rld".encode_utf16().collect();
+    assert_eq!(&expected[..], &buf[..]);
+
+    let mut utf = [0u8; 16];
+    let written = unwrap(&env, env.get_string_utf_region(s, 0, 5, &mut utf));
+    assert_eq!("h\u{e9}llo".as_bytes(), &utf[..written]);
+
+    match *env.get_string_region(s, 8, &mut buf).unwrap_err().kind() {
+        ErrorKind::StringRegionOutOfBounds(8, 5, 11) => {}
+        ref kind => panic!("unexpected error kind: {:?}", kind),
+    }
+    assert!(env.get_string_utf_region(s, 0, 5, &mut [0u8; 4]).is_err());
+    assert!(!unwrap(&env, env.exception_check()));
+}
+
+#[test]
+pub fn new_string_from_jni_str_round_trips() {
+    let env = attach_current_thread();
+    let encoded = JNIString::from("h\u{e9}llo \u{1f600}");
+
+    let s = unwrap(&env, env.new_string_from_jni_str(&encoded));
+    let decoded: String = unwrap(&env, env.get_string(s)).into();
+    assert_eq!("h\u{e9}llo \u{1f600}", decoded);
+}
+
+#[test]
+pub fn get_string_interned_reuses_ids() {
+    let env = attach_current_thread();
+    let mut interner = StringInterner::new();
+
+    let ids: Vec<_> = ["red", "green", "red"]
+        .iter()
+        .map(|s| {
+            let s = unwrap(&env, env.new_string(*s));
+            unwrap(&env, env.get_string_interned(s, &mut interner))
+        })
+        .collect();
+
+    assert_eq!(ids[0], ids[2]);
+    assert_ne!(ids[0], ids[1]);
+    assert_eq!(2, interner.len());
+    assert_eq!(Some("green"), interner.resolve(ids[1]));
+}
+
+#[test]
+pub fn new_object_reflective_picks_matching_constructor() {
+    let env = attach_current_thread();
+    let int_class = unwrap(&env, env.get_static_field("java/lang/Integer", "TYPE", "Ljava/lang/Class;"));
+    let int_class = JClass::from(unwrap(&env, int_class.l()));
+
+    let obj = unwrap(&env, env.new_object_reflective("java/lang/Integer", &[int_class], &[JValue::Int(42)]));
+    let value = unwrap(&env, env.call_method(obj, "intValue", "()I", &[]));
+    assert_eq!(42, unwrap(&env, value.i()));
+}
+
+#[test]
+pub fn new_object_reflective_rejects_ambiguous_constructor() {
+    let env = attach_current_thread();
+    let string_class = unwrap(&env, env.find_class(STRING_CLASS));
+    let arg = unwrap(&env, env.new_string("x"));
+
+    // StringBuilder(String) and StringBuilder(CharSequence) both accept a String
+    let res = env.new_object_reflective("java/lang/StringBuilder", &[string_class], &[JObject::from(arg).into()]);
+    match res {
+        Err(ref e) => match *e.kind() {
+            ErrorKind::AmbiguousOverload(_) => {}
+            _ => panic!("unexpected error: {}", e),
+        },
+        Ok(_) => panic!("expected an ambiguity error"),
+    }
+}
+
+#[test]
+pub fn collection_to_vec_reads_all_elements() {
+    let env = attach_current_thread();
+    let list = unwrap(&env, env.new_object(ARRAYLIST_CLASS, "()V", &[]));
+    assert!(unwrap(&env, env.collection_to_vec(list)).is_empty());
+
+    for s in &["a", "b"] {
+        let elem = unwrap(&env, env.new_string(*s));
+        unwrap(&env, env.call_method(list, "add", "(Ljava/lang/Object;)Z", &[JObject::from(elem).into()]));
+    }
+
+    let elems = unwrap(&env, env.collection_to_vec_typed(list, STRING_CLASS));
+    let elems: Vec<String> = elems
+        .into_iter()
+        .map(|e| unwrap(&env, env.get_string(e.into())).into())
+        .collect();
+    assert_eq!(vec!["a", "b"], elems);
+}
+
+#[test]
+pub fn iterable_iter_walks_any_iterable() {
+    let env = attach_current_thread();
+    let set = unwrap(&env, env.new_object("java/util/TreeSet", "()V", &[]));
+    for s in &["b", "a"] {
+        let elem = unwrap(&env, env.new_string(*s));
+        unwrap(&env, env.call_method(set, "add", "(Ljava/lang/Object;)Z", &[JObject::from(elem).into()]));
+    }
+
+    let elems: Vec<String> = unwrap(&env, env.iterable_iter(set))
+        .map(|e| unwrap(&env, env.get_string(e.into())).into())
+        .collect();
+    assert_eq!(vec!["a", "b"], elems);
+
+    assert!(env.iterable_iter(JObject::null()).is_err());
+    let not_iterable = unwrap(&env, env.new_string("abc"));
+    assert!(env.iterable_iter(not_iterable.into()).is_err());
+    assert_pending_exception(&env, "java/lang/ClassCastException");
+}
+
+#[test]
+pub fn get_array_length_works_for_any_array() {
+    let env = attach_current_thread();
+
+    let bytes = unwrap(&env, env.new_byte_array(3));
+    assert_eq!(3, unwrap(&env, env.get_array_length(bytes)));
+
+    let objects = unwrap(&env, env.new_object_array(5, STRING_CLASS, JObject::null()));
+    assert_eq!(5, unwrap(&env, env.get_array_length(objects)));
+
+    assert!(env.get_array_length(JObject::null().into_inner()).is_err());
+}
+
+#[test]
+pub fn call_macro_builds_signature_from_types() {
+    let env = attach_current_thread();
+    let haystack = unwrap(&env, env.new_string("abcabc"));
+    let needle = unwrap(&env, env.new_string("bc"));
+
+    let index = unwrap(
+        &env,
+        call!(env, haystack.into(), "java/lang/String"::"indexOf"(&JString, i32) -> i32, &needle, 2),
+    );
+    assert_eq!(4, index);
+
+    let joined = unwrap(
+        &env,
+        call!(env, haystack.into(), "java/lang/String"::"concat"(JString) -> JString, needle),
+    );
+    let joined: String = unwrap(&env, env.get_string(joined)).into();
+    assert_eq!("abcabcbc", joined);
+
+    // a receiver of another class is rejected before the call
+    let list = unwrap(&env, env.new_object(ARRAYLIST_CLASS, "()V", &[]));
+    assert!(call!(env, list, "java/lang/String"::"length"() -> i32).is_err());
+    assert_pending_exception(&env, "java/lang/ClassCastException");
+}
+
+#[test]
+pub fn functional_interface_helpers() {
+    let env = attach_current_thread();
+    let s = JObject::from(unwrap(&env, env.new_string("x")));
+
+    let identity = unwrap(&env, env.call_static_method(
+        "java/util/function/Function",
+        "identity",
+        "()Ljava/util/function/Function;",
+        &[],
+    )).l().unwrap();
+    let applied = unwrap(&env, env.apply(identity, s));
+    let same = unwrap(&env, env.call_method(s, "equals", "(Ljava/lang/Object;)Z", &[applied.into()]));
+    assert!(same.z().unwrap());
+
+    let is_x = unwrap(&env, env.call_static_method(
+        "java/util/function/Predicate",
+        "isEqual",
+        "(Ljava/lang/Object;)Ljava/util/function/Predicate;",
+        &[s.into()],
+    )).l().unwrap();
+    assert!(unwrap(&env, env.test(is_x, s)));
+    assert!(!unwrap(&env, env.test(is_x, JObject::null())));
+
+    // Stream.Builder is a Consumer that collects what it's given.
+    let builder = unwrap(&env, env.call_static_method(
+        "java/util/stream/Stream",
+        "builder",
+        "()Ljava/util/stream/Stream$Builder;",
+        &[],
+    )).l().unwrap();
+    unwrap(&env, env.accept(builder, s));
+    let stream = unwrap(&env, env.call_method(builder, "build", "()Ljava/util/stream/Stream;", &[]))
+        .l()
+        .unwrap();
+    let count = unwrap(&env, env.call_method(stream, "count", "()J", &[])).j().unwrap();
+    assert_eq!(1, count);
+
+    assert!(env.get(JObject::null()).is_err());
+    // a string isn't a Supplier
+    assert!(env.get(s).is_err());
+    assert_pending_exception(&env, "java/lang/ClassCastException");
+}
+
+#[test]
+pub fn call_string_method_decodes_result() {
+    let env = attach_current_thread();
+    let s = JObject::from(unwrap(&env, env.new_string("Hello")));
+
+    let upper = unwrap(&env, env.call_string_method(s, "toUpperCase", "()Ljava/lang/String;", &[]));
+    assert_eq!(Some("HELLO".to_owned()), upper);
+
+    let ex = unwrap(&env, env.new_object(EXCEPTION_CLASS, "()V", &[]));
+    let message = unwrap(&env, env.call_string_method(ex, "getMessage", "()Ljava/lang/String;", &[]));
+    assert_eq!(None, message);
+
+    assert!(env.call_string_method(s, "length", "()I", &[]).is_err());
+}
+
+#[test]
+pub fn class_of_caches_classes_by_name() {
+    let env = attach_current_thread();
+    let mut cache = ClassByNameCache::new();
+
+    let mut classes = Vec::new();
+    for s in &["a", "b"] {
+        let s = unwrap(&env, env.new_string(*s));
+        classes.push(unwrap(&env, env.class_of(s.into(), &mut cache)));
+    }
+    let list = unwrap(&env, env.new_object(ARRAYLIST_CLASS, "()V", &[]));
+    classes.push(unwrap(&env, env.class_of(list, &mut cache)));
+
+    // the results can be held together, and outlive the cache
+    cache.clear();
+    assert!(unwrap(&env, env.is_same_object(classes[0].as_obj(), classes[1].as_obj())));
+    assert!(unwrap(&env, env.is_assignable_from(JClass::from(classes[0].as_obj()), STRING_CLASS)));
+    assert!(unwrap(&env, env.is_assignable_from(JClass::from(classes[2].as_obj()), ARRAYLIST_CLASS)));
+
+    unwrap(&env, env.class_of(list, &mut cache));
+    let s = unwrap(&env, env.new_string("c"));
+    unwrap(&env, env.class_of(s.into(), &mut cache));
+    unwrap(&env, env.class_of(list, &mut cache));
+    assert_eq!(2, cache.len());
+    assert!(cache.get("java.lang.String").is_some());
+    assert!(cache.get("java.util.ArrayList").is_some());
+}
+
+#[test]
+pub fn spawn_to_java_future_completes_java_future() {
+    let env = attach_current_thread();
+
+    let vm = unwrap(&env, env.get_java_vm());
+    let done = future::ready(Ok::<_, String>("done".to_owned()));
+    let java_future = unwrap(&env, env.spawn_to_java_future(vm, done));
+    let val = unwrap(&env, env.call_method(java_future, "get", "()Ljava/lang/Object;", &[]))
+        .l()
+        .unwrap();
+    let val: String = unwrap(&env, env.get_string(val.into())).into();
+    assert_eq!("done", val);
+
+    let vm = unwrap(&env, env.get_java_vm());
+    let failed = future::ready(Err::<(), _>("boom"));
+    let java_future = unwrap(&env, env.spawn_to_java_future(vm, failed));
+    assert!(env.call_method(java_future, "get", "()Ljava/lang/Object;", &[]).is_err());
+    let throwable = unwrap(&env, env.exception_occurred());
+    unwrap(&env, env.exception_clear());
+    assert!(unwrap(&env, env.is_instance_of(throwable.into(), "java/util/concurrent/ExecutionException")));
+}
+
+#[test]
+pub fn spawn_to_java_future_with_leaves_driving_to_the_caller() {
+    let env = attach_current_thread();
+
+    let vm = unwrap(&env, env.get_java_vm());
+    let mut spawned = None;
+    let done = future::ready(Ok::<_, String>("done".to_owned()));
+    let java_future = unwrap(&env, env.spawn_to_java_future_with(vm, done, |f| spawned = Some(f)));
+    let is_done = || unwrap(&env, env.call_method(java_future, "isDone", "()Z", &[])).z().unwrap();
+    assert!(!is_done());
+
+    // drive it on this thread, which is already attached
+    let mut completion = spawned.unwrap();
+    let mut cx = Context::from_waker(Waker::noop());
+    assert!(completion.as_mut().poll(&mut cx).is_ready());
+    assert!(is_done());
+    let val = unwrap(&env, env.call_method(java_future, "get", "()Ljava/lang/Object;", &[]))
+        .l()
+        .unwrap();
+    let val: String = unwrap(&env, env.get_string(val.into())).into();
+    assert_eq!("done", val);
+
+    // dropping it before it's done still completes the java future
+    let vm = unwrap(&env, env.get_java_vm());
+    let never = future::pending::<Result<(), String>>();
+    let java_future = unwrap(&env, env.spawn_to_java_future_with(vm, never, |f| drop(f)));
+    assert!(unwrap(&env, env.call_method(java_future, "isCompletedExceptionally", "()Z", &[])).z().unwrap());
+}
+
+#[test]
+pub fn auto_closeable_closes_on_drop() {
+    let env = attach_current_thread();
+    let bytes = unwrap(&env, env.byte_array_from_slice(&[1, 2, 3]));
+    let stream = unwrap(&env, env.new_object(
+        "java/io/ByteArrayInputStream",
+        "([B)V",
+        &[JObject::from(bytes).into()],
+    ));
+    let reader = unwrap(&env, env.new_object(
+        "java/io/InputStreamReader",
+        "(Ljava/io/InputStream;)V",
+        &[stream.into()],
+    ));
+
+    {
+        let closeable = unwrap(&env, env.auto_closeable(reader));
+        let ready = unwrap(&env, env.call_method(closeable.as_obj(), "ready", "()Z", &[]));
+        assert!(ready.z().unwrap());
+    }
+
+    // Reading from a closed reader throws.
+    assert!(env.call_method(reader, "ready", "()Z", &[]).is_err());
+    unwrap(&env, env.exception_clear());
+
+    let s = unwrap(&env, env.new_string("not closeable"));
+    assert!(env.auto_closeable(s.into()).is_err());
+}
+
+#[test]
+pub fn properties_to_hashmap_includes_defaults() {
+    let env = attach_current_thread();
+    let set = |props, key: &str, value: &str| {
+        let key = JObject::from(unwrap(&env, env.new_string(key)));
+        let value = JObject::from(unwrap(&env, env.new_string(value)));
+        unwrap(&env, env.call_method(
+            props,
+            "setProperty",
+            "(Ljava/lang/String;Ljava/lang/String;)Ljava/lang/Object;",
+            &[key.into(), value.into()],
+        ));
+    };
+
+    let defaults = unwrap(&env, env.new_object("java/util/Properties", "()V", &[]));
+    set(defaults, "a", "default");
+    set(defaults, "b", "2");
+    let props = unwrap(&env, env.new_object(
+        "java/util/Properties",
+        "(Ljava/util/Properties;)V",
+        &[defaults.into()],
+    ));
+    set(props, "a", "1");
+
+    let map = unwrap(&env, env.properties_to_hashmap(props));
+    assert_eq!(2, map.len());
+    assert_eq!("1", map["a"]);
+    assert_eq!("2", map["b"]);
+
+    // a plain map isn't a Properties
+    let map = unwrap(&env, env.new_object("java/util/HashMap", "()V", &[]));
+    assert!(env.properties_to_hashmap(map).is_err());
+    assert_pending_exception(&env, "java/lang/ClassCastException");
+}
+
+#[test]
+pub fn system_properties_and_env_vars() {
+    let env = attach_current_thread();
+
+    let tmpdir = unwrap(&env, env.get_system_property("java.io.tmpdir"));
+    assert!(!tmpdir.unwrap().is_empty());
+    assert_eq!(None, unwrap(&env, env.get_system_property("jni.rs.no.such.property")));
+
+    assert_eq!(
+        ::std::env::var("PATH").ok(),
+        unwrap(&env, env.get_env_var("PATH"))
+    );
+    assert_eq!(None, unwrap(&env, env.get_env_var("JNI_RS_NO_SUCH_VARIABLE")));
+}
+
+#[test]
+pub fn runnable_runs_on_another_thread() {
+    let env = attach_current_thread();
+
+    // A FutureTask is a Runnable that records whether it has been run.
+    let noop = unwrap(&env, env.new_object("java/lang/Thread", "()V", &[]));
+    let task = unwrap(&env, env.new_object(
+        "java/util/concurrent/FutureTask",
+        "(Ljava/lang/Runnable;Ljava/lang/Object;)V",
+        &[noop.into(), JObject::null().into()],
+    ));
+    let runnable = unwrap(&env, JRunnable::new(&env, task));
+
+    thread::spawn(move || {
+        let env = attach_current_thread();
+        unwrap(&env, runnable.run(&env));
+    }).join()
+        .unwrap();
+
+    let done = unwrap(&env, env.call_method(task, "isDone", "()Z", &[]));
+    assert!(done.z().unwrap());
+
+    let s = unwrap(&env, env.new_string("not runnable"));
+    assert!(JRunnable::new(&env, s.into()).is_err());
+}
+
+#[test]
+pub fn cmp_objects_sorts_by_natural_ordering() {
+    let env = attach_current_thread();
+    let mut objs: Vec<JObject> = ["b", "c", "a"]
+        .iter()
+        .map(|s| unwrap(&env, env.new_string(*s)).into())
+        .collect();
+
+    objs.sort_by(|a, b| unwrap(&env, env.cmp_objects(*a, *b)));
+    let sorted: Vec<String> = objs.iter()
+        .map(|s| unwrap(&env, env.get_string((*s).into())).into())
+        .collect();
+    assert_eq!(vec!["a", "b", "c"], sorted);
+
+    let list = unwrap(&env, env.new_object(ARRAYLIST_CLASS, "()V", &[]));
+    for &(a, b) in &[(objs[0], list), (list, objs[0])] {
+        assert!(env.compare_to(a, b).is_err());
+        let throwable = unwrap(&env, env.exception_occurred());
+        unwrap(&env, env.exception_clear());
+        assert!(unwrap(&env, env.is_instance_of(throwable.into(), "java/lang/ClassCastException")));
+    }
+}
+
+#[test]
+pub fn no_argument_call_shortcuts() {
+    let env = attach_current_thread();
+    let s = JObject::from(unwrap(&env, env.new_string("abc")));
+
+    assert_eq!(3, unwrap(&env, env.call_int_method_0(s, "length", "()I")));
+    assert!(!unwrap(&env, env.call_bool_method_0(s, "isEmpty", "()Z")));
+    let class = unwrap(&env, env.call_object_method_0(s, "getClass", "()Ljava/lang/Class;"));
+    assert!(unwrap(&env, env.is_assignable_from(JClass::from(class), STRING_CLASS)));
+
+    let n = unwrap(&env, env.new_object("java/lang/Long", "(J)V", &[JValue::Long(42)]));
+    assert_eq!(42, unwrap(&env, env.call_long_method_0(n, "longValue", "()J")));
+    assert_eq!(42.0, unwrap(&env, env.call_double_method_0(n, "doubleValue", "()D")));
+    assert!(unwrap(&env, env.call_method_0(n, "hashCode", "()I")).i().is_ok());
+
+    assert!(env.call_int_method_0(s, "isEmpty", "()Z").is_err());
+}
+
+#[test]
+pub fn call_method_to_string_formats_any_result() {
+    let env = attach_current_thread();
+    let list = unwrap(&env, env.new_object(ARRAYLIST_CLASS, "()V", &[]));
+    let elem = JObject::from(unwrap(&env, env.new_string("a")));
+    let to_string = |name, sig, args: &[JValue]| unwrap(&env, env.call_method_to_string(list, name, sig, args));
+
+    assert_eq!("true", to_string("add", "(Ljava/lang/Object;)Z", &[elem.into()]));
+    assert_eq!("1", to_string("size", "()I", &[]));
+    assert_eq!("[a]", to_string("subList", "(II)Ljava/util/List;", &[JValue::Int(0), JValue::Int(1)]));
+    assert_eq!("void", to_string("clear", "()V", &[]));
+    to_string("add", "(Ljava/lang/Object;)Z", &[JObject::null().into()]);
+    assert_eq!("null", to_string("get", "(I)Ljava/lang/Object;", &[JValue::Int(0)]));
+}
+
+#[test]
+pub fn call_bool_method_on_list_is_empty() {
+    let env = attach_current_thread();
+    let list = unwrap(&env, env.new_object(ARRAYLIST_CLASS, "()V", &[]));
+    assert!(unwrap(&env, env.call_bool_method(list, "isEmpty", "()Z", &[])));
+
+    let elem = JObject::from(unwrap(&env, env.new_string("a")));
+    assert!(unwrap(&env, env.call_bool_method(list, "add", "(Ljava/lang/Object;)Z", &[elem.into()])));
+    assert!(!unwrap(&env, env.call_bool_method(list, "isEmpty", "()Z", &[])));
+
+    assert!(env.call_bool_method(list, "size", "()I", &[]).is_err());
+}
+
+#[test]
+pub fn foreach_array_chunk_visits_all_elements() {
+    let env = attach_current_thread();
+    let bytes: Vec<u8> = (0..10).collect();
+    let array = unwrap(&env, env.byte_array_from_slice(&bytes));
+
+    let mut chunks = Vec::new();
+    unwrap(&env, env.foreach_byte_array_chunk(array, 4, |chunk| chunks.push(chunk.to_vec())));
+    assert_eq!(vec![vec![0, 1, 2, 3], vec![4, 5, 6, 7], vec![8, 9]], chunks);
+
+    let ints = unwrap(&env, env.new_int_array(5));
+    unwrap(&env, env.set_int_array_region(ints, 0, &[1, 2, 3, 4, 5]));
+    let mut sum = 0;
+    unwrap(&env, env.foreach_int_array_chunk(ints, 2, |chunk| sum += chunk.iter().sum::<i32>()));
+    assert_eq!(15, sum);
+
+    assert!(env.foreach_int_array_chunk(ints, 0, |_| {}).is_err());
+}
+
+#[test]
+pub fn current_thread_name_of_attached_thread() {
+    thread::spawn(|| {
+        let env = attach_current_thread();
+        let thread = unwrap(&env, env.call_static_method(
+            "java/lang/Thread",
+            "currentThread",
+            "()Ljava/lang/Thread;",
+            &[],
+        )).l().unwrap();
+        let name = JObject::from(unwrap(&env, env.new_string("jni-rs-named-thread")));
+        unwrap(&env, env.call_method(thread, "setName", "(Ljava/lang/String;)V", &[name.into()]));
+
+        assert_eq!("jni-rs-named-thread", unwrap(&env, env.current_thread_name()));
+    }).join()
+        .unwrap();
+}
+
+#[test]
+pub fn contains_object_uses_java_equality() {
+    let env = attach_current_thread();
+    let haystack: Vec<JObject> = ["a", "b"]
+        .iter()
+        .map(|s| unwrap(&env, env.new_string(*s)).into())
+        .collect();
+
+    // a different object with the same value
+    let needle = unwrap(&env, env.new_string("b"));
+    assert!(unwrap(&env, env.contains_object(&haystack, needle.into())));
+    let needle = unwrap(&env, env.new_string("c"));
+    assert!(!unwrap(&env, env.contains_object(&haystack, needle.into())));
+
+    assert!(!unwrap(&env, env.contains_object(&haystack, JObject::null())));
+    assert!(unwrap(&env, env.contains_object(&[JObject::null()], JObject::null())));
+}
+
+#[test]
+pub fn return_type_kind_of_reflected_methods() {
+    let env = attach_current_thread();
+    let class = unwrap(&env, env.find_class(STRING_CLASS));
+    let no_params = unwrap(&env, env.new_object_array(0, "java/lang/Class", JObject::null()));
+
+    let kind = |name: &str| {
+        let name = JObject::from(unwrap(&env, env.new_string(name)));
+        let method = unwrap(&env, env.call_method(
+            class.into(),
+            "getMethod",
+            "(Ljava/lang/String;[Ljava/lang/Class;)Ljava/lang/reflect/Method;",
+            &[name.into(), JObject::from(no_params).into()],
+        )).l().unwrap();
+        unwrap(&env, env.return_type_kind(method))
+    };
+
+    assert_eq!(ReturnKind::Primitive(Primitive::Int), kind("length"));
+    assert_eq!(ReturnKind::Primitive(Primitive::Boolean), kind("isEmpty"));
+    assert_eq!(ReturnKind::Object, kind("trim"));
+    assert_eq!(ReturnKind::Array, kind("toCharArray"));
+    assert_eq!(ReturnKind::Void, kind("notify"));
+
+    // a class isn't a method
+    assert!(env.return_type_kind(class.into()).is_err());
+    assert_pending_exception(&env, "java/lang/ClassCastException");
+}
+
+#[test]
+pub fn new_array_list_from_keeps_order() {
+    let env = attach_current_thread();
+    let elems: Vec<JObject> = ["a", "b", "c"]
+        .iter()
+        .map(|s| unwrap(&env, env.new_string(*s)).into())
+        .collect();
+
+    let list = unwrap(&env, env.new_array_list_from(elems.iter().cloned()));
+    assert_eq!("[a, b, c]", unwrap(&env, env.call_method_to_string(list, "toString", "()Ljava/lang/String;", &[])));
+
+    let filtered = unwrap(&env, env.new_array_list_from(elems.into_iter().skip(1).filter(|_| true)));
+    assert_eq!(2, unwrap(&env, env.call_int_method_0(filtered, "size", "()I")));
+}
+
+#[test]
+pub fn new_hash_map_from_pairs() {
+    let env = attach_current_thread();
+    let one = JObject::from(unwrap(&env, env.new_string("1")));
+    let two = JObject::from(unwrap(&env, env.new_string("2")));
+
+    let map = unwrap(&env, env.new_hash_map_from(vec![(one, two), (JObject::null(), one), (two, JObject::null())]));
+    assert_eq!(3, unwrap(&env, env.call_int_method_0(map, "size", "()I")));
+    let get = |key: JObject| unwrap(&env, env.call_method_to_string(map, "get", "(Ljava/lang/Object;)Ljava/lang/Object;", &[key.into()]));
+    assert_eq!("2", get(one));
+    assert_eq!("1", get(JObject::null()));
+    assert_eq!("null", get(two));
+
+    let map = unwrap(&env, env.new_hash_map_from_str(vec![("a", one), ("b", two), ("a", two)]));
+    let map = unwrap(&env, env.map_to_hashmap(map));
+    assert_eq!(2, map.len());
+    let a: String = unwrap(&env, env.get_string(map["a"].into())).into();
+    assert_eq!("2", a);
+}
+
+#[test]
+pub fn call_method_with_interrupt_stops_sleep() {
+    let env = attach_current_thread();
+    let lock = unwrap(&env, env.new_object("java/lang/Object", "()V", &[]));
+    let _guard = unwrap(&env, env.lock_obj(lock));
+
+    // the watchdog thread is reused from one call to the next
+    for _ in 0..2 {
+        let start = std::time::Instant::now();
+        let res = env.call_method_with_interrupt(lock, "wait", "(J)V", &[JValue::Long(30_000)], std::time::Duration::from_millis(100));
+        assert!(start.elapsed() < std::time::Duration::from_secs(10));
+        match *res.err().expect("wait should be interrupted").kind() {
+            ErrorKind::JavaException => {}
+            ref kind => panic!("unexpected error kind: {:?}", kind),
+        }
+        assert!(unwrap(&env, env.exception_check()));
+        env.exception_clear().unwrap();
+    }
+
+    // a call that finishes in time isn't interrupted
+    let ret = unwrap(&env, env.call_method_with_interrupt(lock, "hashCode", "()I", &[], std::time::Duration::from_secs(10)));
+    ret.i().unwrap();
+}
+
+#[test]
+pub fn native_try_throws_on_error() {
+    let env = attach_current_thread();
+
+    let ok: jint = native_try!(env, { Ok(5) });
+    assert_eq!(5, ok);
+    assert!(!unwrap(&env, env.exception_check()));
+
+    let err: jobject = native_try!(env, {
+        env.get_string(JObject::null().into())?;
+        Ok(JObject::null().into_inner())
+    });
+    assert!(err.is_null());
+    assert_pending_exception(&env, "java/lang/RuntimeException");
+
+    // an exception thrown by java is kept
+    let () = native_try!(env, {
+        env.call_static_method("java/lang/Integer", "parseInt", "(Ljava/lang/String;)I", &[JValue::Object(JObject::null())])?;
+        Ok(())
+    });
+    assert_pending_exception(&env, "java/lang/NumberFormatException");
+
+    let panicked: jint = native_try!(env, {
+        if true {
+            panic!("boom");
+        }
+        Ok(1)
+    });
+    assert_eq!(0, panicked);
+    assert_pending_exception(&env, "java/lang/RuntimeException");
+
+    let panicked: jdouble = native_try!(env, {
+        let v: Vec<jdouble> = Vec::new();
+        Ok(v[0])
+    });
+    assert_eq!(0.0, panicked);
+    assert_pending_exception(&env, "java/lang/RuntimeException");
+}
+
+enum LookupError {
+    Missing(i32),
+    Other,
+}
+
+impl IntoJavaException for LookupError {
+    fn exception_class(&self) -> &str {
+        match *self {
+            LookupError::Missing(_) => exceptions::NO_SUCH_ELEMENT_EXCEPTION,
+            LookupError::Other => exceptions::RUNTIME_EXCEPTION,
+        }
+    }
+
+    fn exception_message(&self) -> String {
+        match *self {
+            LookupError::Missing(id) => format!("no entry {}", id),
+            LookupError::Other => "lookup failed".to_owned(),
+        }
+    }
+}
+
+#[test]
+pub fn native_try_maps_custom_errors() {
+    let env = attach_current_thread();
+
+    let ok: jint = native_try!(env, LookupError, { Ok(3) });
+    assert_eq!(3, ok);
+
+    let missing: jobject = native_try!(env, LookupError, {
+        Err(LookupError::Missing(7))
+    });
+    assert!(missing.is_null());
+    let desc = unwrap(&env, env.pending_exception()).unwrap();
+    assert_eq!(desc.class, exceptions::NO_SUCH_ELEMENT_EXCEPTION);
+    assert_eq!(desc.msg, "no entry 7");
+    unwrap(&env, env.exception_clear());
+
+    let () = native_try!(env, LookupError, { Err(LookupError::Other) });
+    assert_pending_exception(&env, exceptions::RUNTIME_EXCEPTION);
+}
+
+fn assert_pending_exception(env: &JNIEnv, class: &str) {
+    let ex = unwrap(env, env.exception_occurred());
+    assert!(!ex.is_null());
+    env.exception_clear().unwrap();
+    assert!(unwrap(env, env.is_instance_of(JObject::from(ex), class)));
+}
+
+#[test]
+pub fn process_in_batches_visits_every_element() {
+    let env = attach_current_thread();
+    let items: Vec<JObject> = (0..10)
+        .map(|i| unwrap(&env, env.new_string(i.to_string())).into())
+        .collect();
+    let list = unwrap(&env, env.new_array_list_from(items));
+
+    let mut seen = Vec::new();
+    unwrap(&env, env.process_in_batches(list, 3, |env, item| {
+        let s: String = env.get_string(item.into())?.into();
+        seen.push(s);
+        Ok(())
+    }));
+    let expected: Vec<String> = (0..10).map(|i| i.to_string()).collect();
+    assert_eq!(expected, seen);
+
+    // errors from the callback stop the iteration
+    let mut count = 0;
+    let res = env.process_in_batches(list, 4, |_, _| {
+        count += 1;
+        if count == 6 {
+            Err("stop".into())
+        } else {
+            Ok(())
+        }
+    });
+    assert!(res.is_err());
+    assert_eq!(6, count);
+}
+
+#[test]
+pub fn call_method_indexed_dispatches_by_index() {
+    let env = attach_current_thread();
+    let s: JObject = unwrap(&env, env.new_string("indexed")).into();
+    let method_ids = [
+        unwrap(&env, env.get_method_id(STRING_CLASS, "length", "()I")),
+        unwrap(&env, env.get_method_id(STRING_CLASS, "isEmpty", "()Z")),
+        unwrap(&env, env.get_method_id(STRING_CLASS, "charAt", "(I)C")),
+    ];
+    let ret_types = [
+        JavaType::Primitive(Primitive::Int),
+        JavaType::Primitive(Primitive::Boolean),
+        JavaType::Primitive(Primitive::Char),
+    ];
+
+    unsafe {
+        let length = unwrap(&env, env.call_method_indexed(s, &method_ids, &ret_types, 0, &[]));
+        assert_eq!(7, length.i().unwrap());
+        let empty = unwrap(&env, env.call_method_indexed(s, &method_ids, &ret_types, 1, &[]));
+        assert!(!empty.z().unwrap());
+        let first = unwrap(&env, env.call_method_indexed(s, &method_ids, &ret_types, 2, &[JValue::Int(0)]));
+        assert_eq!('i' as u16, first.c().unwrap());
+
+        match *env.call_method_indexed(s, &method_ids, &ret_types, 3, &[]).unwrap_err().kind() {
+            ErrorKind::InvalidMethodIndex(3, 3) => {}
+            ref kind => panic!("unexpected error kind: {:?}", kind),
+        }
+        match *env.call_method_indexed(s, &method_ids, &ret_types[..2], 0, &[]).unwrap_err().kind() {
+            ErrorKind::InvalidArgList => {}
+            ref kind => panic!("unexpected error kind: {:?}", kind),
+        }
+    }
+}
+
+#[test]
+pub fn static_factory_creates_objects() {
+    let env = attach_current_thread();
+    let of = unwrap(&env, StaticFactory::new(
+        &env,
+        "java/util/Optional",
+        "of",
+        "(Ljava/lang/Object;)Ljava/util/Optional;",
+    ));
+
+    for s in &["a", "b"] {
+        let s = unwrap(&env, env.new_string(*s));
+        let optional = unwrap(&env, of.create(&env, &[JValue::from(JObject::from(s))]));
+        assert!(unwrap(&env, env.is_instance_of(optional, "java/util/Optional")));
+        let value = unwrap(&env, env.call_object_method_0(optional, "get", "()Ljava/lang/Object;"));
+        assert!(unwrap(&env, env.call_bool_method(value, "equals", "(Ljava/lang/Object;)Z", &[JValue::from(JObject::from(s))])));
+    }
+
+    // the wrong number of arguments
+    match *of.create(&env, &[]).unwrap_err().kind() {
+        ErrorKind::InvalidArgList => {}
+        ref kind => panic!("unexpected error kind: {:?}", kind),
+    }
+    // a primitive where an object is expected
+    match *of.create(&env, &[JValue::Int(1)]).unwrap_err().kind() {
+        ErrorKind::InvalidArgType(0, _, _) => {}
+        ref kind => panic!("unexpected error kind: {:?}", kind),
+    }
+    // Optional.of(null) throws
+    assert!(of.create(&env, &[JValue::from(JObject::null())]).is_err());
+    env.exception_clear().unwrap();
+
+    assert!(StaticFactory::new(&env, "java/lang/Integer", "parseInt", "(Ljava/lang/String;)I").is_err());
+}
+
+#[test]
+pub fn static_factory_can_be_shared_between_threads() {
+    static VALUE_OF: OnceLock<StaticFactory> = OnceLock::new();
+    let env = attach_current_thread();
+    VALUE_OF.get_or_init(|| unwrap(&env, StaticFactory::new(
+        &env,
+        "java/lang/Integer",
+        "valueOf",
+        "(I)Ljava/lang/Integer;",
+    )));
+
+    thread::spawn(|| {
+        let env = jvm().attach_current_thread().unwrap();
+        let value = unwrap(&env, VALUE_OF.get().unwrap().create(&env, &[JValue::Int(7)]));
+        assert_eq!(7, unwrap(&env, env.call_int_method_0(value, "intValue", "()I")));
+    }).join().unwrap();
+}
+
+#[test]
+pub fn java_enum_to_rust_maps_constants() {
+    #[derive(Clone, Debug, PartialEq)]
+    enum Unit {
+        Seconds,
+        Minutes,
+    }
+
+    let env = attach_current_thread();
+    let constant = |name: &str| {
+        unwrap(&env, env.get_static_field(
+            "java/util/concurrent/TimeUnit",
+            name,
+            "Ljava/util/concurrent/TimeUnit;",
+        )).l().unwrap()
+    };
+    let mapping = [("SECONDS", Unit::Seconds), ("MINUTES", Unit::Minutes)];
+
+    assert_eq!(Some(Unit::Seconds), unwrap(&env, env.java_enum_to_rust(constant("SECONDS"), &mapping)));
+    assert_eq!(Some(Unit::Minutes), unwrap(&env, env.java_enum_to_rust(constant("MINUTES"), &mapping)));
+    assert_eq!(None, unwrap(&env, env.java_enum_to_rust(constant("DAYS"), &mapping)));
+
+    // NANOSECONDS, MICROSECONDS, MILLISECONDS, SECONDS, ...
+    let by_ordinal = [0, 1, 2, 3];
+    assert_eq!(Some(3), unwrap(&env, env.java_enum_to_rust_by_ordinal(constant("SECONDS"), &by_ordinal)));
+    assert_eq!(None, unwrap(&env, env.java_enum_to_rust_by_ordinal(constant("HOURS"), &by_ordinal)));
+
+    let not_enum = unwrap(&env, env.new_string("SECONDS"));
+    assert!(env.java_enum_to_rust(not_enum.into(), &mapping).is_err());
+}
+
+#[test]
+pub fn resolve_overload_picks_matching_method() {
+    let env = attach_current_thread();
+    let string = || JavaType::Object(STRING_CLASS.into());
+
+    let builder = unwrap(&env, env.new_object("java/lang/StringBuilder", "()V", &[]));
+    let (append_int, sig) = unwrap(&env, env.resolve_overload(
+        "java/lang/StringBuilder",
+        "append",
+        &[JavaType::Primitive(Primitive::Int)],
+    ));
+    assert_eq!("(I)Ljava/lang/StringBuilder;", sig.to_string());
+    unsafe {
+        unwrap(&env, env.call_method_unsafe(builder, append_int, sig.ret, &[JValue::Int(42)]));
+    }
+    assert_eq!("42", unwrap(&env, env.call_method_to_string(builder, "toString", "()Ljava/lang/String;", &[])));
+
+    let (_, sig) = unwrap(&env, env.resolve_overload("java/lang/String", "indexOf", &[string()]));
+    assert_eq!("(Ljava/lang/String;)I", sig.to_string());
+
+    // append(String), append(CharSequence) and append(Object) all fit, the
+    // first is the most specific
+    let (_, sig) = unwrap(&env, env.resolve_overload("java/lang/StringBuilder", "append", &[string()]));
+    assert_eq!("(Ljava/lang/String;)Ljava/lang/StringBuilder;", sig.to_string());
+    let char_sequence = JavaType::Object("java/lang/CharSequence".into());
+    let (_, sig) = unwrap(&env, env.resolve_overload("java/lang/StringBuilder", "append", &[char_sequence]));
+    assert_eq!("(Ljava/lang/CharSequence;)Ljava/lang/StringBuilder;", sig.to_string());
+
+    // describe(Comparable) and describe(CharSequence) both fit a string,
+    // neither is more specific
+    let overloads = define_test_class(&env, "jni_test/Overloads", include_bytes!("java/jni_test/Overloads.class"));
+    assert!(env.resolve_overload(overloads, "describe", &[string()]).is_err());
+    let comparable = JavaType::Object("java/lang/Comparable".into());
+    let (_, sig) = unwrap(&env, env.resolve_overload(overloads, "describe", &[comparable]));
+    assert_eq!("(Ljava/lang/Comparable;)Ljava/lang/String;", sig.to_string());
+
+    match *env.resolve_overload("java/lang/String", "charAt", &[JavaType::Primitive(Primitive::Long)])
+        .unwrap_err()
+        .kind()
+    {
+        ErrorKind::MethodNotFound(ref class, ref name, ref sig) => {
+            assert_eq!("java.lang.String", class);
+            assert_eq!("charAt", name);
+            assert_eq!("(J)", sig);
+        }
+        ref kind => panic!("unexpected error kind: {:?}", kind),
+    }
+}
+
+#[test]
+pub fn callback_handle_invokes_from_any_thread() {
+    let env = attach_current_thread();
+    let list = unwrap(&env, env.new_object(ARRAYLIST_CLASS, "()V", &[]));
+    let add = unwrap(&env, CallbackHandle::new(&env, list, "add", "(Ljava/lang/Object;)Z"));
+    let item = unwrap(&env, env.new_string("event"));
+    let item = OwnedArg::Object(unwrap(&env, env.new_global_ref(item.into())));
+
+    // from the current, attached thread
+    unwrap(&env, add.invoke(&[item.clone()]));
+    // from a thread that isn't attached
+    thread::spawn(move || {
+        add.invoke(&[item.clone(), item.clone()]).unwrap_err();
+        match *add.invoke(&[OwnedArg::Int(1)]).unwrap_err().kind() {
+            ErrorKind::InvalidArgType(0, _, _) => {}
+            ref kind => panic!("unexpected error kind: {:?}", kind),
+        }
+        add.invoke(&[OwnedArg::Null]).unwrap();
+    }).join().unwrap();
+
+    assert_eq!(2, unwrap(&env, env.call_int_method_0(list, "size", "()I")));
+
+    // exceptions are cleared
+    let get = unwrap(&env, CallbackHandle::new(&env, list, "get", "(I)Ljava/lang/Object;"));
+    assert!(get.invoke(&[OwnedArg::Int(5)]).is_err());
+    assert!(!unwrap(&env, env.exception_check()));
+}
+
+#[test]
+pub fn callback_handle_can_be_shared_between_threads() {
+    let env = attach_current_thread();
+    let list = unwrap(&env, env.new_object("java/util/Vector", "()V", &[]));
+    let add = Arc::new(unwrap(&env, CallbackHandle::new(&env, list, "add", "(Ljava/lang/Object;)Z")));
+
+    let handles: Vec<_> = (0..4).map(|_| {
+        let add = add.clone();
+        thread::spawn(move || add.invoke(&[OwnedArg::Null]).unwrap())
+    }).collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    assert_eq!(4, unwrap(&env, env.call_int_method_0(list, "size", "()I")));
+}
+
+#[test]
+pub fn init_args_set_system_properties() {
+    let env = attach_current_thread();
+    assert_eq!(
+        Some("from init args".to_owned()),
+        unwrap(&env, env.get_system_property("jni.test.property"))
+    );
+}
+
+#[test]
+pub fn nested_attach_keeps_thread_attached() {
+    let _env = attach_current_thread();
+    {
+        let inner = jvm().attach_current_thread().unwrap();
+        unwrap(&inner, inner.new_string("inner"));
+    }
+    assert!(jvm().get_env().is_ok());
+
+    thread::spawn(|| {
+        {
+            let _guard = jvm().attach_current_thread().unwrap();
+            assert!(jvm().get_env().is_ok());
+        }
+        assert!(jvm().get_env().is_err());
+    }).join().unwrap();
+}
+
+#[test]
+pub fn weak_ref_upgrades_until_collected() {
+    let env = attach_current_thread();
+
+    let obj = unwrap(&env, env.new_object("java/lang/Object", "()V", &[]));
+    let weak = unwrap(&env, env.new_weak_ref(obj));
+
+    let upgraded = unwrap(&env, weak.upgrade(&env)).expect("object is still referenced");
+    assert!(unwrap(&env, env.call_method(upgraded, "equals", "(Ljava/lang/Object;)Z", &[JValue::from(obj)])).z().unwrap());
+    unwrap(&env, env.delete_local_ref(upgraded));
+    unwrap(&env, env.delete_local_ref(obj));
+
+    for _ in 0..10 {
+        if unwrap(&env, weak.is_garbage_collected(&env)) {
+            break;
+        }
+        unwrap(&env, env.call_static_method("java/lang/System", "gc", "()V", &[]));
+    }
+    assert!(unwrap(&env, weak.upgrade(&env)).is_none());
+}
+
+#[test]
+pub fn with_local_frame_keeps_returned_object() {
+    let env = attach_current_thread();
+
+    let kept = unwrap(&env, env.with_local_frame(16, |env| {
+        let _garbage = env.new_string("dropped with the frame")?;
+        Ok(env.new_string("kept")?.into())
+    }));
+    let kept: String = unwrap(&env, env.get_string(kept.into())).into();
+    assert_eq!(kept, "kept");
+
+    let err = env.with_local_frame(16, |env| {
+        env.new_string("dropped with the frame")?;
+        Err("closure failed".into())
+    });
+    assert!(err.is_err());
+}
+
+#[test]
+pub fn list_wrapper_edits_and_iterates() {
+    let env = attach_current_thread();
+
+    let obj = unwrap(&env, env.new_object("java/util/ArrayList", "()V", &[]));
+    let list = unwrap(&env, env.get_list(obj));
+    for s in &["a", "c"] {
+        unwrap(&env, list.add(unwrap(&env, env.new_string(s)).into()));
+    }
+    unwrap(&env, list.insert(1, unwrap(&env, env.new_string("b")).into()));
+    assert_eq!(unwrap(&env, list.size()), 3);
+
+    let elems: Vec<String> = unwrap(&env, list.iter())
+        .map(|e| env.get_string(e.into()).unwrap().into())
+        .collect();
+    assert_eq!(elems, vec!["a", "b", "c"]);
+
+    let removed = unwrap(&env, list.remove(0)).unwrap();
+    let removed: String = unwrap(&env, env.get_string(removed.into())).into();
+    assert_eq!(removed, "a");
+    let popped = unwrap(&env, list.pop()).unwrap();
+    let popped: String = unwrap(&env, env.get_string(popped.into())).into();
+    assert_eq!(popped, "c");
+    assert_eq!(unwrap(&env, list.size()), 1);
+}
+
+#[test]
+pub fn map_wrapper_edits_and_iterates() {
+    let env = attach_current_thread();
+
+    let obj = unwrap(&env, env.new_object("java/util/HashMap", "()V", &[]));
+    let map = unwrap(&env, env.get_map(obj));
+    let one: JObject = unwrap(&env, env.new_string("one")).into();
+    let two: JObject = unwrap(&env, env.new_string("two")).into();
+    let missing: JObject = unwrap(&env, env.new_string("missing")).into();
+
+    unwrap(&env, map.put(one, unwrap(&env, env.new_string("1")).into()));
+    unwrap(&env, map.put(two, JObject::null()));
+    assert!(unwrap(&env, map.contains_key(one)));
+    assert!(unwrap(&env, map.contains_key(two)));
+    assert!(!unwrap(&env, map.contains_key(missing)));
+
+    let mut entries: Vec<(String, Option<String>)> = unwrap(&env, map.iter())
+        .map(|(k, v)| {
+            let key: String = env.get_string(k.into()).unwrap().into();
+            let value = if v.is_null() { None } else { Some(env.get_string(v.into()).unwrap().into()) };
+            (key, value)
+        })
+        .collect();
+    entries.sort();
+    assert_eq!(entries, vec![("one".to_string(), Some("1".to_string())), ("two".to_string(), None)]);
+
+    unwrap(&env, map.remove(one));
+    assert!(!unwrap(&env, map.contains_key(one)));
+}
+
+#[test]
+pub fn direct_byte_buffer_shares_memory() {
+    let env = attach_current_thread();
+
+    let mut data = vec![1u8, 2, 3, 4];
+    {
+        let buf = unwrap(&env, env.new_direct_byte_buffer(&mut data));
+        assert_eq!(unwrap(&env, env.get_direct_buffer_capacity(buf)), 4);
+
+        unwrap(&env, env.call_method(buf.into(), "put", "(IB)Ljava/nio/ByteBuffer;", &[JValue::Int(0), JValue::Byte(9)]));
+        let bytes = unwrap(&env, unsafe { env.get_direct_buffer_address(buf) });
+        assert_eq!(bytes, &[9, 2, 3, 4]);
+        bytes[3] = 7;
+        let last = unwrap(&env, env.call_method(buf.into(), "get", "(I)B", &[JValue::Int(3)])).b().unwrap();
+        assert_eq!(last, 7);
+    }
+    assert_eq!(data, vec![9, 2, 3, 7]);
+
+    let heap = unwrap(&env, env.call_static_method("java/nio/ByteBuffer", "allocate", "(I)Ljava/nio/ByteBuffer;", &[JValue::Int(4)]))
+        .l()
+        .unwrap();
+    assert!(env.get_direct_buffer_capacity(heap.into()).is_err());
+}
+
+#[test]
+pub fn array_regions_are_bounds_checked() {
+    let env = attach_current_thread();
+
+    let array = unwrap(&env, env.new_long_array(4));
+    unwrap(&env, env.set_long_array_region(array, 1, &[10, 20, 30]));
+    let mut buf = [0; 2];
+    unwrap(&env, env.get_long_array_region(array, 2, &mut buf));
+    assert_eq!(buf, [20, 30]);
+
+    for &(start, len) in &[(3, 2), (-1, 1), (5, 0)] {
+        let err = env.set_long_array_region(array, start, &vec![0; len]).unwrap_err();
+        match *err.kind() {
+            ErrorKind::ArrayRegionOutOfBounds(s, l, 4) if s == start && l == len => {}
+            ref kind => panic!("unexpected error: {}", kind),
+        }
+    }
+    assert!(!unwrap(&env, env.exception_check()));
+}
+
+#[test]
+pub fn array_elements_release_modes() {
+    let env = attach_current_thread();
+
+    let array = unwrap(&env, env.new_int_array(3));
+    unwrap(&env, env.set_int_array_region(array, 0, &[1, 2, 3]));
+    let read = |env: &JNIEnv| {
+        let mut buf = [0; 3];
+        unwrap(env, env.get_int_array_region(array, 0, &mut buf));
+        buf
+    };
+
+    {
+        let mut elems = unwrap(&env, env.get_int_array_elements(array, ReleaseMode::CopyBack));
+        assert_eq!(&*elems, &[1, 2, 3]);
+        elems[0] = 10;
+    }
+    assert_eq!(read(&env), [10, 2, 3]);
+
+    let copied = {
+        let mut elems = unwrap(&env, env.get_int_array_elements(array, ReleaseMode::NoCopyBack));
+        elems[1] = 20;
+        unwrap(&env, elems.commit());
+        elems[2] = 30;
+        elems.is_copy()
+    };
+    if copied {
+        assert_eq!(read(&env), [10, 20, 3]);
+    } else {
+        assert_eq!(read(&env), [10, 20, 30]);
+    }
+
+    // the array types are all jobject, so a mismatch is caught at runtime
+    let bytes = unwrap(&env, env.new_byte_array(3));
+    assert!(env.get_int_array_elements(bytes, ReleaseMode::NoCopyBack).is_err());
+}
+
+#[test]
+pub fn primitive_array_critical_edits_in_place() {
+    let guard = attach_current_thread();
+    let mut env = unsafe { JNIEnv::from_raw(guard.get_native_interface()).unwrap() };
+
+    let array = unwrap(&env, env.new_byte_array(4));
+    {
+        let mut pixels = env.get_primitive_array_critical::<jbyte>(array, ReleaseMode::CopyBack).unwrap();
+        for (i, p) in pixels.iter_mut().enumerate() {
+            *p = i as jbyte * 2;
+        }
+    }
+    let mut buf = [0; 4];
+    unwrap(&env, env.get_byte_array_region(array, 0, &mut buf));
+    assert_eq!(buf, [0, 2, 4, 6]);
+
+    assert!(env.get_primitive_array_critical::<jint>(array, ReleaseMode::NoCopyBack).is_err());
+}
+
+#[test]
+pub fn object_array_wrapper_iterates_with_nulls() {
+    let env = attach_current_thread();
+
+    let raw = unwrap(&env, env.new_object_array(3, "java/lang/String", JObject::null()));
+    let array = unwrap(&env, env.get_object_array(raw));
+    assert_eq!(array.len(), 3);
+    unwrap(&env, array.set(0, unwrap(&env, env.new_string("first")).into()));
+    unwrap(&env, array.set(2, unwrap(&env, env.new_string("last")).into()));
+
+    let elems: Vec<Option<String>> = array
+        .iter()
+        .map(|e| if e.is_null() { None } else { Some(env.get_string(e.into()).unwrap().into()) })
+        .collect();
+    assert_eq!(elems, vec![Some("first".to_string()), None, Some("last".to_string())]);
+
+    assert!(array.get(3).is_err());
+    unwrap(&env, env.exception_clear());
+}
+
+#[test]
+pub fn cached_descriptors_reuse_ids() {
+    let env = attach_current_thread();
+
+    let length = Cached("java/lang/String", "length", "()I");
+    let first: JMethodID = unwrap(&env, length.lookup(&env));
+    let second: JMethodID = unwrap(&env, length.lookup(&env));
+    assert_eq!(first.into_inner(), second.into_inner());
+
+    let s = unwrap(&env, env.new_string("cached"));
+    let len = unwrap(&env, unsafe {
+        env.call_method_unsafe(s.into(), length, JavaType::Primitive(Primitive::Int), &[])
+    }).i().unwrap();
+    assert_eq!(len, 6);
+
+    let max = unwrap(&env, unsafe {
+        env.get_static_field_unsafe(
+            "java/lang/Integer",
+            Cached("java/lang/Integer", "MAX_VALUE", "I"),
+            JavaType::Primitive(Primitive::Int),
+        )
+    }).i().unwrap();
+    assert_eq!(max, jint::max_value());
+
+    let missing: Result<JMethodID, _> = Cached("java/lang/String", "noSuchMethod", "()V").lookup(&env);
+    assert!(missing.is_err());
+    unwrap(&env, env.exception_clear());
+}
+
+#[test]
+pub fn static_fields_can_be_set() {
+    let env = attach_current_thread();
+
+    let class = define_test_class(&env, "jni_test/StaticFields", include_bytes!("java/jni_test/StaticFields.class"));
+
+    unwrap(&env, env.set_static_field(class, "count", "I", JValue::Int(42)));
+    assert_eq!(unwrap(&env, env.get_static_field(class, "count", "I")).i().unwrap(), 42);
+
+    let name = unwrap(&env, env.new_string("changed"));
+    unwrap(&env, env.set_static_field(class, "name", "Ljava/lang/String;", JValue::from(JObject::from(name))));
+    let name = unwrap(&env, env.get_static_field(class, "name", "Ljava/lang/String;")).l().unwrap();
+    let name: String = unwrap(&env, env.get_string(name.into())).into();
+    assert_eq!(name, "changed");
+
+    let err = env.set_static_field(class, "count", "I", JValue::Long(1)).unwrap_err();
+    match *err.kind() {
+        ErrorKind::WrongJValueType(_, _) => {}
+        ref kind => panic!("unexpected error: {}", kind),
+    }
+}
+
+#[test]
+pub fn fields_can_be_read_and_written() {
+    let env = attach_current_thread();
+
+    let key = unwrap(&env, env.new_string("key"));
+    let entry = unwrap(&env, env.new_object(
+        "java/util/AbstractMap$SimpleEntry",
+        "(Ljava/lang/Object;Ljava/lang/Object;)V",
+        &[JValue::from(JObject::from(key)), JValue::from(JObject::null())],
+    ));
+
+    let value = unwrap(&env, env.get_field(entry, "value", "Ljava/lang/Object;")).l().unwrap();
+    assert!(value.is_null());
+
+    let new_value = unwrap(&env, env.new_string("value"));
+    unwrap(&env, env.set_field(entry, "value", "Ljava/lang/Object;", JValue::from(JObject::from(new_value))));
+    let value = unwrap(&env, env.call_method(entry, "getValue", "()Ljava/lang/Object;", &[])).l().unwrap();
+    let value: String = unwrap(&env, env.get_string(value.into())).into();
+    assert_eq!(value, "value");
+
+    let key_field = Cached("java/util/AbstractMap$SimpleEntry", "key", "Ljava/lang/Object;");
+    let read_key = unwrap(&env, unsafe {
+        env.get_field_unsafe(entry, key_field, JavaType::Object("java/lang/Object".into()))
+    }).l().unwrap();
+    assert!(unwrap(&env, env.call_method(read_key, "equals", "(Ljava/lang/Object;)Z", &[JValue::from(JObject::from(key))])).z().unwrap());
+
+    assert!(env.set_field(entry, "value", "Ljava/lang/Object;", JValue::Int(1)).is_err());
+    assert!(env.get_field(JObject::null(), "value", "Ljava/lang/Object;").is_err());
+}
+
+/// Define one of the classes compiled from `tests/java` with the system class
+/// loader.
+fn define_test_class<'a>(env: &'a JNIEnv<'a>, name: &str, bytes: &[u8]) -> JClass<'a> {
+    let loader = unwrap(env, env.call_static_method(
+        "java/lang/ClassLoader",
+        "getSystemClassLoader",
+        "()Ljava/lang/ClassLoader;",
+        &[],
+    )).l().unwrap();
+    unwrap(env, env.define_class(name, loader, bytes))
+}
+
+extern "system" fn native_add(_env: JNIEnv, _class: JClass, a: jint, b: jint) -> jint {
+    a + b
+}
+
+#[test]
+pub fn native_methods_can_be_registered() {
+    let env = attach_current_thread();
+
+    let class = define_test_class(&env, "jni_test/Natives", include_bytes!("java/jni_test/Natives.class"));
+    let add = unwrap(&env, unsafe { NativeMethod::new("add", "(II)I", native_add as *mut c_void) });
+    unwrap(&env, env.register_native_methods(class, &[add]));
+
+    let sum = unwrap(&env, env.call_static_method(class, "add", "(II)I", &[JValue::Int(2), JValue::Int(3)]));
+    assert_eq!(sum.i().unwrap(), 5);
+
+    unwrap(&env, env.unregister_native_methods(class));
+
+    let missing = unwrap(&env, unsafe { NativeMethod::new("missing", "()V", native_add as *mut c_void) });
+    assert!(env.register_native_methods(class, &[missing]).is_err());
+    unwrap(&env, env.exception_clear());
+
+    assert!(unsafe { NativeMethod::new("add", "not a signature", native_add as *mut c_void) }.is_err());
+}
+
+#[test]
+pub fn monitor_guard_holds_lock_until_dropped() {
+    let env = attach_current_thread();
+
+    let obj = unwrap(&env, env.new_object("java/lang/Object", "()V", &[]));
+    let holds_lock = || {
+        unwrap(&env, env.call_static_method(
+            "java/lang/Thread",
+            "holdsLock",
+            "(Ljava/lang/Object;)Z",
+            &[JValue::from(obj)],
+        )).z().unwrap()
+    };
+
+    assert!(!holds_lock());
+    {
+        let _outer = unwrap(&env, env.lock_obj(obj));
+        {
+            let _inner = unwrap(&env, env.lock_obj(obj));
+            assert!(holds_lock());
+        }
+        assert!(holds_lock());
+    }
+    assert!(!holds_lock());
+
+    assert!(env.lock_obj(JObject::null()).is_err());
+}
+
+#[test]
+pub fn pending_exception_is_described_and_kept() {
+    let env = attach_current_thread();
+
+    assert_eq!(unwrap(&env, env.pending_exception()), None);
+
+    unwrap(&env, env.throw_new("java/lang/IllegalStateException", "bad state"));
+    let desc = unwrap(&env, env.pending_exception()).unwrap();
+    assert_eq!(desc.class, "java/lang/IllegalStateException");
+    assert_eq!(desc.msg, "bad state");
+    assert!(unwrap(&env, env.exception_check()));
+
+    let throwable = unwrap(&env, env.exception_occurred());
+    unwrap(&env, env.exception_clear());
+    let desc = unwrap(&env, env.describe_throwable(throwable));
+    assert_eq!(desc.class, "java/lang/IllegalStateException");
+
+    let no_message = unwrap(&env, env.new_object("java/lang/RuntimeException", "()V", &[]));
+    let desc = unwrap(&env, env.describe_throwable(no_message.into()));
+    assert_eq!(desc.msg, "");
+}
+
+#[test]
+pub fn throw_new_with_exception_constant() {
+    let env = attach_current_thread();
+
+    unwrap(&env, env.throw_new(exceptions::ILLEGAL_ARGUMENT_EXCEPTION, "bad argument"));
+    let desc = unwrap(&env, env.pending_exception()).unwrap();
+    assert_eq!(desc.class, exceptions::ILLEGAL_ARGUMENT_EXCEPTION);
+    assert_eq!(desc.msg, "bad argument");
+    unwrap(&env, env.exception_clear());
+
+    unwrap(&env, env.throw_new(exceptions::IO_EXCEPTION, "closed"));
+    assert_pending_exception(&env, exceptions::IO_EXCEPTION);
+}
+
+#[test]
+pub fn call_method_checks_arg_types() {
+    let env = attach_current_thread();
+    let s = unwrap(&env, env.new_string("hello"));
+
+    // an int where the signature has an object
+    let err = env
+        .call_method(s.into(), "equals", "(Ljava/lang/Object;)Z", &[JValue::Int(1)])
+        .unwrap_err();
+    match *err.kind() {
+        ErrorKind::InvalidArgType(0, ref expected, "int") if expected == "Ljava/lang/Object;" => {}
+        ref kind => panic!("unexpected error kind: {:?}", kind),
+    }
+
+    // a long where the signature has an int
+    let err = env
+        .call_static_method("java/lang/Integer", "toString", "(I)Ljava/lang/String;", &[JValue::Long(1)])
+        .unwrap_err();
+    match *err.kind() {
+        ErrorKind::InvalidArgType(0, _, "long") => {}
+        ref kind => panic!("unexpected error kind: {:?}", kind),
+    }
+
+    let ret = unwrap(&env, env.call_method(s.into(), "toUpperCase", "()Ljava/lang/String;", &[]));
+    match *ret.i().unwrap_err().kind() {
+        ErrorKind::WrongJValueType("jint", "object") => {}
+        ref kind => panic!("unexpected error kind: {:?}", kind),
+    }
+    assert!(!unwrap(&env, env.exception_check()));
+}
+
+#[test]
+pub fn jvalue_try_into_rust_types() {
+    let env = attach_current_thread();
+
+    let len: jint = unwrap(&env, JValue::Int(5).try_into());
+    assert_eq!(5, len);
+    let flag: bool = unwrap(&env, JValue::from(true).try_into());
+    assert!(flag);
+    let () = unwrap(&env, JValue::Void.try_into());
+
+    let s = unwrap(&env, env.new_string("hello"));
+    let upper = unwrap(&env, env.call_method(s.into(), "toUpperCase", "()Ljava/lang/String;", &[]));
+    let upper: JObject = unwrap(&env, upper.try_into());
+    assert_eq!("HELLO", String::from(unwrap(&env, env.get_string(upper.into()))));
+
+    let res: Result<jlong, _> = JValue::Int(5).try_into();
+    match *res.unwrap_err().kind() {
+        ErrorKind::WrongJValueType("jlong", "int") => {}
+        ref kind => panic!("unexpected error kind: {:?}", kind),
+    }
+}
+
+#[test]
+pub fn sig_macro_builds_signatures() {
+    assert_eq!(sig!((java.lang.String, int) -> boolean), "(Ljava/lang/String;I)Z");
+    assert_eq!(sig!(() -> void), "()V");
+    assert_eq!(sig!((byte[][], java.lang.Object[], long) -> java.util.List), "([[B[Ljava/lang/Object;J)Ljava/util/List;");
+    assert_eq!(sig!(double[]), "[D");
+
+    let env = attach_current_thread();
+    let s = unwrap(&env, env.new_string("hello"));
+    let prefix = unwrap(&env, env.new_string("he"));
+    let ret = unwrap(&env, env.call_method(s.into(), "startsWith", sig!((java.lang.String) -> boolean), &[JObject::from(prefix).into()]));
+    assert!(ret.z().unwrap());
+}
+
+#[test]
+pub fn created_vm_can_be_found_and_attached() {
+    jvm();
+    assert_eq!(1, JavaVM::list_created_vms().unwrap().len());
+
+    thread::spawn(|| {
+        let vm = JavaVM::singleton().unwrap();
+        let env = vm.attach_current_thread().unwrap();
+        let s = unwrap(&env, env.new_string("attached"));
+        let s: String = unwrap(&env, env.get_string(s)).into();
+        assert_eq!(s, "attached");
+    }).join().unwrap();
+}
+
+#[test]
+pub fn executor_keeps_threads_attached() {
+    let executor = Executor::new(jvm().clone());
+
+    let handles: Vec<_> = (0..4).map(|i| {
+        let executor = executor.clone();
+        thread::spawn(move || {
+            assert!(jvm().get_env().is_err());
+            for _ in 0..2 {
+                let len = executor.with_attached(|env| {
+                    let s = env.new_string(format!("worker {}", i))?;
+                    env.call_method(s.into(), "length", "()I", &[])?.i()
+                }).unwrap();
+                assert_eq!(8, len);
+                assert!(jvm().get_env().is_ok());
+            }
+        })
+    }).collect();
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+}
+
+#[test]
+pub fn cached_env_is_cleared_on_detach() {
+    thread::spawn(|| {
+        assert!(jvm().get_env().is_err());
+        {
+            let guard = jvm().attach_current_thread().unwrap();
+            let env = unsafe { jvm().get_env_cached() }.unwrap();
+            assert_eq!(guard.get_native_interface(), env.get_native_interface());
+            assert_eq!(env.get_native_interface(), jvm().get_env().unwrap().get_native_interface());
+        }
+        assert!(unsafe { jvm().get_env_cached() }.is_err());
+
+        let env = jvm().attach_current_thread_permanently().unwrap();
+        unwrap(&env, env.find_class("java/lang/Object"));
+        assert!(unsafe { jvm().get_env_cached() }.is_ok());
+        unwrap(&env, unsafe { jvm().detach_current_thread() });
+        assert!(unsafe { jvm().get_env_cached() }.is_err());
+    }).join().unwrap();
+}
+
+#[test]
+pub fn get_env_sees_threads_detached_by_others() {
+    thread::spawn(|| {
+        jvm().attach_current_thread_permanently().unwrap();
+        assert!(jvm().get_env().is_ok());
+        // detach without the crate knowing
+        let vm = jvm().get_java_vm_pointer();
+        unsafe { ((**vm).DetachCurrentThread.unwrap())(vm) };
+        assert!(jvm().get_env().is_err());
+    }).join().unwrap();
+}
+
+#[test]
+#[cfg(debug_assertions)]
+pub fn stale_cached_env_is_caught_in_debug_builds() {
+    let res = thread::spawn(|| {
+        jvm().attach_current_thread_permanently().unwrap();
+        // detach without the crate knowing
+        let vm = jvm().get_java_vm_pointer();
+        unsafe { ((**vm).DetachCurrentThread.unwrap())(vm) };
+        let _ = unsafe { jvm().get_env_cached() };
+    }).join();
+    assert!(res.is_err());
+}
+
+#[test]
+pub fn owned_object_is_usable_from_other_threads() {
+    let env = attach_current_thread();
+    let list = unwrap(&env, env.new_object(ARRAYLIST_CLASS, "()V", &[]));
+    let owned = unwrap(&env, OwnedObject::new(&env, list));
+
+    let sent = owned.clone();
+    thread::spawn(move || {
+        let added = sent.with_attached(|env, list| {
+            let item = env.new_string("from another thread")?;
+            env.call_method(list, "add", "(Ljava/lang/Object;)Z", &[JObject::from(item).into()])?.z()
+        }).unwrap();
+        assert!(added);
+    }).join().unwrap();
+
+    let local = unwrap(&env, owned.as_obj(&env));
+    assert_eq!(1, unwrap(&env, env.call_method(local, "size", "()I", &[])).i().unwrap());
+}
+
+#[test]
+pub fn ids_can_be_cached_in_statics() {
+    static SIZE: OnceLock<JMethodID<'static>> = OnceLock::new();
+    static VALUE: OnceLock<JFieldID<'static>> = OnceLock::new();
+
+    thread::spawn(|| {
+        let env = attach_current_thread();
+        let size = unwrap(&env, env.get_method_id(ARRAYLIST_CLASS, "size", "()I"));
+        SIZE.set(size.into_inner().into()).unwrap();
+        let value = unwrap(&env, env.get_field_id("java/lang/Integer", "value", "I"));
+        VALUE.set(value.into_inner().into()).unwrap();
+    }).join().unwrap();
+
+    let env = attach_current_thread();
+    let int = JavaType::Primitive(Primitive::Int);
+
+    let list = unwrap(&env, env.new_object(ARRAYLIST_CLASS, "()V", &[]));
+    let size = unsafe { env.call_method_unsafe(list, *SIZE.get().unwrap(), int.clone(), &[]) };
+    assert_eq!(0, unwrap(&env, size).i().unwrap());
+
+    let boxed = unwrap(&env, env.new_object("java/lang/Integer", "(I)V", &[3.into()]));
+    let value = unsafe { env.get_field_unsafe(boxed, *VALUE.get().unwrap(), int) };
+    assert_eq!(3, unwrap(&env, value).i().unwrap());
+}
+
+#[test]
+pub fn class_cache_finds_classes_once() {
+    static CLASSES: ClassCache = ClassCache::new();
+
+    let env = attach_current_thread();
+    unwrap(&env, CLASSES.preload(&env, &[ARRAYLIST_CLASS, STRING_CLASS]));
+    assert_eq!(2, CLASSES.len());
+    assert!(CLASSES.get("java/util/HashMap").is_none());
+
+    thread::spawn(|| {
+        let env = attach_current_thread();
+        let list = unwrap(&env, env.new_object(CLASSES.class(ARRAYLIST_CLASS), "()V", &[]));
+        assert!(unwrap(&env, env.is_instance_of(list, ARRAYLIST_CLASS)));
+        unwrap(&env, CLASSES.find_class(&env, "java/util/HashMap"));
+    }).join().unwrap();
+
+    assert_eq!(3, CLASSES.len());
+    let first = unwrap(&env, CLASSES.find_class(&env, STRING_CLASS));
+    assert_eq!(first.into_inner(), CLASSES.get(STRING_CLASS).unwrap().into_inner());
+}
+
+#[test]
+pub fn find_class_falls_back_to_app_class_loader() {
+    let env = attach_current_thread();
+    let system = unwrap(&env, env.call_static_method(
+        "java/lang/ClassLoader",
+        "getSystemClassLoader",
+        "()Ljava/lang/ClassLoader;",
+        &[],
+    )).l().unwrap();
+    let urls = unwrap(&env, env.new_object_array(0, "java/net/URL", JObject::null()));
+    let loader = unwrap(&env, env.new_object(
+        "java/net/URLClassLoader",
+        "([Ljava/net/URL;Ljava/lang/ClassLoader;)V",
+        &[JObject::from(urls).into(), system.into()],
+    ));
+    unwrap(&env, env.define_class("jni_test/Point", loader, include_bytes!("java/jni_test/Point.class")));
+
+    assert!(env.find_class("jni_test/Point").is_err());
+    assert_pending_exception(&env, "java/lang/NoClassDefFoundError");
+
+    unwrap(&env, env.set_app_class_loader(loader));
+    let class = unwrap(&env, env.find_class("jni_test/Point"));
+    let found_by = unwrap(&env, env.call_method(class.into(), "getClassLoader", "()Ljava/lang/ClassLoader;", &[]));
+    assert!(unwrap(&env, env.is_instance_of(found_by.l().unwrap(), "java/net/URLClassLoader")));
+
+    // classes no loader knows still fail with FindClass's error
+    assert!(env.find_class("jni_test/Missing").is_err());
+    assert_pending_exception(&env, "java/lang/NoClassDefFoundError");
+
+    unwrap(&env, env.set_app_class_loader(JObject::null()));
+    assert!(env.find_class("jni_test/Point").is_err());
+    unwrap(&env, env.exception_clear());
+}
+
+#[test]
+pub fn define_unnamed_class_takes_name_from_bytecode() {
+    let env = attach_current_thread();
+    let system = unwrap(&env, env.call_static_method(
+        "java/lang/ClassLoader",
+        "getSystemClassLoader",
+        "()Ljava/lang/ClassLoader;",
+        &[],
+    )).l().unwrap();
+    let urls = unwrap(&env, env.new_object_array(0, "java/net/URL", JObject::null()));
+    let loader = unwrap(&env, env.new_object(
+        "java/net/URLClassLoader",
+        "([Ljava/net/URL;Ljava/lang/ClassLoader;)V",
+        &[JObject::from(urls).into(), system.into()],
+    ));
+
+    let class = unwrap(&env, env.define_unnamed_class(loader, include_bytes!("java/jni_test/Person.class")));
+    let name = unwrap(&env, env.call_method(class.into(), "getName", "()Ljava/lang/String;", &[])).l().unwrap();
+    let name: String = unwrap(&env, env.get_string(name.into())).into();
+    assert_eq!(name, "jni_test.Person");
+
+    // defining it again in the same loader is a LinkageError
+    assert!(env.define_class("jni_test/Person", loader, include_bytes!("java/jni_test/Person.class")).is_err());
+    assert_pending_exception(&env, "java/lang/LinkageError");
+}
+
+#[test]
+pub fn reflected_methods_and_fields_round_trip() {
+    let env = attach_current_thread();
+    let int = JavaType::Primitive(Primitive::Int);
+
+    let length_id = unwrap(&env, env.get_method_id(STRING_CLASS, "length", "()I"));
+    let length = unwrap(&env, env.to_reflected_method(STRING_CLASS, length_id));
+    let name = unwrap(&env, env.call_method(length, "getName", "()Ljava/lang/String;", &[])).l().unwrap();
+    let name: String = unwrap(&env, env.get_string(name.into())).into();
+    assert_eq!(name, "length");
+
+    let id = unwrap(&env, env.from_reflected_method(length));
+    let s = unwrap(&env, env.new_string("four"));
+    let len = unsafe { env.call_method_unsafe(s.into(), id, int.clone(), &[]) };
+    assert_eq!(4, unwrap(&env, len).i().unwrap());
+
+    let max_id = unwrap(&env, env.get_static_field_id("java/lang/Integer", "MAX_VALUE", "I"));
+    let max = unwrap(&env, env.to_reflected_static_field("java/lang/Integer", max_id));
+    let id = unwrap(&env, env.from_reflected_static_field(max));
+    let val = unsafe { env.get_static_field_unsafe("java/lang/Integer", id, int.clone()) };
+    assert_eq!(jint::max_value(), unwrap(&env, val).i().unwrap());
+
+    let value_id = unwrap(&env, env.get_field_id("java/lang/Integer", "value", "I"));
+    let value = unwrap(&env, env.to_reflected_field("java/lang/Integer", value_id));
+    let id = unwrap(&env, env.from_reflected_field(value));
+    let boxed = unwrap(&env, env.new_object("java/lang/Integer", "(I)V", &[7.into()]));
+    let val = unsafe { env.get_field_unsafe(boxed, id, int.clone()) };
+    assert_eq!(7, unwrap(&env, val).i().unwrap());
+
+    let value_of_id = unwrap(&env, env.get_static_method_id("java/lang/String", "valueOf", "(I)Ljava/lang/String;"));
+    let value_of = unwrap(&env, env.to_reflected_static_method(STRING_CLASS, value_of_id));
+    let id = unwrap(&env, env.from_reflected_static_method(value_of));
+    let ret = JavaType::Object(STRING_CLASS.to_owned());
+    let s = unsafe { env.call_static_method_unsafe(STRING_CLASS, id, ret, &[12.into()]) };
+    let s: String = unwrap(&env, env.get_string(unwrap(&env, s).l().unwrap().into())).into();
+    assert_eq!(s, "12");
+}
+
+#[test]
+pub fn from_reflected_checks_the_member() {
+    let env = attach_current_thread();
+    let s = unwrap(&env, env.new_string("not a member"));
+    assert!(env.from_reflected_field(s.into()).is_err());
+    assert_pending_exception(&env, "java/lang/ClassCastException");
+
+    let value_id = unwrap(&env, env.get_field_id("java/lang/Integer", "value", "I"));
+    let value = unwrap(&env, env.to_reflected_field("java/lang/Integer", value_id));
+    assert!(env.from_reflected_static_field(value).is_err());
+    assert_pending_exception(&env, "java/lang/IllegalArgumentException");
+
+    let max_id = unwrap(&env, env.get_static_field_id("java/lang/Integer", "MAX_VALUE", "I"));
+    let max = unwrap(&env, env.to_reflected_static_field("java/lang/Integer", max_id));
+    assert!(env.from_reflected_field(max).is_err());
+    assert_pending_exception(&env, "java/lang/IllegalArgumentException");
+    assert!(env.from_reflected_static_method(max).is_err());
+    assert_pending_exception(&env, "java/lang/ClassCastException");
+
+    let length_id = unwrap(&env, env.get_method_id(STRING_CLASS, "length", "()I"));
+    let length = unwrap(&env, env.to_reflected_method(STRING_CLASS, length_id));
+    assert!(env.from_reflected_static_method(length).is_err());
+    assert_pending_exception(&env, "java/lang/IllegalArgumentException");
+    assert!(env.from_reflected_method(value).is_err());
+    assert_pending_exception(&env, "java/lang/ClassCastException");
+
+    let value_of_id = unwrap(&env, env.get_static_method_id(STRING_CLASS, "valueOf", "(I)Ljava/lang/String;"));
+    let value_of = unwrap(&env, env.to_reflected_static_method(STRING_CLASS, value_of_id));
+    assert!(env.from_reflected_method(value_of).is_err());
+    assert_pending_exception(&env, "java/lang/IllegalArgumentException");
+
+    let ctor_id = unwrap(&env, env.get_method_id(ARRAYLIST_CLASS, "<init>", "()V"));
+    let ctor = unwrap(&env, env.to_reflected_method(ARRAYLIST_CLASS, ctor_id));
+    assert_eq!(ctor_id.into_inner(), unwrap(&env, env.from_reflected_method(ctor)).into_inner());
+}
+
+#[test]
+pub fn new_object_by_id_takes_cached_constructors() {
+    let env = attach_current_thread();
+
+    for _ in 0..2 {
+        let ctor = Cached("java/lang/StringBuilder", "<init>", "(Ljava/lang/String;)V");
+        let prefix = unwrap(&env, env.new_string("cached"));
+        let sb = unwrap(&env, env.new_object_by_id("java/lang/StringBuilder", ctor, &[JObject::from(prefix).into()]));
+        let s = unwrap(&env, env.call_method(sb, "toString", "()Ljava/lang/String;", &[])).l().unwrap();
+        let s: String = unwrap(&env, env.get_string(s.into())).into();
+        assert_eq!(s, "cached");
+    }
+
+    let ctor = Cached("java/lang/StringBuilder", "<init>", "(Ljava/lang/String;)V");
+    assert!(env.new_object_by_id("java/lang/StringBuilder", ctor, &[JObject::null().into()]).is_err());
+    assert_pending_exception(&env, "java/lang/NullPointerException");
+}
+
+#[test]
+pub fn call_nonvirtual_method_skips_overrides() {
+    let env = attach_current_thread();
+    let list = unwrap(&env, env.new_object(ARRAYLIST_CLASS, "()V", &[]));
+
+    let to_string = unwrap(&env, env.call_nonvirtual_method(list, "java/lang/Object", "toString", "()Ljava/lang/String;", &[]));
+    let to_string: String = unwrap(&env, env.get_string(to_string.l().unwrap().into())).into();
+    assert!(to_string.starts_with("java.util.ArrayList@"));
+
+    let overridden = unwrap(&env, env.call_method(list, "toString", "()Ljava/lang/String;", &[]));
+    let overridden: String = unwrap(&env, env.get_string(overridden.l().unwrap().into())).into();
+    assert_eq!(overridden, "[]");
+
+    let hash = unwrap(&env, env.call_nonvirtual_method(list, "java/util/AbstractList", "hashCode", "()I", &[]));
+    assert_eq!(1, hash.i().unwrap());
+
+    assert!(env.call_nonvirtual_method(list, "java/lang/Object", "hashCode", "()I", &[JValue::Int(1)]).is_err());
+    // the object must be an instance of the class
+    assert!(env.call_nonvirtual_method(list, "java/lang/String", "hashCode", "()I", &[]).is_err());
+    assert!(!unwrap(&env, env.exception_check()));
+}
+
+#[test]
+pub fn is_same_object_compares_identity() {
+    let env = attach_current_thread();
+    let a = unwrap(&env, env.new_string("same"));
+    let b = unwrap(&env, env.new_string("same"));
+    let global = unwrap(&env, env.new_global_ref(a.into()));
+
+    assert!(unwrap(&env, env.is_same_object(a.into(), global.as_obj())));
+    assert!(!unwrap(&env, env.is_same_object(a.into(), b.into())));
+    assert!(unwrap(&env, env.is_same_object(JObject::null(), JObject::null())));
+    assert!(!unwrap(&env, env.is_same_object(a.into(), JObject::null())));
+}
+
+#[test]
+pub fn type_checks_accept_cached_classes() {
+    let env = attach_current_thread();
+    let list_class = unwrap(&env, env.find_class(ARRAYLIST_CLASS));
+    let global = unwrap(&env, env.new_global_ref(list_class.into()));
+    let list = unwrap(&env, env.new_object(ARRAYLIST_CLASS, "()V", &[]));
+
+    assert!(unwrap(&env, env.is_instance_of(list, &global)));
+    assert!(unwrap(&env, env.is_assignable_from(&global, "java/util/List")));
+    assert!(!unwrap(&env, env.is_assignable_from("java/util/List", &global)));
+}
+
+#[test]
+pub fn cast_to_checks_the_class() {
+    let env = attach_current_thread();
+    let s: JObject = unwrap(&env, env.new_string("cast")).into();
+    let list = unwrap(&env, env.new_object(ARRAYLIST_CLASS, "()V", &[]));
+
+    let js: JString = unwrap(&env, s.cast_to(&env));
+    let js: String = unwrap(&env, env.get_string(js)).into();
+    assert_eq!(js, "cast");
+
+    let jlist = unwrap(&env, list.cast_to::<JList>(&env));
+    unwrap(&env, jlist.add(s));
+    assert_eq!(1, unwrap(&env, jlist.size()));
+
+    match s.cast_to::<JList>(&env) {
+        Err(e) => match *e.kind() {
+            ErrorKind::WrongObjectType(expected, ref actual) => {
+                assert_eq!(expected, "java/util/List");
+                assert_eq!(actual, "java.lang.String");
+            }
+            ref kind => panic!("unexpected error: {:?}", kind),
+        },
+        Ok(_) => panic!("cast of a String to a List succeeded"),
+    }
+    assert!(list.cast_to::<JThrowable>(&env).is_err());
+    assert!(JObject::null().cast_to::<JClass>(&env).is_ok());
+}
+
+fn class_name_of<'a, T: AsRef<JObject<'a>>>(env: &'a JNIEnv<'a>, obj: &T) -> String {
+    let class = unwrap(env, env.get_object_class(*obj.as_ref()));
+    unwrap(env, env.call_string_method(class.into(), "getName", "()Ljava/lang/String;", &[])).unwrap()
+}
+
+#[test]
+pub fn wrappers_convert_to_objects() {
+    let env = attach_current_thread();
+    let s = unwrap(&env, env.new_string("wrapped"));
+    let class = unwrap(&env, env.find_class(STRING_CLASS));
+    let list_obj = unwrap(&env, env.new_object(ARRAYLIST_CLASS, "()V", &[]));
+    let list = unwrap(&env, JList::from_env(&env, list_obj));
+
+    assert_eq!(class_name_of(&env, &s), "java.lang.String");
+    assert_eq!(class_name_of(&env, &class), "java.lang.Class");
+    assert_eq!(class_name_of(&env, &list), "java.util.ArrayList");
+    assert_eq!(class_name_of(&env, &list_obj), "java.util.ArrayList");
+
+    let raw = unwrap(&env, env.new_object_array(1, STRING_CLASS, s.into()));
+    let array = unwrap(&env, JObjectArray::from_env(&env, raw));
+    let array: JObject = array.into();
+    assert_eq!(class_name_of(&env, &array), "[Ljava.lang.String;");
+}
+
+#[test]
+pub fn null_objects_are_rejected() {
+    let env = attach_current_thread();
+
+    let null = JObject::null();
+    assert!(null.is_null());
+    assert!(!unwrap(&env, env.new_object("java/lang/Object", "()V", &[])).is_null());
+
+    fn assert_null_ptr<T>(res: jni::errors::Result<T>) {
+        match *res.err().expect("null should be rejected").kind() {
+            ErrorKind::NullPtr(_) => {}
+            ref kind => panic!("unexpected error kind: {:?}", kind),
+        }
+    }
+
+    assert_null_ptr(env.get_object_class(null));
+    assert_null_ptr(env.call_method(null, "hashCode", "()I", &[]));
+    assert_null_ptr(env.call_nonvirtual_method(null, "java/lang/Object", "hashCode", "()I", &[]));
+    assert_null_ptr(env.get_field(null, "value", "I"));
+
+    let null_class = JClass::from(null);
+    assert_null_ptr(env.throw(JThrowable::from(null)));
+    assert_null_ptr(env.get_superclass(null_class));
+    assert_null_ptr(env.is_assignable_from(null_class, "java/lang/Object"));
+    assert_null_ptr(env.is_instance_of(null, null_class));
+    assert_null_ptr(env.register_native_methods(null_class, &[]));
+    assert_null_ptr(env.unregister_native_methods(null_class));
+    assert_null_ptr(env.get_method_id(null_class, "hashCode", "()I"));
+    assert_null_ptr(env.get_static_method_id(null_class, "valueOf", "(I)Ljava/lang/String;"));
+    assert_null_ptr(env.get_field_id(null_class, "value", "I"));
+    assert_null_ptr(env.get_static_field_id(null_class, "MAX_VALUE", "I"));
+    assert!(!unwrap(&env, env.exception_check()));
+}
+
+#[test]
+pub fn get_object_ref_type_tells_refs_apart() {
+    let env = attach_current_thread();
+
+    let local = unwrap(&env, env.new_object("java/lang/Object", "()V", &[]));
+    assert_eq!(RefType::Local, unwrap(&env, env.get_object_ref_type(local)));
+
+    let global = unwrap(&env, env.new_global_ref(local));
+    assert_eq!(RefType::Global, unwrap(&env, env.get_object_ref_type(global.as_obj())));
+
+    // with debug assertions, storing a local ref in a GlobalRef panics
+    if cfg!(debug_assertions) {
+        let vm = unwrap(&env, env.get_java_vm());
+        let raw = local.into_inner();
+        assert!(std::panic::catch_unwind(move || unsafe { GlobalRef::from_raw(vm, raw) }).is_err());
+    }
+
+    match *env.get_object_ref_type(JObject::null()).unwrap_err().kind() {
+        ErrorKind::NullPtr(_) => {}
+        ref kind => panic!("unexpected error kind: {:?}", kind),
+    }
+}
+
+#[test]
+pub fn lookup_errors_name_the_member() {
+    let env = attach_current_thread();
+
+    match *env.get_method_id(STRING_CLASS, "len", "()I").unwrap_err().kind() {
+        ErrorKind::MethodNotFound(ref class, ref name, ref sig) => {
+            assert_eq!(("java.lang.String", "len", "()I"), (class.as_str(), name.as_str(), sig.as_str()));
+        }
+        ref kind => panic!("unexpected error kind: {:?}", kind),
+    }
+    assert!(!unwrap(&env, env.exception_check()));
+
+    let list = unwrap(&env, env.new_object(ARRAYLIST_CLASS, "()V", &[]));
+    match *env.get_field(list, "length", "I").unwrap_err().kind() {
+        ErrorKind::FieldNotFound(ref class, ref name, ref ty) => {
+            assert_eq!(("java.util.ArrayList", "length", "I"), (class.as_str(), name.as_str(), ty.as_str()));
+        }
+        ref kind => panic!("unexpected error kind: {:?}", kind),
+    }
+    assert!(!unwrap(&env, env.exception_check()));
+
+    let err = env.get_static_field("java/lang/Integer", "MAX", "I").unwrap_err();
+    assert_eq!("Field not found: java.lang.Integer.MAX I", err.to_string());
+}
+
+#[test]
+pub fn java_exceptions_are_captured_in_errors() {
+    let env = attach_current_thread();
+    let list = unwrap(&env, env.new_object(ARRAYLIST_CLASS, "()V", &[]));
+
+    let err = env.call_method(list, "get", "(I)Ljava/lang/Object;", &[JValue::Int(3)]).unwrap_err();
+    match *err.kind() {
+        ErrorKind::JavaException => {}
+        ref kind => panic!("unexpected error kind: {:?}", kind),
+    }
+    assert_pending_exception(&env, "java/lang/IndexOutOfBoundsException");
+    // the details are opt-in
+    assert!(err.exception_details().is_none());
+
+    jni::errors::set_capture_stack_traces(true);
+    let err = env.call_method(list, "get", "(I)Ljava/lang/Object;", &[JValue::Int(3)]).unwrap_err();
+    jni::errors::set_capture_stack_traces(false);
+    // still pending, and still described once cleared
+    assert_pending_exception(&env, "java/lang/IndexOutOfBoundsException");
+
+    let details = err.exception_details().expect("the exception should be captured");
+    assert_eq!("java/lang/IndexOutOfBoundsException", details.exception.class);
+    assert!(details.exception.msg.contains('3'), "{:?}", details.exception.msg);
+    assert!(details.to_string().starts_with("java.lang.IndexOutOfBoundsException: "), "{}", details);
+    assert!(details.stack_trace.contains("java.util.ArrayList.get("), "{}", details.stack_trace);
+
+    // errors that don't come from an exception have none
+    assert!(env.get_method_id(STRING_CLASS, "len", "()I").unwrap_err().exception_details().is_none());
+}
+
+#[test]
+pub fn hash_key_uses_java_equality() {
+    let env = attach_current_thread();
+    let mut map = HashMap::new();
+
+    for _ in 0..3 {
+        let obj = unwrap(&env, env.new_object("java/lang/Integer", "(I)V", &[JValue::Int(7)]));
+        let key = unwrap(&env, JHashKey::new(&env, obj));
+        *map.entry(key).or_insert(0) += 1;
+    }
+
+    assert_eq!(1, map.len());
+    assert_eq!(Some(&3), map.values().next());
+}
+
+#[test]
+pub fn hash_key_clears_exceptions() {
+    let env = attach_current_thread();
+    let list = unwrap(&env, env.new_object(ARRAYLIST_CLASS, "()V", &[]));
+    let elem = unwrap(&env, env.new_string("a"));
+    unwrap(&env, env.call_method(list, "add", "(Ljava/lang/Object;)Z", &[JObject::from(elem).into()]));
+    let sub_list = || {
+        unwrap(&env, env.call_method(list, "subList", "(II)Ljava/util/List;", &[JValue::Int(0), JValue::Int(1)]))
+            .l()
+            .unwrap()
+    };
+    let (sub, other_sub) = (sub_list(), sub_list());
+    // changing the list invalidates its sublists, whose hashCode and equals
+    // then throw ConcurrentModificationException
+    unwrap(&env, env.call_method(list, "add", "(Ljava/lang/Object;)Z", &[JObject::from(elem).into()]));
+
+    let mut map = HashMap::new();
+    map.insert(unwrap(&env, JHashKey::new(&env, sub)), 1);
+    assert!(!unwrap(&env, env.exception_check()));
+
+    // the keys are compared by identity once equals fails
+    let other = unwrap(&env, JHashKey::new(&env, other_sub));
+    assert_eq!(None, map.get(&other));
+    assert!(!unwrap(&env, env.exception_check()));
+}
+
+#[test]
+pub fn call_static_varargs_method_string_format() {
+    let env = attach_current_thread();
+    let fmt = unwrap(&env, env.new_string("%s-%s"));
+    let a = unwrap(&env, env.new_string("foo"));
+    let b = unwrap(&env, env.new_object("java/lang/Integer", "(I)V", &[JValue::Int(3)]));
+
+    let res = unwrap(&env, env.call_static_varargs_method(
+        STRING_CLASS,
+        "format",
+        "(Ljava/lang/String;)Ljava/lang/String;",
+        "java/lang/Object",
+        &[JObject::from(fmt).into()],
+        &[a.into(), b],
+    ));
+    let res: String = unwrap(&env, env.get_string(unwrap(&env, res.l()).into())).into();
+    assert_eq!("foo-3", res);
+
+    // the fixed arguments are checked against the prefix
+    match *env.call_static_varargs_method(
+        STRING_CLASS,
+        "format",
+        "(Ljava/lang/String;)Ljava/lang/String;",
+        "java/lang/Object",
+        &[],
+        &[a.into()],
+    ).unwrap_err().kind() {
+        ErrorKind::InvalidArgList => {}
+        ref kind => panic!("unexpected error kind: {:?}", kind),
+    }
+}
+
+#[test]
+pub fn call_varargs_method_packs_var_args() {
+    let env = attach_current_thread();
+    let a: JObject = unwrap(&env, env.new_string("a")).into();
+    let b: JObject = unwrap(&env, env.new_string("b")).into();
+
+    // String.formatted(Object...) on an instance
+    let fmt: JObject = unwrap(&env, env.new_string("%s+%s")).into();
+    let res = unwrap(&env, env.call_varargs_method(
+        fmt,
+        "formatted",
+        "()Ljava/lang/String;",
+        "java/lang/Object",
+        &[],
+        &[a, b],
+    ));
+    let res: String = unwrap(&env, env.get_string(res.l().unwrap().into())).into();
+    assert_eq!("a+b", res);
+
+    // String.join(CharSequence, CharSequence...) takes a typed array
+    let sep: JObject = unwrap(&env, env.new_string(",")).into();
+    let joined = unwrap(&env, env.call_static_varargs_method(
+        STRING_CLASS,
+        "join",
+        "(Ljava/lang/CharSequence;)Ljava/lang/String;",
+        "java/lang/CharSequence",
+        &[sep.into()],
+        &[a, b],
+    ));
+    let joined: String = unwrap(&env, env.get_string(joined.l().unwrap().into())).into();
+    assert_eq!("a,b", joined);
+}
+
+#[test]
+pub fn propagate_pending_exception_leaves_exception_pending() {
+    let env = attach_current_thread();
+    unwrap(&env, env.propagate_pending_exception());
+
+    let s = unwrap(&env, env.new_string("abc"));
+    let res = env.call_method(s.into(), "substring", "(I)Ljava/lang/String;", &[JValue::Int(5)]);
+    match res {
+        Err(ref e) => match *e.kind() {
+            ErrorKind::JavaException => {}
+            ref kind => panic!("unexpected error kind: {:?}", kind),
+        },
+        Ok(_) => panic!("substring should have thrown"),
+    }
+
+    assert!(env.propagate_pending_exception().is_err());
+    assert!(unwrap(&env, env.exception_check()));
+    unwrap(&env, env.exception_clear());
+}
+
+#[test]
+pub fn failed_call_error_describes_arguments() {
+    let env = attach_current_thread();
+    let list = unwrap(&env, env.new_object(ARRAYLIST_CLASS, "()V", &[]));
+    let elem = JObject::from(unwrap(&env, env.new_string("x")));
+    let args = [JValue::Int(5), elem.into()];
+
+    let context = |e: &jni::errors::Error| e.iter().nth(1).unwrap().to_string();
+
+    let err = env.call_method(list, "add", "(ILjava/lang/Object;)V", &args).unwrap_err();
+    match *err.kind() {
+        ErrorKind::JavaException => {}
+        ref kind => panic!("unexpected error kind: {:?}", kind),
+    }
+    assert_eq!(
+        "call to add(ILjava/lang/Object;)V failed with arguments (5, \"x\")",
+        context(&err)
+    );
+    let throwable = unwrap(&env, env.exception_occurred());
+    unwrap(&env, env.exception_clear());
+    assert!(unwrap(&env, env.is_instance_of(throwable.into(), "java/lang/IndexOutOfBoundsException")));
+
+    jni::errors::set_redact_call_args(true);
+    let err = env.call_method(list, "add", "(ILjava/lang/Object;)V", &args).unwrap_err();
+    jni::errors::set_redact_call_args(false);
+    unwrap(&env, env.exception_clear());
+    assert_eq!(
+        "call to add(ILjava/lang/Object;)V failed with arguments (5, <object>)",
+        context(&err)
+    );
+}
+
+#[test]
+pub fn stack_overflow_is_reported_as_java_error() {
+    let env = attach_current_thread();
+
+    // A list containing itself recurses in hashCode until the stack runs out.
+    let list = unwrap(&env, env.new_object(ARRAYLIST_CLASS, "()V", &[]));
+    unwrap(&env, env.call_method(list, "add", "(Ljava/lang/Object;)Z", &[list.into()]));
+
+    match env.call_method(list, "hashCode", "()I", &[]) {
+        Err(ref e) => match *e.kind() {
+            ErrorKind::JavaError => {}
+            ref kind => panic!("unexpected error kind: {:?}", kind),
+        },
+        Ok(_) => panic!("hashCode should have overflowed the stack"),
+    }
+
+    let throwable = unwrap(&env, env.exception_occurred());
+    unwrap(&env, env.exception_clear());
+    assert!(unwrap(&env, env.is_instance_of(throwable.into(), "java/lang/StackOverflowError")));
+}
+
+#[test]
+pub fn unwrap_result_picks_accessor_by_is_ok() {
+    let env = attach_current_thread();
+    // `Optional` stands in for a result class: `isPresent` picks `get` or `toString`.
+    let value = unwrap(&env, env.new_string("v"));
+    let present = unwrap(&env, env.call_static_method(
+        "java/util/Optional", "of", "(Ljava/lang/Object;)Ljava/util/Optional;", &[JObject::from(value).into()]));
+    let empty = unwrap(&env, env.call_static_method("java/util/Optional", "empty", "()Ljava/util/Optional;", &[]));
+
+    match unwrap(&env, env.unwrap_result(unwrap(&env, present.l()), "isPresent", "get", "toString")) {
+        Ok(v) => assert_eq!("v", String::from(unwrap(&env, env.get_string(v.into())))),
+        Err(_) => panic!("expected Ok"),
+    }
+    match unwrap(&env, env.unwrap_result(unwrap(&env, empty.l()), "isPresent", "get", "toString")) {
+        Ok(_) => panic!("expected Err"),
+        Err(e) => assert_eq!("Optional.empty", String::from(unwrap(&env, env.get_string(e.into())))),
+    }
+
+    // objects of another class are rejected
+    let accessors = unwrap(&env, JResultAccessors::from_env(&env, "java/util/Optional", "isPresent", "get", "toString"));
+    assert!(accessors.unwrap(value.into()).is_err());
+    assert_pending_exception(&env, "java/lang/ClassCastException");
+}
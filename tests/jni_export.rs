@@ -0,0 +1,84 @@
+#![cfg(feature = "invocation")]
+
+extern crate error_chain;
+extern crate jni;
+extern crate jni_macros;
+
+use std::ptr;
+
+use jni::errors::Result;
+use jni::exceptions;
+use jni::objects::{JObject, JString};
+use jni::sys::{jint, jobject};
+use jni::JNIEnv;
+use jni_macros::jni_export;
+
+mod util;
+use util::{attach_current_thread, unwrap};
+
+#[jni_export(class = "jni_test.Exported", name = "repeat")]
+fn repeat(_env: &JNIEnv, s: String, times: jint) -> Result<String> {
+    if times < 0 {
+        return Err("times must not be negative".into());
+    }
+    Ok(s.repeat(times as usize))
+}
+
+#[jni_export(class = "jni_test.Exported")]
+fn byte_sum(bytes: Vec<u8>) -> jint {
+    bytes.iter().map(|&b| b as jint).sum()
+}
+
+#[jni_export(class = "jni_test.Exported")]
+fn first_or_null(items: Option<String>) -> Option<String> {
+    items.and_then(|s| s.split(',').next().map(|s| s.to_owned()))
+}
+
+#[jni_export(class = "jni_test.Exported")]
+fn explode(_flag: bool) {
+    panic!("boom");
+}
+
+fn raw_env(env: &JNIEnv) -> JNIEnv<'static> {
+    unsafe { JNIEnv::from_raw(env.get_native_interface()).unwrap() }
+}
+
+#[test]
+pub fn jni_export_converts_arguments_and_return_values() {
+    let env = attach_current_thread();
+
+    let s = unwrap(&env, env.new_string("ab"));
+    let ret = Java_jni_1test_Exported_repeat(raw_env(&env), ptr::null_mut(), s.into_inner(), 3);
+    assert!(!unwrap(&env, env.exception_check()));
+    let ret: String = unwrap(&env, env.get_string(JString::from(ret))).into();
+    assert_eq!(ret, "ababab");
+
+    let bytes = unwrap(&env, env.byte_array_from_slice(&[1, 2, 3]));
+    assert_eq!(6, Java_jni_1test_Exported_byte_1sum(raw_env(&env), ptr::null_mut(), bytes));
+
+    let null: jobject = JObject::null().into_inner();
+    assert!(Java_jni_1test_Exported_first_1or_1null(raw_env(&env), ptr::null_mut(), null).is_null());
+    let s = unwrap(&env, env.new_string("x,y"));
+    let ret = Java_jni_1test_Exported_first_1or_1null(raw_env(&env), ptr::null_mut(), s.into_inner());
+    let ret: String = unwrap(&env, env.get_string(JString::from(ret))).into();
+    assert_eq!(ret, "x");
+}
+
+#[test]
+pub fn jni_export_throws_errors_and_panics() {
+    let env = attach_current_thread();
+
+    let s = unwrap(&env, env.new_string("ab"));
+    let ret = Java_jni_1test_Exported_repeat(raw_env(&env), ptr::null_mut(), s.into_inner(), -1);
+    assert!(ret.is_null());
+    let desc = unwrap(&env, env.pending_exception()).unwrap();
+    assert_eq!(desc.class, exceptions::RUNTIME_EXCEPTION);
+    assert_eq!(desc.msg, "times must not be negative");
+    unwrap(&env, env.exception_clear());
+
+    Java_jni_1test_Exported_explode(raw_env(&env), ptr::null_mut(), 1);
+    let desc = unwrap(&env, env.pending_exception()).unwrap();
+    assert_eq!(desc.class, exceptions::RUNTIME_EXCEPTION);
+    assert_eq!(desc.msg, "panic in native method: boom");
+    unwrap(&env, env.exception_clear());
+}
@@ -57,3 +57,30 @@ pub fn global_ref_works_in_other_threads() {
             atomic_integer.as_obj(), "getAndSet", "(I)I", &[JValue::from(0)])).i()));
     }
 }
+
+#[test]
+pub fn global_ref_can_be_shared_between_threads() {
+    let env = attach_current_thread();
+    let counter = {
+        let local_ref = AutoLocal::new(&env, unwrap(&env, env.new_object(
+            "java/util/concurrent/atomic/AtomicInteger",
+            "(I)V",
+            &[JValue::from(0)]
+        )));
+        Arc::new(unwrap(&env, env.new_global_ref(local_ref.as_obj())))
+    };
+
+    let handles: Vec<_> = (0..4).map(|_| {
+        let counter = counter.clone();
+        spawn(move || {
+            let env = attach_current_thread();
+            unwrap(&env, env.call_method(counter.as_obj(), "incrementAndGet", "()I", &[]));
+        })
+    }).collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    assert_eq!(4, unwrap(&env, unwrap(&env, env.call_method(
+        counter.as_obj(), "get", "()I", &[])).i()));
+}
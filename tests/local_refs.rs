@@ -0,0 +1,144 @@
+#![cfg(all(feature = "invocation", feature = "local-ref-tracking"))]
+
+#[macro_use]
+extern crate jni;
+extern crate error_chain;
+
+use jni::objects::{ClassByNameCache, JObject, JResultAccessors, JString};
+use jni::sys::jint;
+
+mod util;
+use util::{attach_current_thread, unwrap};
+
+#[test]
+pub fn local_refs_are_counted_per_frame() {
+    let env = attach_current_thread();
+    let start = jni::local_ref_count();
+
+    let s = unwrap(&env, env.new_string("abc"));
+    let upper = unwrap(&env, env.call_method(s.into(), "toUpperCase", "()Ljava/lang/String;", &[]));
+    assert_eq!(start + 2, jni::local_ref_count());
+
+    unwrap(&env, env.delete_local_ref(upper.l().unwrap()));
+    assert_eq!(start + 1, jni::local_ref_count());
+
+    unwrap(&env, env.push_local_frame(16));
+    assert_eq!(0, jni::local_ref_count());
+    let inner = unwrap(&env, env.new_string("def"));
+    assert_eq!(1, jni::local_ref_count());
+    let kept = unwrap(&env, env.pop_local_frame(inner.into()));
+    assert_eq!(start + 2, jni::local_ref_count());
+
+    // a native method body gets its own count
+    let len: jint = native_try!(env, {
+        assert_eq!(0, jni::local_ref_count());
+        let t = env.new_string("native")?;
+        assert_eq!(1, jni::local_ref_count());
+        env.call_method(t.into(), "length", "()I", &[])?.i()
+    });
+    assert_eq!(6, len);
+    assert_eq!(start + 2, jni::local_ref_count());
+
+    unwrap(&env, env.delete_local_ref(kept));
+    unwrap(&env, env.delete_local_ref(JObject::from(s)));
+    assert_eq!(start, jni::local_ref_count());
+}
+
+#[test]
+pub fn result_accessors_free_their_lookups() {
+    let env = attach_current_thread();
+    let empty = unwrap(&env, env.call_static_method("java/util/Optional", "empty", "()Ljava/util/Optional;", &[]))
+        .l()
+        .unwrap();
+    let start = jni::local_ref_count();
+
+    let accessors = unwrap(&env, JResultAccessors::from_env(&env, "java/util/Optional", "isPresent", "get", "toString"));
+    assert_eq!(start, jni::local_ref_count());
+    let err = unwrap(&env, accessors.unwrap(empty)).err().unwrap();
+    unwrap(&env, env.delete_local_ref(err));
+
+    // the second call goes through the cached accessors
+    for _ in 0..2 {
+        let err = unwrap(&env, env.unwrap_result(empty, "isPresent", "get", "toString")).err().unwrap();
+        assert_eq!(start + 1, jni::local_ref_count());
+        unwrap(&env, env.delete_local_ref(err));
+    }
+    assert_eq!(start, jni::local_ref_count());
+}
+
+#[test]
+pub fn thread_lookups_free_their_refs() {
+    let env = attach_current_thread();
+    // the first call looks the class and method ids up
+    unwrap(&env, env.current_thread_name());
+    let start = jni::local_ref_count();
+
+    unwrap(&env, env.current_thread_name());
+    assert_eq!(start, jni::local_ref_count());
+}
+
+#[test]
+pub fn enum_conversions_free_their_refs() {
+    let env = attach_current_thread();
+    let seconds = unwrap(&env, env.get_static_field(
+        "java/util/concurrent/TimeUnit",
+        "SECONDS",
+        "Ljava/util/concurrent/TimeUnit;",
+    )).l().unwrap();
+    let mapping = [("SECONDS", 1), ("MINUTES", 60)];
+    unwrap(&env, env.java_enum_to_rust(seconds, &mapping));
+    let start = jni::local_ref_count();
+
+    assert_eq!(Some(1), unwrap(&env, env.java_enum_to_rust(seconds, &mapping)));
+    assert_eq!(Some(3), unwrap(&env, env.java_enum_to_rust_by_ordinal(seconds, &[0, 1, 2, 3])));
+    assert_eq!(start, jni::local_ref_count());
+}
+
+#[test]
+pub fn casts_free_their_refs() {
+    let env = attach_current_thread();
+    let s: JObject = unwrap(&env, env.new_string("cast")).into();
+    unwrap(&env, s.cast_to::<JString>(&env));
+    let start = jni::local_ref_count();
+
+    unwrap(&env, s.cast_to::<JString>(&env));
+    assert_eq!(start, jni::local_ref_count());
+}
+
+#[test]
+pub fn find_class_fallback_frees_its_refs() {
+    let env = attach_current_thread();
+    let system = unwrap(&env, env.call_static_method(
+        "java/lang/ClassLoader",
+        "getSystemClassLoader",
+        "()Ljava/lang/ClassLoader;",
+        &[],
+    )).l().unwrap();
+    unwrap(&env, env.set_app_class_loader(system));
+    unwrap(&env, env.delete_local_ref(system));
+
+    // neither loader knows the class, so both lookups of the fallback run
+    assert!(env.find_class("jni_test/Missing").is_err());
+    unwrap(&env, env.exception_clear());
+    let start = jni::local_ref_count();
+
+    assert!(env.find_class("jni_test/Missing").is_err());
+    unwrap(&env, env.exception_clear());
+    assert_eq!(start, jni::local_ref_count());
+
+    unwrap(&env, env.set_app_class_loader(JObject::null()));
+}
+
+#[test]
+pub fn class_of_frees_its_refs() {
+    let env = attach_current_thread();
+    let mut cache = ClassByNameCache::new();
+    let s: JObject = unwrap(&env, env.new_string("class")).into();
+    let start = jni::local_ref_count();
+
+    // once for the lookup of the name, once for a cached class
+    for _ in 0..2 {
+        unwrap(&env, env.class_of(s, &mut cache));
+        assert_eq!(start, jni::local_ref_count());
+    }
+}
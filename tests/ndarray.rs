@@ -0,0 +1,52 @@
+#![cfg(all(feature = "invocation", feature = "ndarray"))]
+
+extern crate error_chain;
+extern crate jni;
+extern crate ndarray;
+
+use jni::objects::JObject;
+use ndarray::{arr1, arr2, s};
+
+mod util;
+use util::{attach_current_thread, unwrap};
+
+#[test]
+pub fn double_arrays_read_into_ndarray() {
+    let env = attach_current_thread();
+
+    let array = unwrap(&env, env.new_double_array(3));
+    unwrap(&env, env.set_double_array_region(array, 0, &[1.0, 2.0, 3.0]));
+    let a = unwrap(&env, env.get_double_array_as_array1(array));
+    assert_eq!(vec![1.0, 2.0, 3.0], a.to_vec());
+
+    let matrix = unwrap(&env, env.new_object_array(2, "[D", JObject::null()));
+    for (i, row) in [[1.0, 2.0], [3.0, 4.0]].iter().enumerate() {
+        let inner = unwrap(&env, env.new_double_array(2));
+        unwrap(&env, env.set_double_array_region(inner, 0, row));
+        unwrap(&env, env.set_object_array_element(matrix, i as i32, JObject::from(inner)));
+    }
+    let m = unwrap(&env, env.get_double_array_as_array2(matrix));
+    assert_eq!(arr2(&[[1.0, 2.0], [3.0, 4.0]]), m);
+
+    let short_row = unwrap(&env, env.new_double_array(1));
+    unwrap(&env, env.set_object_array_element(matrix, 1, JObject::from(short_row)));
+    assert!(env.get_double_array_as_array2(matrix).is_err());
+}
+
+#[test]
+pub fn ndarray_written_to_double_arrays() {
+    let env = attach_current_thread();
+
+    let array = unwrap(&env, env.array1_to_double_array(&arr1(&[1.0, 2.0, 3.0])));
+    assert_eq!(vec![1.0, 2.0, 3.0], unwrap(&env, env.get_double_array_as_array1(array)).to_vec());
+
+    let m = arr2(&[[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]);
+    let matrix = unwrap(&env, env.array2_to_double_array_2d(&m));
+    assert_eq!(m, unwrap(&env, env.get_double_array_as_array2(matrix)));
+
+    // strided views are copied too
+    let column = unwrap(&env, env.array1_to_double_array(&m.slice(s![.., 1])));
+    assert_eq!(vec![2.0, 5.0], unwrap(&env, env.get_double_array_as_array1(column)).to_vec());
+    let transposed = unwrap(&env, env.array2_to_double_array_2d(&m.t()));
+    assert_eq!(m.t(), unwrap(&env, env.get_double_array_as_array2(transposed)));
+}
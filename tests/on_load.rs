@@ -0,0 +1,46 @@
+#![cfg(feature = "invocation")]
+
+extern crate error_chain;
+#[macro_use]
+extern crate jni;
+
+use std::ptr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+use jni::sys;
+use jni::JNIVersion;
+
+mod util;
+use util::{jvm, unwrap};
+
+static UNLOADED: AtomicBool = AtomicBool::new(false);
+
+jni_on_load!(version = JNIVersion::V8, store_vm, |vm, _reserved| {
+    let env = vm.get_env()?;
+    env.find_class("java/lang/String")?;
+    Ok(())
+});
+
+jni_on_unload!(|_vm, _reserved| {
+    UNLOADED.store(true, Ordering::SeqCst);
+});
+
+#[test]
+pub fn on_load_stores_vm_until_unloaded() {
+    let vm_ptr = jvm().get_java_vm_pointer();
+    assert!(jni::loaded_java_vm().is_none());
+
+    let _guard = jvm().attach_current_thread().unwrap();
+    assert_eq!(sys::JNI_VERSION_1_8, JNI_OnLoad(vm_ptr, ptr::null_mut()));
+
+    thread::spawn(|| {
+        let vm = jni::loaded_java_vm().unwrap();
+        let env = vm.attach_current_thread().unwrap();
+        unwrap(&env, env.find_class("java/lang/Object"));
+    }).join().unwrap();
+
+    JNI_OnUnload(vm_ptr, ptr::null_mut());
+    assert!(UNLOADED.load(Ordering::SeqCst));
+    assert!(jni::loaded_java_vm().is_none());
+}
@@ -0,0 +1,115 @@
+#![cfg(all(feature = "invocation", feature = "serde"))]
+
+extern crate error_chain;
+extern crate jni;
+#[macro_use]
+extern crate serde_derive;
+
+use jni::jni_serde;
+use jni::objects::{JObject, JValue};
+
+mod util;
+use util::{attach_current_thread, unwrap};
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+struct Uri {
+    scheme: String,
+    host: Option<String>,
+    port: i32,
+    path: String,
+    fragment: Option<String>,
+}
+
+// mirrors the private state of `java.net.InetSocketAddress`
+#[derive(Debug, Deserialize, PartialEq)]
+struct SocketAddress {
+    holder: Holder,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Holder {
+    hostname: String,
+    port: i32,
+}
+
+#[test]
+pub fn java_objects_deserialize_into_structs() {
+    let env = attach_current_thread();
+
+    let s = unwrap(&env, env.new_string("http://example.com:8080/index.html"));
+    let uri = unwrap(&env, env.new_object("java/net/URI", "(Ljava/lang/String;)V", &[JValue::from(JObject::from(s))]));
+    let uri: Uri = unwrap(&env, jni_serde::from_object(&env, uri));
+    assert_eq!(
+        Uri {
+            scheme: "http".into(),
+            host: Some("example.com".into()),
+            port: 8080,
+            path: "/index.html".into(),
+            fragment: None,
+        },
+        uri
+    );
+
+    let host = unwrap(&env, env.new_string("example.com"));
+    let addr = unwrap(&env, env.call_static_method(
+        "java/net/InetSocketAddress",
+        "createUnresolved",
+        "(Ljava/lang/String;I)Ljava/net/InetSocketAddress;",
+        &[JValue::from(JObject::from(host)), JValue::Int(80)],
+    )).l().unwrap();
+    // `holder` is declared on InetSocketAddress, `hostname` and `port` on
+    // its nested holder class
+    let addr: SocketAddress = unwrap(&env, jni_serde::from_object(&env, addr));
+    assert_eq!(Holder { hostname: "example.com".into(), port: 80 }, addr.holder);
+
+    // a field the java class doesn't have
+    #[derive(Debug, Deserialize)]
+    struct Missing {
+        #[allow(dead_code)]
+        nope: i32,
+    }
+    let obj = unwrap(&env, env.new_object("java/lang/Object", "()V", &[]));
+    assert!(jni_serde::from_object::<Missing>(&env, obj).is_err());
+    assert!(!unwrap(&env, env.exception_check()));
+}
+
+#[test]
+pub fn structs_serialize_into_java_objects() {
+    let env = attach_current_thread();
+
+    let uri = Uri {
+        scheme: "https".into(),
+        host: Some("example.com".into()),
+        port: 8443,
+        path: "/a/b".into(),
+        fragment: Some("top".into()),
+    };
+    let class = unwrap(&env, env.find_class("java/net/URI"));
+    let obj = unwrap(&env, jni_serde::to_object(&env, &uri, class));
+    assert_eq!(
+        "https://example.com:8443/a/b#top",
+        unwrap(&env, env.call_method_to_string(obj, "toString", "()Ljava/lang/String;", &[]))
+    );
+    let back: Uri = unwrap(&env, jni_serde::from_object(&env, obj));
+    assert_eq!(uri, back);
+
+    // a string can't go into the int field
+    #[derive(Serialize)]
+    struct BadPort {
+        port: &'static str,
+    }
+    assert!(jni_serde::to_object(&env, &BadPort { port: "80" }, class).is_err());
+}
+
+#[cfg(feature = "local-ref-tracking")]
+#[test]
+pub fn deserializing_frees_its_local_refs() {
+    let env = attach_current_thread();
+    let s = unwrap(&env, env.new_string("http://example.com:8080/index.html"));
+    let uri = unwrap(&env, env.new_object("java/net/URI", "(Ljava/lang/String;)V", &[JValue::from(JObject::from(s))]));
+    // the first call looks the classes and field ids up
+    let _: Uri = unwrap(&env, jni_serde::from_object(&env, uri));
+    let start = jni::local_ref_count();
+    let _: Uri = unwrap(&env, jni_serde::from_object(&env, uri));
+    assert_eq!(start, jni::local_ref_count());
+}
@@ -14,6 +14,7 @@ pub fn jvm() -> &'static Arc<JavaVM> {
             .version(JNIVersion::V8)
             .option("-Xcheck:jni")
             .option("-Xdebug")
+            .property("jni.test.property", "from init args")
             .build()
             .unwrap_or_else(|e| panic!("{}", e.display_chain().to_string()));
 